@@ -0,0 +1,340 @@
+//! Set operations between two whole trees, walking both trees together by `OctantPlacement`.
+//!
+//! Trees follow leaf semantics: octant without children is filled with its data in whole volume
+//! and missing octant is empty space. Where leaf of one tree overlaps branch of other tree, leaf is treated
+//! as if it was subdivided into children with its data, so combined data always comes from overlapping volumes.
+//! Tree without root is empty, when operation leaves no volume, root is removed too. Empty trees are valid operands.
+//!
+//! For `SpatialOctreeBase` use its `base`, when both trees share the same root voxel.
+use super::octant_meta::OctantPlacement;
+use super::octant_storage_trait::{ModifiableOctantStorage, OctantStorage, StorageError, StorageResult};
+use super::octree_base::OctreeBase;
+
+
+/// Octant of right tree, either stored one or implicit child of leaf filled with leaf's data.
+enum RightOctant<'a, OctantId, Data> {
+	Stored(OctantId),
+	Filled(&'a Data)
+}
+
+impl<OctreeStorage> OctreeBase<OctreeStorage>
+where
+	OctreeStorage: ModifiableOctantStorage,
+	OctreeStorage::Data: Clone
+{
+	/// Union of both trees stored into this tree, see `union_in_place`.
+	pub fn union_with<Right, F>(&mut self, other: &OctreeBase<Right>, combine: F) -> StorageResult<()>
+	where
+		Right: OctantStorage<OctantId = OctreeStorage::OctantId, Data = OctreeStorage::Data>,
+		F: FnMut(&OctreeStorage::Data, &OctreeStorage::Data) -> OctreeStorage::Data
+	{
+		union_in_place(self, other, combine)
+	}
+
+	/// Intersection of both trees stored into this tree, see `intersection_in_place`.
+	pub fn intersect_with<Right, F>(&mut self, other: &OctreeBase<Right>, combine: F) -> StorageResult<()>
+	where
+		Right: OctantStorage<OctantId = OctreeStorage::OctantId, Data = OctreeStorage::Data>,
+		F: FnMut(&OctreeStorage::Data, &OctreeStorage::Data) -> OctreeStorage::Data
+	{
+		intersection_in_place(self, other, combine)
+	}
+
+	/// Difference of both trees stored into this tree, see `difference_in_place`.
+	pub fn subtract<Right>(&mut self, other: &OctreeBase<Right>) -> StorageResult<()>
+	where Right: OctantStorage<OctantId = OctreeStorage::OctantId, Data = OctreeStorage::Data> {
+		difference_in_place(self, other)
+	}
+}
+
+/// Create new tree containing volume of both trees.
+///
+/// ## Arguments
+/// * `combine` - Creates data where both trees have octant, called with left data first.
+pub fn union<Storage, F>(left: &OctreeBase<Storage>, right: &OctreeBase<Storage>, combine: F) -> StorageResult<OctreeBase<Storage>>
+where
+	Storage: ModifiableOctantStorage + Default,
+	Storage::Data: Clone,
+	F: FnMut(&Storage::Data, &Storage::Data) -> Storage::Data
+{
+	let mut result: OctreeBase<Storage> = copy_tree(left)?;
+	union_in_place(&mut result, right, combine)?;
+	Ok(result)
+}
+
+/// Create new tree containing only volume shared by both trees.
+///
+/// ## Arguments
+/// * `combine` - Creates data where both trees have octant, called with left data first.
+pub fn intersection<Storage, F>(left: &OctreeBase<Storage>, right: &OctreeBase<Storage>, combine: F) -> StorageResult<OctreeBase<Storage>>
+where
+	Storage: ModifiableOctantStorage + Default,
+	Storage::Data: Clone,
+	F: FnMut(&Storage::Data, &Storage::Data) -> Storage::Data
+{
+	let mut result: OctreeBase<Storage> = copy_tree(left)?;
+	intersection_in_place(&mut result, right, combine)?;
+	Ok(result)
+}
+
+/// Create new tree containing volume of left tree which is not part of right tree, data of left tree is kept.
+pub fn difference<Storage>(left: &OctreeBase<Storage>, right: &OctreeBase<Storage>) -> StorageResult<OctreeBase<Storage>>
+where
+	Storage: ModifiableOctantStorage + Default,
+	Storage::Data: Clone
+{
+	let mut result: OctreeBase<Storage> = copy_tree(left)?;
+	difference_in_place(&mut result, right)?;
+	Ok(result)
+}
+
+/// Add volume of right tree into left tree, branches existing only in right tree are copied.
+pub fn union_in_place<Left, Right, F>(left: &mut OctreeBase<Left>, right: &OctreeBase<Right>, mut combine: F) -> StorageResult<()>
+where
+	Left: ModifiableOctantStorage,
+	Left::Data: Clone,
+	Right: OctantStorage<OctantId = Left::OctantId, Data = Left::Data>,
+	F: FnMut(&Left::Data, &Left::Data) -> Left::Data
+{
+	let left_root_id: Left::OctantId = left.octants.get_root_id();
+	let right_root_id: Right::OctantId = right.octants.get_root_id();
+	let Some(right_root_data) = right.octants.get_octant(&right_root_id) else {
+		return Ok(());
+	};
+	if left.octants.get_octant(&left_root_id).is_none() {
+		left.octants.insert_root(right_root_data.clone());
+		for (right_child_id, child_placement) in right.octants.get_existing_children_with_placement(&right_root_id)? {
+			copy_branch(&mut left.octants, &left_root_id, child_placement, &right.octants, &right_child_id)?;
+		}
+		return Ok(());
+	}
+	union_recursive(&mut left.octants, &left_root_id, &right.octants, RightOctant::Stored(right_root_id), &mut combine)
+}
+
+/// Keep only volume of left tree which is also part of right tree.
+pub fn intersection_in_place<Left, Right, F>(left: &mut OctreeBase<Left>, right: &OctreeBase<Right>, mut combine: F) -> StorageResult<()>
+where
+	Left: ModifiableOctantStorage,
+	Left::Data: Clone,
+	Right: OctantStorage<OctantId = Left::OctantId, Data = Left::Data>,
+	F: FnMut(&Left::Data, &Left::Data) -> Left::Data
+{
+	let left_root_id: Left::OctantId = left.octants.get_root_id();
+	let right_root_id: Right::OctantId = right.octants.get_root_id();
+	if left.octants.get_octant(&left_root_id).is_none() {
+		return Ok(());
+	}
+	if right.octants.get_octant(&right_root_id).is_none() {
+		left.octants.remove_octant(&left_root_id);
+		return Ok(());
+	}
+	intersection_recursive(&mut left.octants, &left_root_id, &right.octants, RightOctant::Stored(right_root_id), &mut combine)
+}
+
+/// Remove volume of right tree from left tree.
+pub fn difference_in_place<Left, Right>(left: &mut OctreeBase<Left>, right: &OctreeBase<Right>) -> StorageResult<()>
+where
+	Left: ModifiableOctantStorage,
+	Left::Data: Clone,
+	Right: OctantStorage<OctantId = Left::OctantId, Data = Left::Data>
+{
+	let left_root_id: Left::OctantId = left.octants.get_root_id();
+	let right_root_id: Right::OctantId = right.octants.get_root_id();
+	if left.octants.get_octant(&left_root_id).is_none() || right.octants.get_octant(&right_root_id).is_none() {
+		return Ok(());
+	}
+	difference_recursive(&mut left.octants, &left_root_id, &right.octants, &right_root_id)
+}
+
+fn copy_tree<Storage>(tree: &OctreeBase<Storage>) -> StorageResult<OctreeBase<Storage>>
+where
+	Storage: ModifiableOctantStorage + Default,
+	Storage::Data: Clone
+{
+	let root_id: Storage::OctantId = tree.octants.get_root_id();
+	let mut copy: OctreeBase<Storage> = OctreeBase::default();
+	let copy_root_id: Storage::OctantId = copy.octants.get_root_id();
+	let Some(root_data) = tree.octants.get_octant(&root_id) else {
+		copy.octants.remove_octant(&copy_root_id);
+		return Ok(copy);
+	};
+	copy.octants.insert_root(root_data.clone());
+	for (child_id, child_placement) in tree.octants.get_existing_children_with_placement(&root_id)? {
+		copy_branch(&mut copy.octants, &copy_root_id, child_placement, &tree.octants, &child_id)?;
+	}
+	Ok(copy)
+}
+
+/// Insert copy of source branch as child of parent.
+fn copy_branch<Target, Source>(target: &mut Target, parent_id: &Target::OctantId, placement: OctantPlacement, source: &Source, source_id: &Source::OctantId) -> StorageResult<()>
+where
+	Target: ModifiableOctantStorage,
+	Target::Data: Clone,
+	Source: OctantStorage<Data = Target::Data>
+{
+	let source_data: Target::Data = source.get_octant(source_id).ok_or(StorageError::InvalidOctantId)?.clone();
+	let (copied_id, _) = target.insert_octant(parent_id, placement, source_data)?;
+	for (source_child_id, child_placement) in source.get_existing_children_with_placement(source_id)? {
+		copy_branch(target, &copied_id, child_placement, source, &source_child_id)?;
+	}
+	Ok(())
+}
+
+/// Children of right octant, `None` when right octant is filled in whole volume.
+fn right_children<Right: OctantStorage>(right: &Right, right_octant: &RightOctant<Right::OctantId, Right::Data>) -> StorageResult<Option<[Option<Right::OctantId>; OctantPlacement::OCTANTS_COUNT]>> {
+	let RightOctant::Stored(right_id) = right_octant else {
+		return Ok(None);
+	};
+	let children = right.get_existing_children_with_placement(right_id)?;
+	if children.is_empty() {
+		return Ok(None);
+	}
+	let mut ordered_children = [None; OctantPlacement::OCTANTS_COUNT];
+	for (child_id, child_placement) in children {
+		ordered_children[child_placement as usize] = Some(child_id);
+	}
+	Ok(Some(ordered_children))
+}
+
+fn right_data<'a, Right: OctantStorage>(right: &'a Right, right_octant: &RightOctant<'a, Right::OctantId, Right::Data>) -> StorageResult<&'a Right::Data> {
+	match right_octant {
+		RightOctant::Stored(right_id) => right.get_octant(right_id).ok_or(StorageError::InvalidOctantId),
+		RightOctant::Filled(data) => Ok(data)
+	}
+}
+
+/// Replace data of left octant with combined data and return original data.
+fn combine_into<Left, F>(left: &mut Left, left_id: &Left::OctantId, right_data: &Left::Data, combine: &mut F) -> StorageResult<Left::Data>
+where
+	Left: ModifiableOctantStorage,
+	F: FnMut(&Left::Data, &Left::Data) -> Left::Data
+{
+	let left_data: &mut Left::Data = left.get_octant_mut(left_id).ok_or(StorageError::InvalidOctantId)?;
+	let combined_data: Left::Data = combine(left_data, right_data);
+	Ok(std::mem::replace(left_data, combined_data))
+}
+
+/// Remove octant, which lost all of its volume, emptied root is removed too, so it doesn't turn into filled leaf.
+fn remove_if_emptied<Left: ModifiableOctantStorage>(left: &mut Left, left_id: &Left::OctantId) -> StorageResult<()> {
+	if left.get_existing_children_with_placement(left_id)?.is_empty() {
+		left.remove_octant(left_id);
+	}
+	Ok(())
+}
+
+fn union_recursive<Left, Right, F>(left: &mut Left, left_id: &Left::OctantId, right: &Right, right_octant: RightOctant<Right::OctantId, Right::Data>, combine: &mut F) -> StorageResult<()>
+where
+	Left: ModifiableOctantStorage,
+	Left::Data: Clone,
+	Right: OctantStorage<OctantId = Left::OctantId, Data = Left::Data>,
+	F: FnMut(&Left::Data, &Left::Data) -> Left::Data
+{
+	let right_octant_data: &Left::Data = right_data(right, &right_octant)?;
+	let original_left_data: Left::Data = combine_into(left, left_id, right_octant_data, combine)?;
+	let left_children = left.get_existing_children_with_placement(left_id)?;
+
+	let Some(right_children) = right_children(right, &right_octant)? else {
+		// right octant fills whole volume, so empty parts of left octant are filled by it
+		if left_children.is_empty() {
+			return Ok(());
+		}
+		let mut left_child_ids = [None; OctantPlacement::OCTANTS_COUNT];
+		for (child_id, child_placement) in left_children {
+			left_child_ids[child_placement as usize] = Some(child_id);
+		}
+		for child_placement in OctantPlacement::OCTANTS_ORDERED {
+			match left_child_ids[child_placement as usize] {
+				Some(left_child_id) => union_recursive(left, &left_child_id, right, RightOctant::Filled(right_octant_data), combine)?,
+				None => {
+					left.insert_octant(left_id, child_placement, right_octant_data.clone())?;
+				}
+			}
+		}
+		return Ok(());
+	};
+
+	if left_children.is_empty() {
+		left.subdivide(left_id, |_| original_left_data.clone())?;
+	}
+	for child_placement in OctantPlacement::OCTANTS_ORDERED {
+		let Some(right_child_id) = right_children[child_placement as usize] else {
+			continue;
+		};
+		match left.get_existing_child(left_id, child_placement) {
+			Ok(left_child_id) => union_recursive(left, &left_child_id, right, RightOctant::Stored(right_child_id), combine)?,
+			Err(StorageError::ChildNotFound(_)) => copy_branch(left, left_id, child_placement, right, &right_child_id)?,
+			Err(error) => return Err(error)
+		}
+	}
+	Ok(())
+}
+
+fn intersection_recursive<Left, Right, F>(left: &mut Left, left_id: &Left::OctantId, right: &Right, right_octant: RightOctant<Right::OctantId, Right::Data>, combine: &mut F) -> StorageResult<()>
+where
+	Left: ModifiableOctantStorage,
+	Left::Data: Clone,
+	Right: OctantStorage<OctantId = Left::OctantId, Data = Left::Data>,
+	F: FnMut(&Left::Data, &Left::Data) -> Left::Data
+{
+	let right_octant_data: &Left::Data = right_data(right, &right_octant)?;
+	let original_left_data: Left::Data = combine_into(left, left_id, right_octant_data, combine)?;
+	let left_children = left.get_existing_children_with_placement(left_id)?;
+
+	let Some(right_children) = right_children(right, &right_octant)? else {
+		// right octant fills whole volume, so left branch is kept with combined data
+		for (left_child_id, _) in left_children {
+			intersection_recursive(left, &left_child_id, right, RightOctant::Filled(right_octant_data), combine)?;
+		}
+		return Ok(());
+	};
+
+	let is_left_leaf: bool = left_children.is_empty();
+	for child_placement in OctantPlacement::OCTANTS_ORDERED {
+		let left_child = match left.get_existing_child(left_id, child_placement) {
+			Ok(left_child_id) => Some(left_child_id),
+			Err(StorageError::ChildNotFound(_)) if is_left_leaf => right_children[child_placement as usize]
+				.map(|_| left.insert_octant(left_id, child_placement, original_left_data.clone()))
+				.transpose()?
+				.map(|(left_child_id, _)| left_child_id),
+			Err(StorageError::ChildNotFound(_)) => None,
+			Err(error) => return Err(error)
+		};
+		match (left_child, right_children[child_placement as usize]) {
+			(Some(left_child_id), Some(right_child_id)) => intersection_recursive(left, &left_child_id, right, RightOctant::Stored(right_child_id), combine)?,
+			(Some(left_child_id), None) => {
+				left.remove_octant(&left_child_id);
+			},
+			(None, _) => ()
+		}
+	}
+	remove_if_emptied(left, left_id)
+}
+
+fn difference_recursive<Left, Right>(left: &mut Left, left_id: &Left::OctantId, right: &Right, right_id: &Right::OctantId) -> StorageResult<()>
+where
+	Left: ModifiableOctantStorage,
+	Left::Data: Clone,
+	Right: OctantStorage<OctantId = Left::OctantId, Data = Left::Data>
+{
+	let Some(right_children) = right_children(right, &RightOctant::Stored(*right_id))? else {
+		// right octant fills whole volume, nothing of left octant is left
+		left.remove_octant(left_id);
+		return Ok(());
+	};
+
+	if left.get_existing_children_with_placement(left_id)?.is_empty() {
+		let left_data: Left::Data = left.get_octant(left_id).ok_or(StorageError::InvalidOctantId)?.clone();
+		left.subdivide(left_id, |_| left_data.clone())?;
+	}
+	for child_placement in OctantPlacement::OCTANTS_ORDERED {
+		let Some(right_child_id) = right_children[child_placement as usize] else {
+			continue;
+		};
+		match left.get_existing_child(left_id, child_placement) {
+			Ok(left_child_id) => difference_recursive(left, &left_child_id, right, &right_child_id)?,
+			Err(StorageError::ChildNotFound(_)) => (),
+			Err(error) => return Err(error)
+		}
+	}
+	remove_if_emptied(left, left_id)
+}
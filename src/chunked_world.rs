@@ -0,0 +1,395 @@
+//! Unbounded world made of spatial octree chunks on regular grid.
+//!
+//! Every chunk is `SpatialSparseOctree` whose root covers one cell of grid, chunk at coordinate `c` spans
+//! `[c * chunk_size, (c + 1) * chunk_size)` on every axis. Chunks are loaded or created lazily by `ChunkLoader`
+//! and handed back to it on eviction, queries are routed across chunk borders and see only loaded chunks.
+use std::collections::HashMap;
+
+use glam::{IVec3, Vec3A};
+
+use super::field_sampling::descend_towards_point_from_storage;
+use super::morton_based_storage::morton_octant_id::MortonOctantId;
+use super::octant_meta::OctantNeighborDirection;
+use super::octant_storage_trait::{OctantStorage, StorageResult};
+use super::root_growth::insert_towards_point;
+use super::spatial_octree_base::existing_children_with_voxel_from_storage;
+use super::voxels::voxel_cube::VolumetricCube;
+use super::{Depth, SpatialSparseOctree};
+
+
+pub type Chunk<Data> = SpatialSparseOctree<Data>;
+
+/// Source of chunks, e.g. procedural generator or reader of saved world.
+pub trait ChunkLoader<Data> {
+	/// Load saved chunk or create new one, root voxel of returned chunk is replaced by `chunk_voxel`.
+	fn load_chunk(&mut self, chunk_coord: IVec3, chunk_voxel: VolumetricCube) -> Chunk<Data>;
+
+	/// Take chunk which is evicted from world, default implementation drops it.
+	fn unload_chunk(&mut self, _chunk_coord: IVec3, _chunk: Chunk<Data>) {}
+}
+
+impl<Data, F> ChunkLoader<Data> for F
+where F: FnMut(IVec3, VolumetricCube) -> Chunk<Data> {
+	fn load_chunk(&mut self, chunk_coord: IVec3, chunk_voxel: VolumetricCube) -> Chunk<Data> {
+		self(chunk_coord, chunk_voxel)
+	}
+}
+
+/// Id of octant inside of world.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ChunkOctantId {
+	pub chunk_coord: IVec3,
+	pub octant_id: MortonOctantId
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+	pub octant: ChunkOctantId,
+	/// Distance from ray origin to point where ray enters octant, zero when origin is inside of it.
+	pub distance: f32
+}
+
+pub struct ChunkedWorld<Data, Loader: ChunkLoader<Data>> {
+	chunk_half_extent: f32,
+	chunks: HashMap<IVec3, Chunk<Data>>,
+	loader: Loader
+}
+
+impl<Data, Loader: ChunkLoader<Data>> ChunkedWorld<Data, Loader> {
+	/// ## Panics
+	/// When `chunk_half_extent` is not positive finite number.
+	pub fn new(chunk_half_extent: f32, loader: Loader) -> Self {
+		assert!(chunk_half_extent.is_finite() && chunk_half_extent > 0.0, "Chunk half extent has to be positive finite number.");
+		ChunkedWorld{
+			chunk_half_extent,
+			chunks: HashMap::new(),
+			loader
+		}
+	}
+
+	pub fn chunk_half_extent(&self) -> f32 {
+		self.chunk_half_extent
+	}
+
+	pub fn loader(&self) -> &Loader {
+		&self.loader
+	}
+
+	pub fn loader_mut(&mut self) -> &mut Loader {
+		&mut self.loader
+	}
+
+	/// Coordinate of chunk containing point, points on border belong to chunk in positive direction.
+	pub fn chunk_coord_at(&self, point: Vec3A) -> IVec3 {
+		(point / (2.0 * self.chunk_half_extent)).floor().as_ivec3()
+	}
+
+	/// Root voxel of chunk at coordinate, whether it is loaded or not.
+	pub fn chunk_voxel(&self, chunk_coord: IVec3) -> VolumetricCube {
+		let center: Vec3A = (chunk_coord.as_vec3a() + 0.5) * (2.0 * self.chunk_half_extent);
+		VolumetricCube::new(center, self.chunk_half_extent)
+	}
+
+	pub fn is_loaded(&self, chunk_coord: IVec3) -> bool {
+		self.chunks.contains_key(&chunk_coord)
+	}
+
+	pub fn loaded_chunk_count(&self) -> usize {
+		self.chunks.len()
+	}
+
+	pub fn loaded_chunks(&self) -> impl Iterator<Item = (IVec3, &Chunk<Data>)> {
+		self.chunks.iter().map(|(chunk_coord, chunk)| (*chunk_coord, chunk))
+	}
+
+	pub fn get_chunk(&self, chunk_coord: IVec3) -> Option<&Chunk<Data>> {
+		self.chunks.get(&chunk_coord)
+	}
+
+	pub fn get_chunk_mut(&mut self, chunk_coord: IVec3) -> Option<&mut Chunk<Data>> {
+		self.chunks.get_mut(&chunk_coord)
+	}
+
+	/// Get loaded chunk, or load it through loader.
+	pub fn chunk_or_load(&mut self, chunk_coord: IVec3) -> &mut Chunk<Data> {
+		let chunk_voxel: VolumetricCube = self.chunk_voxel(chunk_coord);
+		let loader: &mut Loader = &mut self.loader;
+		self.chunks.entry(chunk_coord).or_insert_with(
+			||{
+				let mut chunk: Chunk<Data> = loader.load_chunk(chunk_coord, chunk_voxel);
+				chunk.set_root_voxel(chunk_voxel);
+				chunk
+			}
+		)
+	}
+
+	/// Load all chunks which are closer to anchor than radius.
+	///
+	/// ## Returns
+	/// Number of newly loaded chunks.
+	pub fn load_around(&mut self, anchor: Vec3A, radius: f32) -> usize {
+		let min_coord: IVec3 = self.chunk_coord_at(anchor - radius);
+		let max_coord: IVec3 = self.chunk_coord_at(anchor + radius);
+		let mut loaded_count: usize = 0;
+		for x in min_coord.x..=max_coord.x {
+			for y in min_coord.y..=max_coord.y {
+				for z in min_coord.z..=max_coord.z {
+					let chunk_coord: IVec3 = IVec3::new(x, y, z);
+					if self.is_loaded(chunk_coord) || self.distance_to_chunk(anchor, chunk_coord) > radius {
+						continue;
+					}
+					self.chunk_or_load(chunk_coord);
+					loaded_count += 1;
+				}
+			}
+		}
+		loaded_count
+	}
+
+	/// Hand chunk back to loader.
+	///
+	/// ## Returns
+	/// `false` when chunk wasn't loaded.
+	pub fn unload_chunk(&mut self, chunk_coord: IVec3) -> bool {
+		match self.chunks.remove(&chunk_coord) {
+			Some(chunk) => {
+				self.loader.unload_chunk(chunk_coord, chunk);
+				true
+			},
+			None => false
+		}
+	}
+
+	/// Unload chunks which are farther than `keep_distance` from every anchor, chunks are unloaded in order of coordinates.
+	///
+	/// ## Returns
+	/// Number of unloaded chunks.
+	pub fn evict_far_chunks(&mut self, anchors: &[Vec3A], keep_distance: f32) -> usize {
+		let mut far_chunks: Vec<IVec3> = self.chunks.keys()
+			.copied()
+			.filter(|&chunk_coord| anchors.iter().all(|&anchor| self.distance_to_chunk(anchor, chunk_coord) > keep_distance))
+			.collect();
+		far_chunks.sort_by_key(|chunk_coord| chunk_coord.to_array());
+		for &chunk_coord in &far_chunks {
+			self.unload_chunk(chunk_coord);
+		}
+		far_chunks.len()
+	}
+
+	/// Unload every chunk, chunks are unloaded in order of coordinates.
+	pub fn unload_all(&mut self) {
+		let mut chunk_coords: Vec<IVec3> = self.chunks.keys().copied().collect();
+		chunk_coords.sort_by_key(|chunk_coord| chunk_coord.to_array());
+		for chunk_coord in chunk_coords {
+			self.unload_chunk(chunk_coord);
+		}
+	}
+
+	pub fn get_octant(&self, octant: &ChunkOctantId) -> Option<&Data> {
+		self.chunks.get(&octant.chunk_coord)?.octants().get_octant(&octant.octant_id)
+	}
+
+	pub fn get_octant_mut(&mut self, octant: &ChunkOctantId) -> Option<&mut Data> {
+		self.chunks.get_mut(&octant.chunk_coord)?.octants_mut().get_octant_mut(&octant.octant_id)
+	}
+
+	pub fn get_voxel_by_id(&self, octant: &ChunkOctantId) -> Option<VolumetricCube> {
+		self.chunks.get(&octant.chunk_coord)?.get_voxel_by_id(&octant.octant_id)
+	}
+
+	/// Deepest existing octant containing point, `None` when chunk of point isn't loaded.
+	pub fn octant_at_point(&self, point: Vec3A) -> Option<ChunkOctantId> {
+		let chunk_coord: IVec3 = self.chunk_coord_at(point);
+		let chunk: &Chunk<Data> = self.chunks.get(&chunk_coord)?;
+		let (octant_id, _) = descend_towards_point_in_chunk(chunk, point, Depth::MAX)?;
+		Some(ChunkOctantId{chunk_coord, octant_id})
+	}
+
+	/// Insert data into octant containing point, chunk of point is loaded when needed.
+	///
+	/// ## Arguments
+	/// * `depth` - Depth of inserted octant inside of chunk.
+	/// * `fill_data` - Creates data of missing octants on the way to inserted octant.
+	///
+	/// ## Errors
+	/// Returns `StorageError::OverMaxDepth` when depth is below max depth of chunk storage.
+	pub fn insert_at_point<F>(&mut self, point: Vec3A, depth: Depth, data: Data, mut fill_data: F) -> StorageResult<ChunkOctantId>
+	where F: FnMut(&VolumetricCube) -> Data {
+		let chunk_coord: IVec3 = self.chunk_coord_at(point);
+		let chunk: &mut Chunk<Data> = self.chunk_or_load(chunk_coord);
+		let chunk_voxel: VolumetricCube = *chunk.get_root_voxel();
+		let octant_id: MortonOctantId = insert_towards_point(chunk.octants_mut(), &chunk_voxel, point, depth, data, &mut fill_data)?;
+		Ok(ChunkOctantId{chunk_coord, octant_id})
+	}
+
+	/// Leaves of loaded chunks which intersect axis aligned box, touching counts as intersection.
+	pub fn leaves_in_box(&self, min: Vec3A, max: Vec3A) -> Vec<ChunkOctantId> {
+		let mut leaves: Vec<ChunkOctantId> = Vec::new();
+		let min_coord: IVec3 = self.chunk_coord_at(min);
+		let max_coord: IVec3 = self.chunk_coord_at(max);
+		for x in min_coord.x..=max_coord.x {
+			for y in min_coord.y..=max_coord.y {
+				for z in min_coord.z..=max_coord.z {
+					let chunk_coord: IVec3 = IVec3::new(x, y, z);
+					if let Some(chunk) = self.chunks.get(&chunk_coord) {
+						collect_leaves_in_box(chunk, chunk.get_root_id(), *chunk.get_root_voxel(), min, max, &mut |octant_id| leaves.push(ChunkOctantId{chunk_coord, octant_id}));
+					}
+				}
+			}
+		}
+		leaves
+	}
+
+	/// Find closest leaf of loaded chunks hit by ray whose data passes predicate, chunks are walked in order along ray.
+	///
+	/// ## Arguments
+	/// * `direction` - Direction of ray, doesn't have to be normalized.
+	/// * `max_distance` - Hits farther from origin are ignored.
+	///
+	/// ## Returns
+	/// `None` when nothing was hit or direction is zero.
+	///
+	/// ## Panics
+	/// When `max_distance` is not finite, chunks along ray are walked until `max_distance`.
+	pub fn raycast<F>(&self, origin: Vec3A, direction: Vec3A, max_distance: f32, mut hit_predicate: F) -> Option<RayHit>
+	where F: FnMut(&Data) -> bool {
+		assert!(max_distance.is_finite(), "Ray has to have finite max distance.");
+		let ray: Ray = Ray{
+			origin,
+			direction: direction.try_normalize()?
+		};
+		let chunk_size: f32 = 2.0 * self.chunk_half_extent;
+		let mut chunk_coord: IVec3 = self.chunk_coord_at(origin);
+		let mut step: IVec3 = IVec3::ZERO;
+		let mut next_border_distance: [f32; 3] = [f32::INFINITY; 3];
+		let mut border_step_distance: [f32; 3] = [f32::INFINITY; 3];
+		for axis in 0..3 {
+			if ray.direction[axis] == 0.0 {
+				continue;
+			}
+			step[axis] = if ray.direction[axis] > 0.0 { 1 } else { -1 };
+			let border: i32 = chunk_coord[axis] + (step[axis] > 0) as i32;
+			next_border_distance[axis] = (border as f32 * chunk_size - ray.origin[axis]) / ray.direction[axis];
+			border_step_distance[axis] = chunk_size / ray.direction[axis].abs();
+		}
+
+		loop {
+			if let Some(chunk) = self.chunks.get(&chunk_coord) {
+				let root_voxel: VolumetricCube = *chunk.get_root_voxel();
+				let hit = ray.interval(&root_voxel)
+					.and_then(|interval| raycast_octant(chunk, chunk.get_root_id(), root_voxel, interval, &ray, max_distance, &mut hit_predicate));
+				if let Some((octant_id, distance)) = hit {
+					return Some(RayHit{octant: ChunkOctantId{chunk_coord, octant_id}, distance});
+				}
+			}
+
+			let axis: usize = (0..3)
+				.min_by(|&a, &b| next_border_distance[a].total_cmp(&next_border_distance[b]))
+				.unwrap();
+			if next_border_distance[axis] > max_distance {
+				return None;
+			}
+			chunk_coord[axis] += step[axis];
+			next_border_distance[axis] += border_step_distance[axis];
+		}
+	}
+
+	/// Neighbor of octant in direction, which can lie in other chunk.
+	///
+	/// ## Returns
+	/// Deepest existing octant, not deeper than octant itself, covering space of same sized neighbor,
+	/// `None` when octant doesn't exist or chunk of neighbor isn't loaded.
+	pub fn neighbor(&self, octant: &ChunkOctantId, direction: OctantNeighborDirection) -> Option<ChunkOctantId> {
+		let voxel: VolumetricCube = self.get_voxel_by_id(octant)?;
+		let neighbor_center: Vec3A = voxel.center() + VolumetricCube::get_spatial_neighbor_direction(direction) * (2.0 * voxel.half_extent());
+		let chunk_coord: IVec3 = self.chunk_coord_at(neighbor_center);
+		let chunk: &Chunk<Data> = self.chunks.get(&chunk_coord)?;
+		let (octant_id, _) = descend_towards_point_in_chunk(chunk, neighbor_center, octant.octant_id.compute_depth())?;
+		Some(ChunkOctantId{chunk_coord, octant_id})
+	}
+
+	/// Distance from point to closest point of chunk, zero for points inside.
+	fn distance_to_chunk(&self, point: Vec3A, chunk_coord: IVec3) -> f32 {
+		let chunk_voxel: VolumetricCube = self.chunk_voxel(chunk_coord);
+		((point - chunk_voxel.center()).abs() - chunk_voxel.half_extent())
+			.max(Vec3A::ZERO)
+			.length()
+	}
+}
+
+/// Walk existing octants of chunk towards point, `None` when chunk has no root.
+fn descend_towards_point_in_chunk<Data>(chunk: &Chunk<Data>, point: Vec3A, max_depth: Depth) -> Option<(MortonOctantId, VolumetricCube)> {
+	let root_voxel: &VolumetricCube = chunk.get_root_voxel();
+	// chunk of point is found by rounding, so point can miss its root voxel by rounding error
+	let point: Vec3A = point.clamp(root_voxel.min(), root_voxel.max());
+	descend_towards_point_from_storage(chunk.octants(), root_voxel, point, max_depth)
+}
+
+fn collect_leaves_in_box<Data, F>(chunk: &Chunk<Data>, octant_id: MortonOctantId, voxel: VolumetricCube, min: Vec3A, max: Vec3A, on_leaf: &mut F)
+where F: FnMut(MortonOctantId) {
+	if voxel.min().cmpgt(max).any() || voxel.max().cmplt(min).any() {
+		return;
+	}
+	let children: Vec<(MortonOctantId, VolumetricCube)> = existing_children_with_voxel_from_storage(chunk.octants(), &octant_id, &voxel);
+	if children.is_empty() {
+		on_leaf(octant_id);
+	}
+	for (child_id, child_voxel) in children {
+		collect_leaves_in_box(chunk, child_id, child_voxel, min, max, on_leaf);
+	}
+}
+
+/// Visit children in order in which ray enters them, so first accepted leaf is closest one.
+fn raycast_octant<Data, F>(
+	chunk: &Chunk<Data>,
+	octant_id: MortonOctantId,
+	voxel: VolumetricCube,
+	(entry, exit): (f32, f32),
+	ray: &Ray,
+	max_distance: f32,
+	hit_predicate: &mut F
+) -> Option<(MortonOctantId, f32)>
+where F: FnMut(&Data) -> bool {
+	if exit < 0.0 || entry > max_distance {
+		return None;
+	}
+	let children: Vec<(MortonOctantId, VolumetricCube)> = existing_children_with_voxel_from_storage(chunk.octants(), &octant_id, &voxel);
+	if children.is_empty() {
+		let data: &Data = chunk.octants().get_octant(&octant_id)?;
+		return hit_predicate(data).then_some((octant_id, entry.max(0.0)));
+	}
+	let mut children: Vec<(MortonOctantId, VolumetricCube, (f32, f32))> = children.into_iter()
+		.filter_map(|(child_id, child_voxel)| ray.interval(&child_voxel).map(|interval| (child_id, child_voxel, interval)))
+		.collect();
+	children.sort_by(|(_, _, (a, _)), (_, _, (b, _))| a.total_cmp(b));
+	children.into_iter()
+		.find_map(|(child_id, child_voxel, interval)| raycast_octant(chunk, child_id, child_voxel, interval, ray, max_distance, hit_predicate))
+}
+
+struct Ray {
+	origin: Vec3A,
+	/// Normalized direction.
+	direction: Vec3A
+}
+
+impl Ray {
+	/// Distances at which ray enters and exits voxel, entry can be negative when origin is inside.
+	fn interval(&self, voxel: &VolumetricCube) -> Option<(f32, f32)> {
+		let (min, max) = (voxel.min(), voxel.max());
+		let mut entry: f32 = f32::NEG_INFINITY;
+		let mut exit: f32 = f32::INFINITY;
+		for axis in 0..3 {
+			if self.direction[axis] == 0.0 {
+				if self.origin[axis] < min[axis] || self.origin[axis] > max[axis] {
+					return None;
+				}
+				continue;
+			}
+			let near: f32 = (min[axis] - self.origin[axis]) / self.direction[axis];
+			let far: f32 = (max[axis] - self.origin[axis]) / self.direction[axis];
+			entry = entry.max(near.min(far));
+			exit = exit.min(near.max(far));
+		}
+		(entry <= exit).then_some((entry, exit))
+	}
+}
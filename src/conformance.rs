@@ -0,0 +1,480 @@
+//! Reusable checks certifying that `ModifiableOctantStorage` implementation behaves as storages of this crate.
+//!
+//! Checks panic on first misbehavior, so they are meant to be called from tests of storage implementations.
+//! Every check expects storage with root octant and leaves storage with only root octant.
+//! Data stored by checks is created by `data` function from placement, it must create different data for different placements.
+//!
+//! ## Examples
+//! ```
+//! use modsvo::{conformance, morton_based_storage::hashed_octant_storage::HashedOctantStorage};
+//!
+//! let mut storage = HashedOctantStorage::<u32>::default();
+//! conformance::check_all(&mut storage, |placement| placement as u32);
+//! ```
+use std::fmt::Debug;
+
+use super::octant_meta::OctantPlacement;
+use super::octant_storage_trait::{ModifiableOctantStorage, StorageError};
+use super::octree_base::{breadth_first_search_from_storage, depth_first_search_from_storage, SearchControlFlow, SearchControlFlowResult};
+use super::Depth;
+
+
+/// Run every check of this module.
+pub fn check_all<Storage, F>(storage: &mut Storage, data: F)
+where
+	Storage: ModifiableOctantStorage,
+	Storage::Data: Clone + PartialEq + Debug,
+	F: Fn(OctantPlacement) -> Storage::Data
+{
+	check_modifiable_octant_storage(storage, 1, &data);
+	check_children_order(storage, &data);
+	check_depth_limit(storage, &data);
+	check_removal_cascade(storage, &data);
+	check_remove_octant_and_fill(storage, &data);
+	check_depth_first_search_control_flow(storage, &data);
+	check_breadth_first_search_control_flow(storage, &data);
+}
+
+/// Exercise whole storage interface, repeated `repeat` times on the same storage.
+pub fn check_modifiable_octant_storage<Storage, F>(storage: &mut Storage, repeat: u8, data: F)
+where
+	Storage: ModifiableOctantStorage,
+	Storage::Data: Clone + PartialEq + Debug,
+	F: Fn(OctantPlacement) -> Storage::Data
+{
+	if repeat == 0 {
+		return;
+	}
+	let assigning_function = |assign_to_placement: OctantPlacement|{
+		data(assign_to_placement)
+	};
+
+	let reverse_assigning_function = |assign_to_placement: OctantPlacement|{
+		data(OctantPlacement::OCTANTS_ORDERED[OctantPlacement::OCTANTS_COUNT - 1 - assign_to_placement as usize])
+	};
+
+	assert!(storage.get_max_depth() > 0, "Max depth returned '0', depth and breadth first searches can't work with such storage.");
+	
+	let root_data: &mut Storage::Data = storage.get_octant_mut(&storage.get_root_id())
+		.expect("Root octant not inserted or inserted.");
+	*root_data = data(OctantPlacement::LOWER_BOTTOM_LEFT);
+	
+	let children_check1: [Storage::OctantId; OctantPlacement::OCTANTS_COUNT] = storage.subdivide(&storage.get_root_id(), assigning_function).unwrap();
+
+	OctantPlacement::OCTANTS_ORDERED.iter()
+		.enumerate()
+		.for_each(
+			|(index, &octant_placement)|{
+				let child_data = storage.get_octant(&children_check1[index])
+					.expect("Subdivide failed to create all children.");
+				// this is testing child octant order
+				assert_eq!(*child_data, data(octant_placement));
+			}
+		);
+
+	let children_reverse_check: [Storage::OctantId; OctantPlacement::OCTANTS_COUNT] = storage.subdivide(&storage.get_root_id(), reverse_assigning_function).unwrap();
+
+	OctantPlacement::OCTANTS_ORDERED.iter()
+		.rev()
+		.enumerate()
+		.for_each(
+			|(index, &octant_placement)|{
+				let child_data = storage.get_octant(&children_check1[index])
+					.expect("Subdivide failed to create all children.");
+				// this is testing child octant order
+				assert_eq!(*child_data, data(octant_placement));
+			}
+		);
+	let grand_child: Storage::OctantId = storage.subdivide(
+		&children_reverse_check[OctantPlacement::UPPER_BOTTOM_RIGHT as usize],
+		assigning_function).unwrap()
+		[OctantPlacement::LOWER_TOP_LEFT as usize];
+
+	let first_insert_data: Storage::Data = data(OctantPlacement::LOWER_BOTTOM_LEFT);
+	let Ok((first_insert_child, None)) = storage.insert_octant(&grand_child, OctantPlacement::LOWER_TOP_RIGHT, first_insert_data.clone()) else {
+		panic!("First insert returned data that should not have been added yet.");
+	};
+
+	let second_insert_data: Storage::Data = data(OctantPlacement::UPPER_TOP_RIGHT);
+	let Ok((second_insert_child, Some(old_data))) = storage.insert_octant(&grand_child, OctantPlacement::LOWER_TOP_RIGHT, second_insert_data.clone()) else {
+		panic!("First insert was not successful");
+	};
+	assert_eq!(old_data, first_insert_data);
+
+	let new_data = storage.get_octant(&second_insert_child)
+		.expect("Previous insert did not insert data into persistent storage.");
+
+	assert_eq!(*new_data, second_insert_data);
+
+	if first_insert_child != second_insert_child{
+		panic!("Second insert produced different ID which isn't allowed for usage purposes, when user wants to refer to same data multiple times.");
+	}
+	let changed_data: Storage::Data = data(OctantPlacement::LOWER_TOP_RIGHT);
+	let mut_data = storage.get_octant_mut(&second_insert_child).unwrap();
+	*mut_data = changed_data.clone();
+
+	assert_eq!(storage.get_octant(&second_insert_child), Some(&changed_data));
+	
+	assert_eq!(storage.get_octant_depth(&second_insert_child), Some(3));
+	assert_eq!(storage.get_octant_depth(&grand_child), Some(2));
+	assert_eq!(storage.get_octant_depth(&storage.get_root_id()), Some(0));
+
+
+	let maybe_correct_child = storage.get_existing_child(&grand_child, OctantPlacement::LOWER_TOP_RIGHT)
+		.expect("Wrong child placement when inserting.");
+	
+	if maybe_correct_child != second_insert_child {
+		panic!("Wrong child octant_id.")
+	}
+
+	storage.get_existing_children(&grand_child).unwrap()
+		.iter()
+		.flatten()
+		.for_each(
+			|child_id|{
+				if *child_id != second_insert_child {
+					panic!("Wrong children linking.");
+				}
+			}
+		);
+	
+	storage.get_ancestors_for(&second_insert_child)
+		.expect("Couldn't get ancestors.")
+		.zip(
+			[grand_child, children_reverse_check[OctantPlacement::UPPER_BOTTOM_RIGHT as usize], storage.get_root_id()]
+		)
+		.for_each(
+			|(ancestor_id, reference_id)| {
+				if ancestor_id != reference_id{
+					panic!("Ancestor linking broken.");
+				}
+			}
+		);
+	
+	let child_placement = storage.which_child_of(&children_reverse_check[OctantPlacement::UPPER_BOTTOM_RIGHT as usize], &grand_child)
+		.expect("which_child_of didn't find placement from valid parent_id and child_id.");
+
+	assert_eq!(child_placement as usize, OctantPlacement::LOWER_TOP_LEFT as usize);
+
+	
+	{	
+		let mut next_decent_id = storage.get_root_id();
+	
+		let max_depth = storage.get_max_depth();
+		for depth in  0 ..= (max_depth as u16 +1) {
+			let insertion_result = storage.insert_octant(&next_decent_id, OctantPlacement::UPPER_TOP_LEFT, second_insert_data.clone());
+			match insertion_result {
+				Ok(_) => {},
+				Err(StorageError::OverMaxDepth(max)) => {
+					assert_eq!(max, max_depth);
+					if max_depth as u16 == depth {
+						break;
+					}
+					else {
+						panic!("Depth error was given sooner than max depth was reached {}", max_depth);
+					}
+				},
+				_ => {
+					panic!("Wrong error given.");
+				}
+			}
+			
+			next_decent_id  = insertion_result.unwrap().0;
+
+						
+		}
+		
+	}
+	
+	
+	let grand_grand_children = storage.get_existing_children(&grand_child).unwrap();
+	storage.remove_octant(&grand_child);
+	if storage.get_octant(&grand_child).is_some() {
+		panic!("Removing top octant failed.");
+	}
+
+	assert!(
+		grand_grand_children.iter()
+			.flatten()
+			.all(|child_id| storage.get_octant(child_id).is_none()),
+		"Removing octant's children failed(children accessible after parent removal)."
+	);
+
+	if storage.get_octant(&second_insert_child).is_some() {
+		panic!("Removing children octants failed.");
+	}
+
+	storage.remove_octant(&storage.get_root_id());
+
+	let root_exist = storage.get_octant(&storage.get_root_id());
+	assert_eq!(root_exist, None);
+
+	let root_children_exist = storage.get_existing_children(&storage.get_root_id()).iter()
+		.flatten()
+		.last()
+		.is_some();
+	assert!(!root_children_exist);
+
+
+	let reinserted_root_data: Storage::Data = data(OctantPlacement::UPPER_BOTTOM_LEFT);
+	storage.insert_root(reinserted_root_data.clone());
+	let last_root_data = storage.get_octant(&storage.get_root_id()).unwrap();
+	assert_eq!(reinserted_root_data, *last_root_data);
+
+	check_modifiable_octant_storage(storage, repeat - 1, data);
+
+}
+
+/// Check that children are created, listed and identified in order of `OctantPlacement::OCTANTS_ORDERED`.
+pub fn check_children_order<Storage, F>(storage: &mut Storage, data: F)
+where
+	Storage: ModifiableOctantStorage,
+	Storage::Data: Clone + PartialEq + Debug,
+	F: Fn(OctantPlacement) -> Storage::Data
+{
+	let root_id: Storage::OctantId = storage.get_root_id();
+	let children: [Storage::OctantId; OctantPlacement::OCTANTS_COUNT] = storage.subdivide(&root_id, &data)
+		.expect("Subdividing root failed.");
+	let listed_children = storage.get_existing_children(&root_id)
+		.expect("Listing children of root failed.");
+
+	for (child_index, placement) in OctantPlacement::OCTANTS_ORDERED.into_iter().enumerate() {
+		let child_id: Storage::OctantId = children[child_index];
+		assert_eq!(storage.get_octant(&child_id), Some(&data(placement)), "Child data doesn't belong to its placement.");
+		assert!(listed_children[child_index] == Some(child_id), "get_existing_children is not ordered by OctantPlacement::OCTANTS_ORDERED.");
+		assert!(storage.get_existing_child(&root_id, placement).ok() == Some(child_id), "get_existing_child disagrees with subdivide.");
+		assert_eq!(storage.which_child_of(&root_id, &child_id).ok(), Some(placement), "which_child_of disagrees with subdivide.");
+		assert!(storage.get_parent(&child_id) == Some(root_id), "Parent of child is not root.");
+		assert_eq!(storage.get_octant_depth(&child_id), Some(1));
+	}
+	clear_below_root(storage);
+}
+
+/// Check that octants can be inserted down to `get_max_depth` and no deeper.
+pub fn check_depth_limit<Storage, F>(storage: &mut Storage, data: F)
+where
+	Storage: ModifiableOctantStorage,
+	Storage::Data: Clone + PartialEq + Debug,
+	F: Fn(OctantPlacement) -> Storage::Data
+{
+	let max_depth: Depth = storage.get_max_depth();
+	assert!(max_depth > 0, "Max depth returned '0', searches can't work with such storage.");
+
+	let mut deepest_id: Storage::OctantId = storage.get_root_id();
+	for depth in 1..=max_depth {
+		deepest_id = storage.insert_octant(&deepest_id, OctantPlacement::UPPER_TOP_LEFT, data(OctantPlacement::UPPER_TOP_LEFT))
+			.unwrap_or_else(|_| panic!("Inserting octant at depth {} failed, max depth is {}.", depth, max_depth))
+			.0;
+		assert_eq!(storage.get_octant_depth(&deepest_id), Some(depth));
+	}
+	assert!(
+		matches!(storage.insert_octant(&deepest_id, OctantPlacement::UPPER_TOP_LEFT, data(OctantPlacement::UPPER_TOP_LEFT)), Err(StorageError::OverMaxDepth(depth)) if depth == max_depth),
+		"Inserting below max depth didn't return StorageError::OverMaxDepth with max depth."
+	);
+	assert!(
+		matches!(storage.get_existing_child(&deepest_id, OctantPlacement::UPPER_TOP_LEFT), Err(StorageError::OverMaxDepth(depth)) if depth == max_depth),
+		"Getting child below max depth didn't return StorageError::OverMaxDepth with max depth."
+	);
+	assert!(
+		matches!(storage.get_existing_children(&deepest_id), Err(StorageError::OverMaxDepth(depth)) if depth == max_depth),
+		"Getting children below max depth didn't return StorageError::OverMaxDepth with max depth."
+	);
+	clear_below_root(storage);
+}
+
+/// Check that removal cascades through whole branch and leaves rest of tree untouched.
+pub fn check_removal_cascade<Storage, F>(storage: &mut Storage, data: F)
+where
+	Storage: ModifiableOctantStorage,
+	Storage::Data: Clone + PartialEq + Debug,
+	F: Fn(OctantPlacement) -> Storage::Data
+{
+	let root_id: Storage::OctantId = storage.get_root_id();
+	let children = storage.subdivide(&root_id, &data).expect("Subdividing root failed.");
+	let grand_children = storage.subdivide(&children[0], &data).expect("Subdividing child failed.");
+	let (great_grand_child, _) = storage.insert_octant(&grand_children[7], OctantPlacement::LOWER_BOTTOM_LEFT, data(OctantPlacement::LOWER_BOTTOM_LEFT)).expect("Inserting octant failed.");
+
+	assert_eq!(storage.remove_octant(&children[0]), Some(()), "Removing existing octant failed.");
+	assert_eq!(storage.remove_octant(&children[0]), None, "Removing already removed octant succeeded.");
+	assert!(storage.get_octant(&children[0]).is_none(), "Removed octant is still accessible.");
+	assert!(
+		grand_children.iter().chain([&great_grand_child]).all(|octant_id| storage.get_octant(octant_id).is_none()),
+		"Descendants of removed octant are still accessible."
+	);
+	assert!(
+		matches!(storage.get_existing_child(&root_id, OctantPlacement::OCTANTS_ORDERED[0]), Err(StorageError::ChildNotFound(_))),
+		"Parent still links removed child."
+	);
+	assert!(children[1..].iter().all(|octant_id| storage.get_octant(octant_id).is_some()), "Removal affected siblings.");
+	assert!(storage.get_octant(&root_id).is_some(), "Removal affected parent.");
+	clear_below_root(storage);
+}
+
+/// Check that `remove_octant_and_fill` collects every octant of removed branch with its data.
+pub fn check_remove_octant_and_fill<Storage, F>(storage: &mut Storage, data: F)
+where
+	Storage: ModifiableOctantStorage,
+	Storage::Data: Clone + PartialEq + Debug,
+	F: Fn(OctantPlacement) -> Storage::Data
+{
+	let root_id: Storage::OctantId = storage.get_root_id();
+	let (branch_id, _) = storage.insert_octant(&root_id, OctantPlacement::LOWER_TOP_RIGHT, data(OctantPlacement::LOWER_TOP_RIGHT)).expect("Inserting octant failed.");
+	let children = storage.subdivide(&branch_id, &data).expect("Subdividing octant failed.");
+	let (grand_child, _) = storage.insert_octant(&children[2], OctantPlacement::UPPER_TOP_RIGHT, data(OctantPlacement::UPPER_TOP_RIGHT)).expect("Inserting octant failed.");
+	let mut expected: Vec<(Storage::OctantId, Storage::Data)> = vec![(branch_id, data(OctantPlacement::LOWER_TOP_RIGHT)), (grand_child, data(OctantPlacement::UPPER_TOP_RIGHT))];
+	expected.extend(children.iter().zip(OctantPlacement::OCTANTS_ORDERED).map(|(&child_id, placement)| (child_id, data(placement))));
+
+	let mut collected: Vec<(Storage::OctantId, Storage::Data)> = Vec::new();
+	assert_eq!(storage.remove_octant_and_fill(&branch_id, &mut collected), Some(()), "Removing existing octant failed.");
+	assert_eq!(collected.len(), expected.len(), "Number of collected octants differs from size of removed branch.");
+	assert!(
+		expected.iter().all(|expected_octant| collected.contains(expected_octant)),
+		"Collected octants are missing octant of removed branch or its data."
+	);
+	assert!(expected.iter().all(|(octant_id, _)| storage.get_octant(octant_id).is_none()), "Collected octants are still accessible.");
+
+	let mut nothing_collected: Vec<(Storage::OctantId, Storage::Data)> = Vec::new();
+	assert_eq!(storage.remove_octant_and_fill(&branch_id, &mut nothing_collected), None, "Removing already removed octant succeeded.");
+	assert!(nothing_collected.is_empty());
+	clear_below_root(storage);
+}
+
+/// Check depth first order and `SearchControlFlow` semantics of `depth_first_search_from_storage` on storage.
+pub fn check_depth_first_search_control_flow<Storage, F>(storage: &mut Storage, data: F)
+where
+	Storage: ModifiableOctantStorage,
+	Storage::Data: Clone + PartialEq + Debug,
+	F: Fn(OctantPlacement) -> Storage::Data
+{
+	let root_id: Storage::OctantId = storage.get_root_id();
+	let (children, grand_children) = build_search_tree(storage, &data);
+
+	let mut visited: Vec<(Depth, Storage::OctantId)> = Vec::new();
+	let search_result = depth_first_search_from_storage(
+		storage,
+		&root_id,
+		|depth, octant_id|{
+			assert!(storage.get_octant(octant_id).is_some(), "Visited octant has no data.");
+			visited.push((depth, *octant_id));
+			SearchControlFlow::Continue
+		}
+	).expect("Depth first search failed.");
+	assert!(matches!(search_result, SearchControlFlowResult::Continue(_)));
+	let expected: Vec<(Depth, Storage::OctantId)> = std::iter::once((0, root_id))
+		.chain(children.iter().zip(grand_children.iter()).flat_map(|(&child_id, grand_children)| std::iter::once((1, child_id)).chain(grand_children.iter().map(|&grand_child_id| (2, grand_child_id)))))
+		.collect();
+	assert!(visited == expected, "Depth first search didn't visit octants in depth first order of OctantPlacement::OCTANTS_ORDERED.");
+
+	// skipped octant is visited, but not its children
+	let mut visited_count: usize = 0;
+	depth_first_search_from_storage(
+		storage,
+		&root_id,
+		|_, octant_id|{
+			visited_count += 1;
+			if *octant_id == children[0] { SearchControlFlow::Skip } else { SearchControlFlow::Continue }
+		}
+	).expect("Depth first search failed.");
+	assert_eq!(visited_count, expected.len() - 8, "SearchControlFlow::Skip didn't skip children of octant.");
+
+	let break_id: Storage::OctantId = grand_children[1][1];
+	let mut visited_count: usize = 0;
+	let search_result = depth_first_search_from_storage(
+		storage,
+		&root_id,
+		|_, octant_id|{
+			visited_count += 1;
+			if *octant_id == break_id { SearchControlFlow::Break } else { SearchControlFlow::Continue }
+		}
+	).expect("Depth first search failed.");
+	assert_eq!(visited_count, 13, "SearchControlFlow::Break didn't stop search.");
+	assert!(
+		matches!(search_result, SearchControlFlowResult::Break(octant_id) if octant_id == break_id),
+		"Search didn't return id of octant which stopped it."
+	);
+	clear_below_root(storage);
+}
+
+/// Check breadth first order and `SearchControlFlow` semantics of `breadth_first_search_from_storage` on storage.
+pub fn check_breadth_first_search_control_flow<Storage, F>(storage: &mut Storage, data: F)
+where
+	Storage: ModifiableOctantStorage,
+	Storage::Data: Clone + PartialEq + Debug,
+	F: Fn(OctantPlacement) -> Storage::Data
+{
+	let root_id: Storage::OctantId = storage.get_root_id();
+	let (children, grand_children) = build_search_tree(storage, &data);
+
+	let mut visited: Vec<(Depth, Storage::OctantId)> = Vec::new();
+	breadth_first_search_from_storage(
+		storage,
+		&root_id,
+		|depth, octant_id|{
+			assert!(storage.get_octant(octant_id).is_some(), "Visited octant has no data.");
+			visited.push((depth, *octant_id));
+			SearchControlFlow::Continue
+		}
+	).expect("Breadth first search failed.");
+	let expected: Vec<(Depth, Storage::OctantId)> = std::iter::once((0, root_id))
+		.chain(children.iter().map(|&child_id| (1, child_id)))
+		.chain(grand_children.iter().flatten().map(|&grand_child_id| (2, grand_child_id)))
+		.collect();
+	assert!(visited == expected, "Breadth first search didn't visit octants by depth in order of OctantPlacement::OCTANTS_ORDERED.");
+
+	let mut visited_count: usize = 0;
+	let search_result = breadth_first_search_from_storage(
+		storage,
+		&root_id,
+		|depth, _|{
+			visited_count += 1;
+			if depth == 1 { SearchControlFlow::Skip } else { SearchControlFlow::Continue }
+		}
+	).expect("Breadth first search failed.");
+	assert_eq!(visited_count, 9, "SearchControlFlow::Skip didn't skip children of octants.");
+	assert!(matches!(search_result, SearchControlFlowResult::Skip(_)));
+
+	let break_id: Storage::OctantId = children[3];
+	let mut visited_count: usize = 0;
+	let search_result = breadth_first_search_from_storage(
+		storage,
+		&root_id,
+		|_, octant_id|{
+			visited_count += 1;
+			if *octant_id == break_id { SearchControlFlow::Break } else { SearchControlFlow::Continue }
+		}
+	).expect("Breadth first search failed.");
+	assert_eq!(visited_count, 5, "SearchControlFlow::Break didn't stop search.");
+	assert!(
+		matches!(search_result, SearchControlFlowResult::Break(octant_id) if octant_id == break_id),
+		"Search didn't return id of octant which stopped it."
+	);
+	clear_below_root(storage);
+}
+
+/// Subdivide root and all of its children, returns children of root and their children in order of placement.
+fn build_search_tree<Storage, F>(storage: &mut Storage, data: &F) -> SearchTreeIds<Storage::OctantId>
+where
+	Storage: ModifiableOctantStorage,
+	F: Fn(OctantPlacement) -> Storage::Data
+{
+	let root_id: Storage::OctantId = storage.get_root_id();
+	let children = storage.subdivide(&root_id, data).expect("Subdividing root failed.");
+	let grand_children = children.iter()
+		.map(|child_id| storage.subdivide(child_id, data).expect("Subdividing child failed."))
+		.collect();
+	(children, grand_children)
+}
+
+/// Children of root and their children.
+type SearchTreeIds<OctantId> = ([OctantId; OctantPlacement::OCTANTS_COUNT], Vec<[OctantId; OctantPlacement::OCTANTS_COUNT]>);
+
+fn clear_below_root<Storage>(storage: &mut Storage)
+where
+	Storage: ModifiableOctantStorage,
+	Storage::Data: Clone
+{
+	let root_id: Storage::OctantId = storage.get_root_id();
+	let root_data: Storage::Data = storage.get_octant(&root_id).expect("Storage has no root.").clone();
+	storage.remove_octant(&root_id);
+	storage.insert_root(root_data);
+}
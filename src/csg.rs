@@ -0,0 +1,274 @@
+//! Constructive solid geometry edits, filling and carving shapes into spatial octree.
+//!
+//! Edits follow leaf semantics: octant without children is filled with its data in whole volume
+//! and missing octant is empty space. Tree without root is empty, carving all of its volume removes root too
+//! and filling into empty tree creates root again.
+use glam::Vec3A;
+
+use super::octant_meta::OctantPlacement;
+use super::octant_storage_trait::{ModifiableOctantStorage, StorageError, StorageResult};
+use super::spatial_octree_base::SpatialOctreeBase;
+use super::voxel_trait::Voxel;
+use super::voxels::voxel_cube::{SpatialRelation, VolumetricCube};
+use super::Depth;
+
+
+/// Shape which can be filled into or carved out of spatial octree.
+pub trait CsgShape {
+	/// Relation of voxel to shape, `Contained` means voxel is whole inside of shape.
+	fn relation_to_voxel(&self, voxel: &VolumetricCube) -> SpatialRelation;
+
+	/// Decides octants on boundary of shape at target depth.
+	fn contains_point(&self, point: Vec3A) -> bool;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sphere {
+	pub origin: Vec3A,
+	pub radius: f32
+}
+
+impl Sphere {
+	pub fn new(origin: Vec3A, radius: f32) -> Self {
+		Sphere{
+			origin,
+			radius
+		}
+	}
+}
+
+impl CsgShape for Sphere {
+	fn relation_to_voxel(&self, voxel: &VolumetricCube) -> SpatialRelation {
+		voxel.collides_with_sphere(self.origin, self.radius)
+	}
+
+	fn contains_point(&self, point: Vec3A) -> bool {
+		point.distance_squared(self.origin) <= self.radius * self.radius
+	}
+}
+
+impl CsgShape for VolumetricCube {
+	fn relation_to_voxel(&self, voxel: &VolumetricCube) -> SpatialRelation {
+		voxel.collides_with_box(self)
+	}
+
+	fn contains_point(&self, point: Vec3A) -> bool {
+		VolumetricCube::contains_point(self, point)
+	}
+}
+
+/// What happens with volume covered by shape.
+#[derive(Clone, Debug)]
+pub enum CsgOperation<Data> {
+	/// Covered volume is set to data.
+	Fill(Data),
+	/// Covered volume is removed.
+	Carve
+}
+
+impl<Storage> SpatialOctreeBase<Storage, VolumetricCube>
+where
+	Storage: ModifiableOctantStorage,
+	Storage::Data: Clone
+{
+	/// Fill volume of shape with data, subdividing along boundary of shape up to `target_depth`.
+	///
+	/// Subtrees fully covered by shape are replaced with single octant, boundary octants at `target_depth`
+	/// are filled when their center is inside of shape.
+	///
+	/// ## Errors
+	/// Returns `StorageError::OverMaxDepth` when `target_depth` exceeds depth supported by storage.
+	pub fn fill_shape<S: CsgShape>(&mut self, shape: &S, target_depth: Depth, data: Storage::Data) -> StorageResult<()> {
+		self.apply_shape(shape, target_depth, &CsgOperation::Fill(data))
+	}
+
+	/// Remove volume of shape, subdividing along boundary of shape up to `target_depth`.
+	///
+	/// Subtrees fully covered by shape are removed with `remove_octant`, when no volume is left root is removed too,
+	/// so tree becomes empty.
+	///
+	/// ## Errors
+	/// Returns `StorageError::OverMaxDepth` when `target_depth` exceeds depth supported by storage.
+	pub fn carve_shape<S: CsgShape>(&mut self, shape: &S, target_depth: Depth) -> StorageResult<()> {
+		self.apply_shape(shape, target_depth, &CsgOperation::Carve)
+	}
+
+	pub fn apply_shape<S: CsgShape>(&mut self, shape: &S, target_depth: Depth, operation: &CsgOperation<Storage::Data>) -> StorageResult<()> {
+		let root_id: Storage::OctantId = self.get_root_id();
+		let root_voxel: VolumetricCube = *self.get_root_voxel();
+		apply_shape_from_storage(self.octants_mut(), &root_id, &root_voxel, shape, target_depth, operation)
+	}
+}
+
+/// Apply fill or carve of shape to branch starting at octant, `target_depth` is absolute depth in tree.
+pub fn apply_shape_from_storage<Storage, S>(
+	storage: &mut Storage,
+	octant_id: &Storage::OctantId,
+	octant_voxel: &VolumetricCube,
+	shape: &S,
+	target_depth: Depth,
+	operation: &CsgOperation<Storage::Data>
+) -> StorageResult<()>
+where
+	Storage: ModifiableOctantStorage,
+	Storage::Data: Clone,
+	S: CsgShape
+{
+	let max_depth: Depth = storage.get_max_depth();
+	if target_depth > max_depth {
+		return Err(StorageError::OverMaxDepth(max_depth));
+	}
+	let Some(depth) = storage.get_octant_depth(octant_id) else {
+		// tree without root is empty, there is nothing to carve
+		if *octant_id != storage.get_root_id() {
+			return Err(StorageError::InvalidOctantId);
+		}
+		return match operation {
+			CsgOperation::Fill(data) => fill_empty_root(storage, octant_voxel, shape, target_depth, data),
+			CsgOperation::Carve => Ok(())
+		};
+	};
+	apply_shape_recursive(storage, depth, octant_id, octant_voxel, shape, target_depth, operation)
+}
+
+fn is_covered<S: CsgShape>(shape: &S, depth: Depth, voxel: &VolumetricCube, target_depth: Depth) -> Option<bool> {
+	match shape.relation_to_voxel(voxel) {
+		SpatialRelation::Separate => Some(false),
+		SpatialRelation::Contained => Some(true),
+		SpatialRelation::Intersecting if depth >= target_depth => Some(shape.contains_point(voxel.center())),
+		SpatialRelation::Intersecting => None
+	}
+}
+
+fn remove_children<Storage: ModifiableOctantStorage>(storage: &mut Storage, octant_id: &Storage::OctantId) -> StorageResult<()> {
+	for child_id in storage.get_existing_children(octant_id)?.into_iter().flatten() {
+		storage.remove_octant(&child_id);
+	}
+	Ok(())
+}
+
+fn has_children<Storage: ModifiableOctantStorage>(storage: &Storage, octant_id: &Storage::OctantId) -> bool {
+	storage.get_existing_children(octant_id)
+		.map(|children| children.iter().any(Option::is_some))
+		.unwrap_or(false)
+}
+
+/// Apply shape to existing octant.
+fn apply_shape_recursive<Storage, S>(
+	storage: &mut Storage,
+	depth: Depth,
+	octant_id: &Storage::OctantId,
+	voxel: &VolumetricCube,
+	shape: &S,
+	target_depth: Depth,
+	operation: &CsgOperation<Storage::Data>
+) -> StorageResult<()>
+where
+	Storage: ModifiableOctantStorage,
+	Storage::Data: Clone,
+	S: CsgShape
+{
+	match is_covered(shape, depth, voxel, target_depth) {
+		Some(false) => return Ok(()),
+		Some(true) => {
+			remove_children(storage, octant_id)?;
+			match operation {
+				CsgOperation::Fill(data) => {
+					let octant_data = storage.get_octant_mut(octant_id).ok_or(StorageError::InvalidOctantId)?;
+					*octant_data = data.clone();
+				},
+				CsgOperation::Carve => {
+					storage.remove_octant(octant_id);
+				}
+			}
+			return Ok(());
+		},
+		None => ()
+	}
+
+	// leaf is filled in whole volume, so its children inherit its data before part of it is edited
+	if !has_children(storage, octant_id) {
+		let leaf_data: Storage::Data = storage.get_octant(octant_id).ok_or(StorageError::InvalidOctantId)?.clone();
+		storage.subdivide(octant_id, |_| leaf_data.clone())?;
+	}
+
+	for child_placement in OctantPlacement::OCTANTS_ORDERED {
+		let child_voxel: VolumetricCube = voxel.make_sub_voxel(child_placement);
+		match storage.get_existing_child(octant_id, child_placement) {
+			Ok(child_id) => apply_shape_recursive(storage, depth + 1, &child_id, &child_voxel, shape, target_depth, operation)?,
+			Err(StorageError::ChildNotFound(_)) => if let CsgOperation::Fill(data) = operation {
+				fill_empty_recursive(storage, depth + 1, octant_id, child_placement, &child_voxel, shape, target_depth, data)?;
+			},
+			Err(error) => return Err(error)
+		}
+	}
+
+	// carving all children leaves octant without content, it must not turn into filled leaf, not even root
+	if matches!(operation, CsgOperation::Carve) && !has_children(storage, octant_id) {
+		storage.remove_octant(octant_id);
+	}
+	Ok(())
+}
+
+/// Fill shape into empty tree, root is created only when shape covers part of its volume.
+fn fill_empty_root<Storage, S>(storage: &mut Storage, root_voxel: &VolumetricCube, shape: &S, target_depth: Depth, data: &Storage::Data) -> StorageResult<()>
+where
+	Storage: ModifiableOctantStorage,
+	Storage::Data: Clone,
+	S: CsgShape
+{
+	let is_root_covered: Option<bool> = is_covered(shape, 0, root_voxel, target_depth);
+	if is_root_covered == Some(false) {
+		return Ok(());
+	}
+	storage.insert_root(data.clone());
+	if is_root_covered.is_none() {
+		let root_id: Storage::OctantId = storage.get_root_id();
+		for child_placement in OctantPlacement::OCTANTS_ORDERED {
+			let child_voxel: VolumetricCube = root_voxel.make_sub_voxel(child_placement);
+			fill_empty_recursive(storage, 1, &root_id, child_placement, &child_voxel, shape, target_depth, data)?;
+		}
+		if !has_children(storage, &root_id) {
+			storage.remove_octant(&root_id);
+		}
+	}
+	Ok(())
+}
+
+/// Fill shape into empty space where octant doesn't exist yet.
+#[allow(clippy::too_many_arguments)]
+fn fill_empty_recursive<Storage, S>(
+	storage: &mut Storage,
+	depth: Depth,
+	parent_id: &Storage::OctantId,
+	placement: OctantPlacement,
+	voxel: &VolumetricCube,
+	shape: &S,
+	target_depth: Depth,
+	data: &Storage::Data
+) -> StorageResult<()>
+where
+	Storage: ModifiableOctantStorage,
+	Storage::Data: Clone,
+	S: CsgShape
+{
+	match is_covered(shape, depth, voxel, target_depth) {
+		Some(false) => Ok(()),
+		Some(true) => {
+			storage.insert_octant(parent_id, placement, data.clone())?;
+			Ok(())
+		},
+		None => {
+			let (octant_id, _) = storage.insert_octant(parent_id, placement, data.clone())?;
+			for child_placement in OctantPlacement::OCTANTS_ORDERED {
+				let child_voxel: VolumetricCube = voxel.make_sub_voxel(child_placement);
+				fill_empty_recursive(storage, depth + 1, &octant_id, child_placement, &child_voxel, shape, target_depth, data)?;
+			}
+			// shape only touched this octant without covering any part of it
+			if !has_children(storage, &octant_id) {
+				storage.remove_octant(&octant_id);
+			}
+			Ok(())
+		}
+	}
+}
@@ -0,0 +1,120 @@
+//! Top-down inheritance of data from parents to children, so uniform regions can be held by single octant.
+//!
+//! Missing octant inherits data of its nearest existing ancestor, `sample_at` reads data this way
+//! and `push_down` turns inherited data into real children when region stops being uniform.
+use super::morton_based_storage::morton_octant_id::MortonOctantId;
+use super::octant_meta::OctantPlacement;
+use super::octant_storage_trait::{ModifiableOctantStorage, OctantStorage, StorageError, StorageResult};
+use super::octree_base::OctreeBase;
+
+
+impl<Storage: OctantStorage<OctantId = MortonOctantId>> OctreeBase<Storage> {
+	/// Data of octant, or data inherited from its nearest existing ancestor, see `sample_at_from_storage`.
+	pub fn sample_at(&self, octant_id: &MortonOctantId) -> Option<&Storage::Data> {
+		sample_at_from_storage(&self.octants, octant_id)
+	}
+
+	/// Octant itself or its nearest existing ancestor, see `inheritance_source_from_storage`.
+	pub fn inheritance_source(&self, octant_id: &MortonOctantId) -> Option<MortonOctantId> {
+		inheritance_source_from_storage(&self.octants, octant_id)
+	}
+}
+
+impl<Storage: ModifiableOctantStorage> OctreeBase<Storage> {
+	/// Create missing children from data of octant, see `push_down_from_storage`.
+	pub fn push_down<F>(&mut self, octant_id: &Storage::OctantId, split_func: F) -> StorageResult<[Storage::OctantId; OctantPlacement::OCTANTS_COUNT]>
+	where F: FnMut(&Storage::Data, OctantPlacement) -> Storage::Data {
+		push_down_from_storage(&mut self.octants, octant_id, split_func)
+	}
+}
+
+impl<Storage: ModifiableOctantStorage<OctantId = MortonOctantId>> OctreeBase<Storage> {
+	/// Create octant from data inherited by it, see `materialize_from_storage`.
+	pub fn materialize<F>(&mut self, octant_id: &MortonOctantId, split_func: F) -> StorageResult<MortonOctantId>
+	where F: FnMut(&Storage::Data, OctantPlacement) -> Storage::Data {
+		materialize_from_storage(&mut self.octants, octant_id, split_func)
+	}
+}
+
+/// Find octant which holds data of given octant, walking `MortonParentIdIterator` when octant doesn't exist.
+///
+/// ## Returns
+///  `Some(MortonOctantId)` of octant itself when it exists, otherwise of its nearest existing ancestor.
+///  `None` when storage has no root.
+pub fn inheritance_source_from_storage<Storage>(storage: &Storage, octant_id: &MortonOctantId) -> Option<MortonOctantId>
+where Storage: OctantStorage<OctantId = MortonOctantId> {
+	if !octant_id.is_valid() {
+		return None;
+	}
+	std::iter::once(*octant_id)
+		.chain(octant_id.parent_id_iter())
+		.find(|candidate_id| storage.get_octant(candidate_id).is_some())
+}
+
+/// Read data of octant, missing octant inherits data of its nearest existing ancestor.
+///
+/// ## Returns
+///  `None` when storage has no root or id isn't valid.
+pub fn sample_at_from_storage<'a, Storage>(storage: &'a Storage, octant_id: &MortonOctantId) -> Option<&'a Storage::Data>
+where Storage: OctantStorage<OctantId = MortonOctantId> {
+	let source_id: MortonOctantId = inheritance_source_from_storage(storage, octant_id)?;
+	storage.get_octant(&source_id)
+}
+
+/// Create missing children of octant from its data, existing children are kept as they are.
+/// Plain inheritance is `|data, _| data.clone()`, which keeps `sample_at` of all children unchanged.
+///
+/// ## Returns
+///  `StorageResult` with ids of all children.
+/// ## Errors
+///   * InvalidOctantId - when octant doesn't exist
+///   * OverMaxDepth - when octant lies at maximum depth
+pub fn push_down_from_storage<Storage: ModifiableOctantStorage, F>(storage: &mut Storage, octant_id: &Storage::OctantId, mut split_func: F) -> StorageResult<[Storage::OctantId; OctantPlacement::OCTANTS_COUNT]>
+where F: FnMut(&Storage::Data, OctantPlacement) -> Storage::Data {
+	let parent_data: &Storage::Data = storage.get_octant(octant_id).ok_or(StorageError::InvalidOctantId)?;
+	let mut children_data: [Option<Storage::Data>; OctantPlacement::OCTANTS_COUNT] = Default::default();
+	for placement in OctantPlacement::OCTANTS_ORDERED {
+		match storage.get_existing_child(octant_id, placement) {
+			Ok(_) => {},
+			Err(StorageError::ChildNotFound(_)) => children_data[placement as usize] = Some(split_func(parent_data, placement)),
+			Err(error) => return Err(error)
+		}
+	}
+
+	let mut children_ids: [Option<Storage::OctantId>; OctantPlacement::OCTANTS_COUNT] = [None; OctantPlacement::OCTANTS_COUNT];
+	for (placement, child_data) in OctantPlacement::OCTANTS_ORDERED.into_iter().zip(children_data) {
+		children_ids[placement as usize] = Some(match child_data {
+			Some(child_data) => storage.insert_octant(octant_id, placement, child_data)?.0,
+			None => storage.get_existing_child(octant_id, placement)?
+		});
+	}
+	Ok(children_ids.map(|child_id| child_id.expect("All children exist after pushing data down.")))
+}
+
+/// Create octant by pushing data down from its nearest existing ancestor, level by level,
+/// so all siblings along the path are created too and keep their inherited data.
+///
+/// ## Returns
+///  `StorageResult` with id of octant, which is unchanged when octant already existed.
+/// ## Errors
+///   * InvalidOctantId - when storage has no root or id isn't valid
+///   * OverMaxDepth - when octant lies below maximum depth of storage
+pub fn materialize_from_storage<Storage, F>(storage: &mut Storage, octant_id: &MortonOctantId, mut split_func: F) -> StorageResult<MortonOctantId>
+where
+	Storage: ModifiableOctantStorage<OctantId = MortonOctantId>,
+	F: FnMut(&Storage::Data, OctantPlacement) -> Storage::Data
+{
+	if octant_id.is_valid() && octant_id.compute_depth() > storage.get_max_depth() {
+		return Err(StorageError::OverMaxDepth(storage.get_max_depth()));
+	}
+	let source_id: MortonOctantId = inheritance_source_from_storage(storage, octant_id).ok_or(StorageError::InvalidOctantId)?;
+
+	let missing_ids: Vec<MortonOctantId> = std::iter::once(*octant_id)
+		.chain(octant_id.parent_id_iter())
+		.take_while(|missing_id| *missing_id != source_id)
+		.collect();
+	for missing_id in missing_ids.iter().rev() {
+		push_down_from_storage(storage, &missing_id.parent_id(), &mut split_func)?;
+	}
+	Ok(*octant_id)
+}
@@ -0,0 +1,115 @@
+//! Bottom-up propagation of data from children to parents, used to keep aggregates like average color,
+//! occupancy or maximum density in inner octants for level of detail and pruning.
+//!
+//! Reducer receives data of all 8 children ordered by `OctantPlacement`, `None` for missing child,
+//! and returns new data of parent. Leaves are never changed.
+use std::collections::HashSet;
+use std::hash::Hash;
+
+use super::octant_meta::OctantPlacement;
+use super::octant_storage_trait::{OctantStorage, StorageError, StorageResult};
+use super::octree_base::OctreeBase;
+use super::Depth;
+
+
+pub type ChildrenData<'a, Data> = [Option<&'a Data>; OctantPlacement::OCTANTS_COUNT];
+
+impl<Storage: OctantStorage> OctreeBase<Storage> {
+	/// Recompute data of every inner octant of branch, see `propagate_up_from_storage`.
+	pub fn propagate_up<F>(&mut self, octant_id: &Storage::OctantId, reducer: F) -> StorageResult<usize>
+	where F: Fn(&ChildrenData<Storage::Data>) -> Storage::Data {
+		propagate_up_from_storage(&mut self.octants, octant_id, reducer)
+	}
+
+	pub fn propagate_up_from_root<F>(&mut self, reducer: F) -> StorageResult<usize>
+	where F: Fn(&ChildrenData<Storage::Data>) -> Storage::Data {
+		let root_id: Storage::OctantId = self.octants.get_root_id();
+		propagate_up_from_storage(&mut self.octants, &root_id, reducer)
+	}
+
+	/// Recompute data of ancestors of changed octants only, see `propagate_up_dirty_from_storage`.
+	pub fn propagate_up_dirty<I, F>(&mut self, dirty_ids: I, reducer: F) -> StorageResult<usize>
+	where
+		Storage::OctantId: Hash + Eq,
+		I: IntoIterator<Item = Storage::OctantId>,
+		F: Fn(&ChildrenData<Storage::Data>) -> Storage::Data
+	{
+		propagate_up_dirty_from_storage(&mut self.octants, dirty_ids, reducer)
+	}
+}
+
+/// Recompute data of every inner octant of branch in post-order, so every parent is reduced from already updated children.
+///
+/// ## Returns
+///  `StorageResult` with number of recomputed octants.
+/// ## Errors
+///   * InvalidOctantId - when octant doesn't exist
+pub fn propagate_up_from_storage<Storage: OctantStorage, F>(storage: &mut Storage, octant_id: &Storage::OctantId, reducer: F) -> StorageResult<usize>
+where F: Fn(&ChildrenData<Storage::Data>) -> Storage::Data {
+	let _ = storage.get_octant(octant_id).ok_or(StorageError::InvalidOctantId)?;
+
+	// reversed pre-order visits children before their parents
+	let mut inner_ids: Vec<Storage::OctantId> = Vec::new();
+	let mut stack: Vec<Storage::OctantId> = vec![*octant_id];
+	while let Some(current_id) = stack.pop() {
+		let children_count: usize = stack.len();
+		stack.extend(storage.get_existing_children(&current_id).unwrap_or([None; OctantPlacement::OCTANTS_COUNT]).into_iter().flatten());
+		if stack.len() > children_count {
+			inner_ids.push(current_id);
+		}
+	}
+
+	for inner_id in inner_ids.iter().rev() {
+		reduce_children(storage, inner_id, &reducer)?;
+	}
+	Ok(inner_ids.len())
+}
+
+/// Recompute data of ancestors of changed octants, deepest first, every octant is recomputed once.
+/// Changed inner octants are recomputed too, so after removal of octant its parent can be passed instead.
+/// Changed leaves keep their data.
+///
+/// ## Returns
+///  `StorageResult` with number of recomputed octants.
+/// ## Errors
+///   * InvalidOctantId - when changed octant doesn't exist
+pub fn propagate_up_dirty_from_storage<Storage, I, F>(storage: &mut Storage, dirty_ids: I, reducer: F) -> StorageResult<usize>
+where
+	Storage: OctantStorage,
+	Storage::OctantId: Hash + Eq,
+	I: IntoIterator<Item = Storage::OctantId>,
+	F: Fn(&ChildrenData<Storage::Data>) -> Storage::Data
+{
+	let mut visited_ids: HashSet<Storage::OctantId> = HashSet::new();
+	let mut ancestors: Vec<(Depth, Storage::OctantId)> = Vec::new();
+	for dirty_id in dirty_ids {
+		let ancestor_ids: Storage::ParentIdIterator = storage.get_ancestors_for(&dirty_id).ok_or(StorageError::InvalidOctantId)?;
+		if storage.get_existing_children(&dirty_id).unwrap_or([None; OctantPlacement::OCTANTS_COUNT]).iter().any(Option::is_some) && visited_ids.insert(dirty_id) {
+			let depth: Depth = storage.get_octant_depth(&dirty_id).ok_or(StorageError::InvalidOctantId)?;
+			ancestors.push((depth, dirty_id));
+		}
+		// ancestors go towards root, rest of path was collected with other octant
+		for ancestor_id in ancestor_ids {
+			if !visited_ids.insert(ancestor_id) {
+				break;
+			}
+			let depth: Depth = storage.get_octant_depth(&ancestor_id).ok_or(StorageError::InvalidOctantId)?;
+			ancestors.push((depth, ancestor_id));
+		}
+	}
+
+	ancestors.sort_by(|(left_depth, _), (right_depth, _)| right_depth.cmp(left_depth));
+	for (_, ancestor_id) in &ancestors {
+		reduce_children(storage, ancestor_id, &reducer)?;
+	}
+	Ok(ancestors.len())
+}
+
+fn reduce_children<Storage: OctantStorage, F>(storage: &mut Storage, octant_id: &Storage::OctantId, reducer: &F) -> StorageResult<()>
+where F: Fn(&ChildrenData<Storage::Data>) -> Storage::Data {
+	let children_ids: [Option<Storage::OctantId>; OctantPlacement::OCTANTS_COUNT] = storage.get_existing_children(octant_id).unwrap_or([None; OctantPlacement::OCTANTS_COUNT]);
+	let children_data: ChildrenData<Storage::Data> = children_ids.map(|child_id| child_id.and_then(|child_id| storage.get_octant(&child_id)));
+	let reduced_data: Storage::Data = reducer(&children_data);
+	*storage.get_octant_mut(octant_id).ok_or(StorageError::InvalidOctantId)? = reduced_data;
+	Ok(())
+}
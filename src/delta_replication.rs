@@ -0,0 +1,634 @@
+//! Replication of octree edits from server to clients as compact binary deltas.
+//!
+//! Server wraps its storage into `ReplicatedOctantStorage`, which records every modification and groups recorded
+//! modifications into versioned `DeltaMessage` by `commit()`. Clients apply messages in order through `DeltaReceiver`,
+//! which gives tree identical to the server one. Client which joins late or lags behind history kept by server
+//! receives baseline message holding whole tree instead.
+//!
+//! ## Message format
+//! All numbers are little endian, ids, versions and counts are LEB128 encoded.
+//! * magic `MSVD`, format version `u8` and flags `u8`, flag `1` marks baseline
+//! * base version and version
+//! * count of operations followed by operations, each starts with tag `u8` and Morton code of octant
+//!
+//! Data is encoded by `PagedData`, so every type which can be paged can be replicated too.
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Read, Write};
+
+use super::morton_based_storage::morton_octant_id::MortonOctantId;
+use super::morton_based_storage::paged_octant_storage::PagedData;
+use super::octant_meta::OctantPlacement;
+use super::octant_storage_trait::{ModifiableOctantStorage, OctantStorage, StorageError, StorageResult};
+use super::Depth;
+
+
+const DELTA_MAGIC: &[u8; 4] = b"MSVD";
+const DELTA_FORMAT_VERSION: u8 = 1;
+const BASELINE_FLAG: u8 = 1;
+
+pub type ClientId = u64;
+
+#[derive(Debug)]
+pub enum DeltaError {
+	Io(io::Error),
+	/// Message doesn't start with magic or has unknown format version, tag or placement.
+	InvalidMessage,
+	/// Message doesn't follow version already applied by receiver, missing messages or baseline have to be applied first.
+	VersionMismatch{expected: Option<u64>, found: u64},
+	Storage(StorageError)
+}
+
+impl From<io::Error> for DeltaError {
+	fn from(error: io::Error) -> Self {
+		DeltaError::Io(error)
+	}
+}
+
+impl From<StorageError> for DeltaError {
+	fn from(error: StorageError) -> Self {
+		DeltaError::Storage(error)
+	}
+}
+
+/// Single replicated modification, octants are addressed by `MortonOctantId`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DeltaOperation<Data> {
+	/// Set or replace root data.
+	InsertRoot(Data),
+	/// Create or replace child of parent at placement.
+	Insert(MortonOctantId, OctantPlacement, Data),
+	/// Remove octant together with its whole branch.
+	Remove(MortonOctantId),
+	/// Replace data of existing octant.
+	SetData(MortonOctantId, Data),
+	/// Create or replace all children of octant, data is ordered by placement.
+	Subdivide(MortonOctantId, [Data; OctantPlacement::OCTANTS_COUNT])
+}
+
+impl<Data> DeltaOperation<Data> {
+	fn tag(&self) -> u8 {
+		match self {
+			Self::InsertRoot(_) => 0,
+			Self::Insert(..) => 1,
+			Self::Remove(_) => 2,
+			Self::SetData(..) => 3,
+			Self::Subdivide(..) => 4
+		}
+	}
+}
+
+/// Operations turning tree of `base_version` into tree of `version`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeltaMessage<Data> {
+	/// Version which has to be applied before this message, not used by baseline.
+	pub base_version: u64,
+	pub version: u64,
+	/// Baseline clears tree first and holds whole tree of `version`.
+	pub is_baseline: bool,
+	pub operations: Vec<DeltaOperation<Data>>
+}
+
+impl<Data: PagedData> DeltaMessage<Data> {
+	pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		writer.write_all(DELTA_MAGIC)?;
+		writer.write_all(&[DELTA_FORMAT_VERSION, if self.is_baseline { BASELINE_FLAG } else { 0 }])?;
+		write_varint(writer, self.base_version)?;
+		write_varint(writer, self.version)?;
+		write_varint(writer, self.operations.len() as u64)?;
+		for operation in &self.operations {
+			writer.write_all(&[operation.tag()])?;
+			match operation {
+				DeltaOperation::InsertRoot(data) => {
+					data.write_to(writer)?;
+				},
+				DeltaOperation::Insert(parent_id, placement, data) => {
+					write_varint(writer, parent_id.as_morton())?;
+					writer.write_all(&[*placement as u8])?;
+					data.write_to(writer)?;
+				},
+				DeltaOperation::Remove(octant_id) => {
+					write_varint(writer, octant_id.as_morton())?;
+				},
+				DeltaOperation::SetData(octant_id, data) => {
+					write_varint(writer, octant_id.as_morton())?;
+					data.write_to(writer)?;
+				},
+				DeltaOperation::Subdivide(octant_id, children_data) => {
+					write_varint(writer, octant_id.as_morton())?;
+					for data in children_data {
+						data.write_to(writer)?;
+					}
+				}
+			}
+		}
+		Ok(())
+	}
+
+	pub fn read<R: Read>(reader: &mut R) -> Result<Self, DeltaError> {
+		let mut header = [0_u8; 6];
+		reader.read_exact(&mut header)?;
+		if &header[0..4] != DELTA_MAGIC || header[4] != DELTA_FORMAT_VERSION || header[5] & !BASELINE_FLAG != 0 {
+			return Err(DeltaError::InvalidMessage);
+		}
+		let base_version: u64 = read_varint(reader)?;
+		let version: u64 = read_varint(reader)?;
+		let operation_count: u64 = read_varint(reader)?;
+
+		let mut operations: Vec<DeltaOperation<Data>> = Vec::new();
+		for _ in 0..operation_count {
+			let operation = match read_u8(reader)? {
+				0 => DeltaOperation::InsertRoot(Data::read_from(reader)?),
+				1 => {
+					let parent_id: MortonOctantId = read_octant_id(reader)?;
+					let placement: OctantPlacement = read_placement(reader)?;
+					DeltaOperation::Insert(parent_id, placement, Data::read_from(reader)?)
+				},
+				2 => DeltaOperation::Remove(read_octant_id(reader)?),
+				3 => {
+					let octant_id: MortonOctantId = read_octant_id(reader)?;
+					DeltaOperation::SetData(octant_id, Data::read_from(reader)?)
+				},
+				4 => {
+					let octant_id: MortonOctantId = read_octant_id(reader)?;
+					let mut children_data: Vec<Data> = Vec::with_capacity(OctantPlacement::OCTANTS_COUNT);
+					for _ in 0..OctantPlacement::OCTANTS_COUNT {
+						children_data.push(Data::read_from(reader)?);
+					}
+					let children_data: [Data; OctantPlacement::OCTANTS_COUNT] = children_data.try_into()
+						.map_err(|_| DeltaError::InvalidMessage)?;
+					DeltaOperation::Subdivide(octant_id, children_data)
+				},
+				_ => return Err(DeltaError::InvalidMessage)
+			};
+			operations.push(operation);
+		}
+
+		Ok(
+			DeltaMessage{
+				base_version,
+				version,
+				is_baseline: header[5] & BASELINE_FLAG != 0,
+				operations
+			}
+		)
+	}
+
+	pub fn to_bytes(&self) -> Vec<u8> {
+		let mut bytes: Vec<u8> = Vec::new();
+		self.write(&mut bytes).expect("Writing into vector can't fail.");
+		bytes
+	}
+
+	pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, DeltaError> {
+		Self::read(&mut bytes)
+	}
+}
+
+impl<Data: Clone> DeltaMessage<Data> {
+	/// Message which recreates whole tree held by storage.
+	pub fn baseline<Storage>(storage: &Storage, version: u64) -> Self
+	where Storage: OctantStorage<OctantId = MortonOctantId, Data = Data> {
+		let mut operations: Vec<DeltaOperation<Data>> = Vec::new();
+		let root_id: MortonOctantId = storage.get_root_id();
+		if let Some(root_data) = storage.get_octant(&root_id) {
+			operations.push(DeltaOperation::InsertRoot(root_data.clone()));
+			push_children_insertions(storage, &root_id, &mut operations);
+		}
+		DeltaMessage{
+			base_version: 0,
+			version,
+			is_baseline: true,
+			operations
+		}
+	}
+
+	/// Apply operations to storage, baseline removes whole tree first.
+	///
+	/// ## Errors
+	/// Returns `StorageError` when operation refers to missing octant, storage is left partially modified in such case.
+	pub fn apply_to<Storage>(&self, storage: &mut Storage) -> StorageResult<()>
+	where Storage: ModifiableOctantStorage<OctantId = MortonOctantId, Data = Data> {
+		if self.is_baseline {
+			let root_id: MortonOctantId = storage.get_root_id();
+			storage.remove_octant(&root_id);
+		}
+		for operation in &self.operations {
+			apply_delta_operation(storage, operation)?;
+		}
+		Ok(())
+	}
+}
+
+/// Apply single operation to storage.
+///
+/// ## Errors
+///   * InvalidOctantId - when operation refers to octant missing in storage
+///   * OverMaxDepth - when created octant would lie below maximum depth
+pub fn apply_delta_operation<Storage>(storage: &mut Storage, operation: &DeltaOperation<Storage::Data>) -> StorageResult<()>
+where
+	Storage: ModifiableOctantStorage<OctantId = MortonOctantId>,
+	Storage::Data: Clone
+{
+	match operation {
+		DeltaOperation::InsertRoot(data) => {
+			storage.insert_root(data.clone());
+		},
+		DeltaOperation::Insert(parent_id, placement, data) => {
+			storage.insert_octant(parent_id, *placement, data.clone())?;
+		},
+		DeltaOperation::Remove(octant_id) => {
+			storage.remove_octant(octant_id).ok_or(StorageError::InvalidOctantId)?;
+		},
+		DeltaOperation::SetData(octant_id, data) => {
+			*storage.get_octant_mut(octant_id).ok_or(StorageError::InvalidOctantId)? = data.clone();
+		},
+		DeltaOperation::Subdivide(octant_id, children_data) => {
+			storage.subdivide(octant_id, |placement| children_data[placement as usize].clone())?;
+		}
+	}
+	Ok(())
+}
+
+fn push_children_insertions<Storage>(storage: &Storage, parent_id: &MortonOctantId, operations: &mut Vec<DeltaOperation<Storage::Data>>)
+where
+	Storage: OctantStorage<OctantId = MortonOctantId>,
+	Storage::Data: Clone
+{
+	for placement in OctantPlacement::OCTANTS_ORDERED {
+		let Ok(child_id) = storage.get_existing_child(parent_id, placement) else {
+			continue;
+		};
+		let Some(child_data) = storage.get_octant(&child_id) else {
+			continue;
+		};
+		operations.push(DeltaOperation::Insert(*parent_id, placement, child_data.clone()));
+		push_children_insertions(storage, &child_id, operations);
+	}
+}
+
+/// Client side of replication, tracks last applied version.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DeltaReceiver {
+	version: Option<u64>
+}
+
+impl DeltaReceiver {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Last applied version, `None` until first baseline is applied.
+	pub fn version(&self) -> Option<u64> {
+		self.version
+	}
+
+	/// Apply message following last applied version or baseline, already applied messages are skipped.
+	///
+	/// ## Returns
+	/// Version of storage after applying message.
+	/// ## Errors
+	///   * VersionMismatch - when message doesn't follow last applied version, storage is left unchanged
+	///   * Storage - when operation fails, storage has to be replaced by baseline afterwards
+	pub fn apply<Storage>(&mut self, storage: &mut Storage, message: &DeltaMessage<Storage::Data>) -> Result<u64, DeltaError>
+	where
+		Storage: ModifiableOctantStorage<OctantId = MortonOctantId>,
+		Storage::Data: Clone
+	{
+		if !message.is_baseline {
+			match self.version {
+				Some(version) if message.version <= version => return Ok(version),
+				Some(version) if message.base_version == version => {},
+				_ => return Err(DeltaError::VersionMismatch{expected: self.version, found: message.base_version})
+			}
+		}
+
+		self.version = None;
+		message.apply_to(storage)?;
+		self.version = Some(message.version);
+		Ok(message.version)
+	}
+}
+
+enum PendingOperation<Data> {
+	Ready(DeltaOperation<Data>),
+	/// Data handed out by `get_octant_mut()`, it is read when operations are committed.
+	SetData(MortonOctantId)
+}
+
+/// Storage wrapper which records modifications for replication to clients.
+///
+/// Recorded modifications are turned into `DeltaMessage` by `commit()`. Committed messages are kept in history
+/// until every client acknowledged them, or until history grows over its limit, so clients which lag behind
+/// can catch up by `updates_for()`. Clients not covered by history receive `baseline()` instead.
+/// Without any client only the last committed message is kept, since clients joining later start from `baseline()`.
+///
+/// ## Examples
+/// ```
+/// use modsvo::{delta_replication::{DeltaMessage, DeltaReceiver}, octant_storage_trait::{ModifiableOctantStorage, OctantStorage}};
+/// use modsvo::{SparseOctree, SparseOctreeReplicated};
+///
+/// let mut server = SparseOctreeReplicated::<u32>::default();
+/// let mut client = SparseOctree::<u32>::default();
+/// let mut receiver = DeltaReceiver::new();
+/// receiver.apply(&mut client.octants, &server.octants.baseline()).unwrap();
+///
+/// let root_id = server.octants.get_root_id();
+/// server.octants.subdivide(&root_id, |_| 1).unwrap();
+/// let message: Vec<u8> = server.octants.commit().unwrap().to_bytes();
+/// receiver.apply(&mut client.octants, &DeltaMessage::from_bytes(&message).unwrap()).unwrap();
+/// assert_eq!(client.octants.get_octant(&root_id.children_ids()[0]), Some(&1));
+/// ```
+pub struct ReplicatedOctantStorage<Storage: OctantStorage> {
+	storage: Storage,
+	pending: Vec<PendingOperation<Storage::Data>>,
+	version: u64,
+	history: VecDeque<DeltaMessage<Storage::Data>>,
+	history_limit: usize,
+	acknowledged_versions: HashMap<ClientId, u64>
+}
+
+impl<Storage> ReplicatedOctantStorage<Storage>
+where
+	Storage: ModifiableOctantStorage<OctantId = MortonOctantId>,
+	Storage::Data: Clone
+{
+	pub fn new(storage: Storage) -> Self {
+		ReplicatedOctantStorage{
+			storage,
+			pending: Vec::new(),
+			version: 0,
+			history: VecDeque::new(),
+			history_limit: usize::MAX,
+			acknowledged_versions: HashMap::new()
+		}
+	}
+
+	/// Limit number of committed messages kept for clients which didn't acknowledge them yet.
+	pub fn with_history_limit(mut self, history_limit: usize) -> Self {
+		self.history_limit = history_limit;
+		self.enforce_history_limit();
+		self
+	}
+
+	pub fn storage(&self) -> &Storage {
+		&self.storage
+	}
+
+	/// Returns wrapped storage and drops recorded history.
+	pub fn into_inner(self) -> Storage {
+		self.storage
+	}
+
+	/// Version of last committed message.
+	pub fn version(&self) -> u64 {
+		self.version
+	}
+
+	pub fn has_pending(&self) -> bool {
+		!self.pending.is_empty()
+	}
+
+	pub fn history_len(&self) -> usize {
+		self.history.len()
+	}
+
+	/// Turn modifications recorded since last commit into message of next version.
+	///
+	/// ## Returns
+	/// Committed message, `None` when nothing was modified.
+	pub fn commit(&mut self) -> Option<&DeltaMessage<Storage::Data>> {
+		let mut operations: Vec<DeltaOperation<Storage::Data>> = Vec::with_capacity(self.pending.len());
+		for pending_operation in self.pending.drain(..) {
+			match pending_operation {
+				PendingOperation::Ready(operation) => operations.push(operation),
+				// octant removed later in same commit, removal is replicated on its own
+				PendingOperation::SetData(octant_id) => if let Some(data) = self.storage.get_octant(&octant_id) {
+					operations.push(DeltaOperation::SetData(octant_id, data.clone()));
+				}
+			}
+		}
+		if operations.is_empty() {
+			return None;
+		}
+
+		let message = DeltaMessage{
+			base_version: self.version,
+			version: self.version + 1,
+			is_baseline: false,
+			operations
+		};
+		self.version = message.version;
+		self.prune_acknowledged();
+		self.history.push_back(message);
+		self.enforce_history_limit();
+		self.history.back()
+	}
+
+	/// Commit pending modifications and return message recreating whole tree of current version.
+	pub fn baseline(&mut self) -> DeltaMessage<Storage::Data> {
+		self.commit();
+		DeltaMessage::baseline(&self.storage, self.version)
+	}
+
+	/// Remember version applied by client, messages acknowledged by all clients are dropped from history.
+	pub fn acknowledge(&mut self, client_id: ClientId, version: u64) {
+		let acknowledged_version: &mut u64 = self.acknowledged_versions.entry(client_id).or_insert(version);
+		*acknowledged_version = version.max(*acknowledged_version).min(self.version);
+		self.prune_acknowledged();
+	}
+
+	/// Stop keeping history for disconnected client.
+	pub fn remove_client(&mut self, client_id: ClientId) {
+		self.acknowledged_versions.remove(&client_id);
+		self.prune_acknowledged();
+	}
+
+	/// Committed messages not acknowledged by client yet, in order in which they have to be applied.
+	///
+	/// ## Returns
+	/// `None` when client is unknown or history doesn't reach its version anymore, client needs `baseline()` then.
+	pub fn updates_for(&self, client_id: ClientId) -> Option<Vec<&DeltaMessage<Storage::Data>>> {
+		let acknowledged_version: u64 = *self.acknowledged_versions.get(&client_id)?;
+		let updates: Vec<&DeltaMessage<Storage::Data>> = self.history.iter()
+			.filter(|message| message.version > acknowledged_version)
+			.collect();
+		let covered_from: u64 = updates.first().map_or(self.version, |first_update| first_update.base_version);
+		if covered_from == acknowledged_version {
+			Some(updates)
+		}
+		else {
+			None
+		}
+	}
+
+	fn prune_acknowledged(&mut self) {
+		let Some(&oldest_version) = self.acknowledged_versions.values().min() else {
+			self.history.clear();
+			return;
+		};
+		while self.history.front().is_some_and(|message| message.version <= oldest_version) {
+			self.history.pop_front();
+		}
+	}
+
+	fn enforce_history_limit(&mut self) {
+		while self.history.len() > self.history_limit {
+			self.history.pop_front();
+		}
+	}
+
+	fn record(&mut self, operation: PendingOperation<Storage::Data>) {
+		if let (PendingOperation::SetData(octant_id), Some(PendingOperation::SetData(last_id))) = (&operation, self.pending.last()) {
+			if octant_id == last_id {
+				return;
+			}
+		}
+		self.pending.push(operation);
+	}
+}
+
+impl<Storage> OctantStorage for ReplicatedOctantStorage<Storage>
+where
+	Storage: ModifiableOctantStorage<OctantId = MortonOctantId>,
+	Storage::Data: Clone
+{
+	type OctantId = MortonOctantId;
+	type ParentIdIterator = Storage::ParentIdIterator;
+	type Data = Storage::Data;
+
+	fn get_root_id(&self) -> Self::OctantId {
+		self.storage.get_root_id()
+	}
+
+	fn get_max_depth(&self) -> Depth {
+		self.storage.get_max_depth()
+	}
+
+	fn get_octant_depth(&self, octant_id: &Self::OctantId) -> Option<Depth> {
+		self.storage.get_octant_depth(octant_id)
+	}
+
+	fn get_octant(&self, octant_id: &Self::OctantId) -> Option<&Self::Data> {
+		self.storage.get_octant(octant_id)
+	}
+
+	fn get_octant_mut(&mut self, octant_id: &Self::OctantId) -> Option<&mut Self::Data> {
+		let _ = self.storage.get_octant(octant_id)?;
+		self.record(PendingOperation::SetData(*octant_id));
+		self.storage.get_octant_mut(octant_id)
+	}
+
+	fn get_existing_child(&self, parent_id: &Self::OctantId, child_placement: OctantPlacement) -> StorageResult<Self::OctantId> {
+		self.storage.get_existing_child(parent_id, child_placement)
+	}
+
+	fn get_parent(&self, octant_id: &Self::OctantId) -> Option<Self::OctantId> {
+		self.storage.get_parent(octant_id)
+	}
+
+	fn get_ancestors_for(&self, octant_id: &Self::OctantId) -> Option<Self::ParentIdIterator> {
+		self.storage.get_ancestors_for(octant_id)
+	}
+
+	fn get_existing_children(&self, parent_id: &Self::OctantId) -> StorageResult<[Option<Self::OctantId>; OctantPlacement::OCTANTS_COUNT]> {
+		self.storage.get_existing_children(parent_id)
+	}
+
+	fn which_child_of(&self, parent_id: &Self::OctantId, child_id: &Self::OctantId) -> StorageResult<OctantPlacement> {
+		self.storage.which_child_of(parent_id, child_id)
+	}
+
+	fn estimated_heap_bytes(&self) -> Option<usize> {
+		self.storage.estimated_heap_bytes()
+	}
+}
+
+impl<Storage> ModifiableOctantStorage for ReplicatedOctantStorage<Storage>
+where
+	Storage: ModifiableOctantStorage<OctantId = MortonOctantId>,
+	Storage::Data: Clone
+{
+	fn insert_root(&mut self, custom_data: Self::Data) -> Option<Self::Data> {
+		self.record(PendingOperation::Ready(DeltaOperation::InsertRoot(custom_data.clone())));
+		self.storage.insert_root(custom_data)
+	}
+
+	fn insert_octant(&mut self, parent_id: &Self::OctantId, child_octant_placement: OctantPlacement, custom_data: Self::Data) -> StorageResult<(Self::OctantId, Option<Self::Data>)> {
+		let operation = DeltaOperation::Insert(*parent_id, child_octant_placement, custom_data.clone());
+		let inserted = self.storage.insert_octant(parent_id, child_octant_placement, custom_data)?;
+		self.record(PendingOperation::Ready(operation));
+		Ok(inserted)
+	}
+
+	fn remove_octant(&mut self, octant_id: &Self::OctantId) -> Option<()> {
+		self.storage.remove_octant(octant_id)?;
+		self.record(PendingOperation::Ready(DeltaOperation::Remove(*octant_id)));
+		Some(())
+	}
+
+	fn remove_octant_and_fill(&mut self, octant_id: &Self::OctantId, collected_octant_data: &mut Vec<(Self::OctantId, Self::Data)>) -> Option<()> {
+		self.storage.remove_octant_and_fill(octant_id, collected_octant_data)?;
+		self.record(PendingOperation::Ready(DeltaOperation::Remove(*octant_id)));
+		Some(())
+	}
+
+	fn subdivide<F>(&mut self, parent_id: &Self::OctantId, create_custom_data: F) -> StorageResult<[Self::OctantId; OctantPlacement::OCTANTS_COUNT]>
+	where F: FnMut(OctantPlacement) -> Self::Data {
+		let children_data: [Self::Data; OctantPlacement::OCTANTS_COUNT] = OctantPlacement::OCTANTS_ORDERED.map(create_custom_data);
+		let children_ids = self.storage.subdivide(parent_id, |placement| children_data[placement as usize].clone())?;
+		self.record(PendingOperation::Ready(DeltaOperation::Subdivide(*parent_id, children_data)));
+		Ok(children_ids)
+	}
+}
+
+impl<Storage> Default for ReplicatedOctantStorage<Storage>
+where
+	Storage: ModifiableOctantStorage<OctantId = MortonOctantId> + Default,
+	Storage::Data: Clone
+{
+	fn default() -> Self {
+		Self::new(Storage::default())
+	}
+}
+
+fn write_varint<W: Write>(writer: &mut W, mut value: u64) -> io::Result<()> {
+	loop {
+		let byte: u8 = (value & 0x7f) as u8;
+		value >>= 7;
+		if value == 0 {
+			return writer.write_all(&[byte]);
+		}
+		writer.write_all(&[byte | 0x80])?;
+	}
+}
+
+fn read_varint<R: Read>(reader: &mut R) -> Result<u64, DeltaError> {
+	let mut value: u64 = 0;
+	for shift in (0..64).step_by(7) {
+		let byte: u8 = read_u8(reader)?;
+		value |= ((byte & 0x7f) as u64) << shift;
+		if byte & 0x80 == 0 {
+			return Ok(value);
+		}
+	}
+	Err(DeltaError::InvalidMessage)
+}
+
+fn read_u8<R: Read>(reader: &mut R) -> io::Result<u8> {
+	let mut byte = [0_u8; 1];
+	reader.read_exact(&mut byte)?;
+	Ok(byte[0])
+}
+
+fn read_octant_id<R: Read>(reader: &mut R) -> Result<MortonOctantId, DeltaError> {
+	let morton_code: u64 = read_varint(reader)?;
+	if morton_code == 0 {
+		return Err(DeltaError::InvalidMessage);
+	}
+	Ok(MortonOctantId::from_morton_code(morton_code))
+}
+
+fn read_placement<R: Read>(reader: &mut R) -> Result<OctantPlacement, DeltaError> {
+	let placement_index: usize = read_u8(reader)? as usize;
+	OctantPlacement::OCTANTS_ORDERED.get(placement_index).copied().ok_or(DeltaError::InvalidMessage)
+}
@@ -0,0 +1,191 @@
+//! Sampling of octant data as continuous field at arbitrary positions.
+//!
+//! Data of octant is treated as sample at its center. Nearest sampling returns data of deepest existing octant
+//! containing position. Trilinear sampling blends 8 samples around position on grid of that octant,
+//! every sample is nearest sample at its own position, so missing neighbors fall back to their ancestors
+//! and samples outside of root are clamped to its border.
+use glam::{Vec2, Vec3, Vec3A, Vec4};
+
+use super::octant_storage_trait::OctantStorage;
+use super::spatial_octree_base::SpatialOctreeBase;
+use super::voxel_trait::Voxel;
+use super::voxels::voxel_cube::VolumetricCube;
+use super::Depth;
+
+
+/// Data which can be blended linearly, `t` goes from `0.0` returning `self` to `1.0` returning `other`.
+pub trait Lerp {
+	fn lerp(&self, other: &Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+	fn lerp(&self, other: &Self, t: f32) -> Self {
+		self + (other - self) * t
+	}
+}
+
+impl Lerp for f64 {
+	fn lerp(&self, other: &Self, t: f32) -> Self {
+		self + (other - self) * t as f64
+	}
+}
+
+macro_rules! impl_lerp_for_vectors {
+	($($vector:ty),*) => {
+		$(
+			impl Lerp for $vector {
+				fn lerp(&self, other: &Self, t: f32) -> Self {
+					<$vector>::lerp(*self, *other, t)
+				}
+			}
+		)*
+	};
+}
+
+impl_lerp_for_vectors!(Vec2, Vec3, Vec3A, Vec4);
+
+impl<Storage: OctantStorage> SpatialOctreeBase<Storage, VolumetricCube> {
+	/// Deepest existing octant containing point, see `octant_at_point_from_storage`.
+	pub fn octant_at_point(&self, point: Vec3A) -> Option<(Storage::OctantId, VolumetricCube)> {
+		octant_at_point_from_storage(self.octants(), self.get_root_voxel(), point)
+	}
+
+	/// Data of deepest existing octant containing point, `None` when point lies outside of root.
+	pub fn sample_nearest(&self, point: Vec3A) -> Option<&Storage::Data> {
+		let (octant_id, _) = self.octant_at_point(point)?;
+		self.octants().get_octant(&octant_id)
+	}
+
+	/// Blend data of octants around point, see `sample_trilinear_from_storage`.
+	pub fn sample_trilinear(&self, point: Vec3A) -> Option<Storage::Data>
+	where Storage::Data: Lerp {
+		sample_trilinear_from_storage(self.octants(), self.get_root_voxel(), point)
+	}
+
+	/// Gradient of scalar field, see `sample_gradient_from_storage`.
+	pub fn sample_gradient<F>(&self, point: Vec3A, step: f32, scalar: F) -> Option<Vec3A>
+	where
+		Storage::Data: Lerp,
+		F: Fn(&Storage::Data) -> f32
+	{
+		sample_gradient_from_storage(self.octants(), self.get_root_voxel(), point, step, scalar)
+	}
+
+	/// Normalized gradient, for signed distance field it is normal of surface pointing outside.
+	///
+	/// ## Returns
+	/// `Some(Vec3A::ZERO)` when field is flat around point, `None` when point lies outside of root.
+	pub fn sample_normal<F>(&self, point: Vec3A, step: f32, scalar: F) -> Option<Vec3A>
+	where
+		Storage::Data: Lerp,
+		F: Fn(&Storage::Data) -> f32
+	{
+		Some(self.sample_gradient(point, step, scalar)?.normalize_or_zero())
+	}
+}
+
+/// Descend from root towards point as deep as octants exist.
+///
+/// ## Returns
+/// Id and voxel of deepest existing octant containing point, `None` when point lies outside of root or there is no root.
+pub fn octant_at_point_from_storage<Storage: OctantStorage>(storage: &Storage, root_voxel: &VolumetricCube, point: Vec3A) -> Option<(Storage::OctantId, VolumetricCube)> {
+	descend_towards_point_from_storage(storage, root_voxel, point, Depth::MAX)
+}
+
+/// Descend from root towards point through existing octants, at most `max_depth` levels below root.
+///
+/// ## Returns
+/// Id and voxel of deepest reached octant containing point, `None` when point lies outside of root or there is no root.
+pub fn descend_towards_point_from_storage<Storage: OctantStorage>(storage: &Storage, root_voxel: &VolumetricCube, point: Vec3A, max_depth: Depth) -> Option<(Storage::OctantId, VolumetricCube)> {
+	if !root_voxel.contains_point(point) {
+		return None;
+	}
+	let mut octant_id: Storage::OctantId = storage.get_root_id();
+	let _ = storage.get_octant(&octant_id)?;
+	let mut voxel: VolumetricCube = *root_voxel;
+	for _ in 0..max_depth {
+		let placement = voxel.guess_octant(point);
+		match storage.get_existing_child(&octant_id, placement) {
+			Ok(child_id) => {
+				octant_id = child_id;
+				voxel = voxel.make_sub_voxel(placement);
+			},
+			Err(_) => break
+		}
+	}
+	Some((octant_id, voxel))
+}
+
+/// Blend 8 samples around point on grid of deepest octant containing point.
+///
+/// ## Returns
+/// Blended data, `None` when point lies outside of root or there is no root.
+pub fn sample_trilinear_from_storage<Storage>(storage: &Storage, root_voxel: &VolumetricCube, point: Vec3A) -> Option<Storage::Data>
+where
+	Storage: OctantStorage,
+	Storage::Data: Lerp
+{
+	let (_, voxel) = octant_at_point_from_storage(storage, root_voxel, point)?;
+	let cell_size: f32 = voxel.half_extent() * 2.0;
+	// samples lie at octant centers, first sample is the one below point on every axis
+	let grid_position: Vec3A = (point - root_voxel.min()) / cell_size - 0.5;
+	let first_cell: Vec3A = grid_position.floor();
+	let weights: Vec3A = grid_position - first_cell;
+	let first_sample: Vec3A = root_voxel.min() + (first_cell + 0.5) * cell_size;
+
+	let lowest_sample: Vec3A = root_voxel.min() + voxel.half_extent();
+	let highest_sample: Vec3A = root_voxel.max() - voxel.half_extent();
+	let sample_at = |corner: Vec3A| -> Option<&Storage::Data> {
+		let sample_point: Vec3A = (first_sample + corner * cell_size).clamp(lowest_sample, highest_sample);
+		let (octant_id, _) = octant_at_point_from_storage(storage, root_voxel, sample_point)?;
+		storage.get_octant(&octant_id)
+	};
+
+	let mut along_x: Vec<Storage::Data> = Vec::with_capacity(4);
+	for (y, z) in [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)] {
+		let low: &Storage::Data = sample_at(Vec3A::new(0.0, y, z))?;
+		let high: &Storage::Data = sample_at(Vec3A::new(1.0, y, z))?;
+		along_x.push(low.lerp(high, weights.x));
+	}
+	let bottom: Storage::Data = along_x[0].lerp(&along_x[1], weights.y);
+	let top: Storage::Data = along_x[2].lerp(&along_x[3], weights.y);
+	Some(bottom.lerp(&top, weights.z))
+}
+
+/// Estimate gradient of scalar field by central differences of trilinear samples,
+/// differences which would leave root are taken one-sided.
+///
+/// ## Arguments
+/// * `step` - Distance between point and samples, half extent of octants around point is good choice.
+/// * `scalar` - Converts blended data into value of field.
+///
+/// ## Returns
+/// Gradient, `None` when point lies outside of root or there is no root.
+///
+/// ## Panics
+/// When `step` isn't positive and finite.
+pub fn sample_gradient_from_storage<Storage, F>(storage: &Storage, root_voxel: &VolumetricCube, point: Vec3A, step: f32, scalar: F) -> Option<Vec3A>
+where
+	Storage: OctantStorage,
+	Storage::Data: Lerp,
+	F: Fn(&Storage::Data) -> f32
+{
+	assert!(step.is_finite() && step > 0.0, "Gradient step has to be positive and finite.");
+	let center_value: f32 = scalar(&sample_trilinear_from_storage(storage, root_voxel, point)?);
+	let sample_value = |sample_point: Vec3A| -> Option<f32> {
+		sample_trilinear_from_storage(storage, root_voxel, sample_point).map(|data| scalar(&data))
+	};
+
+	let mut gradient: Vec3A = Vec3A::ZERO;
+	for axis in 0..3 {
+		let mut offset: Vec3A = Vec3A::ZERO;
+		offset[axis] = step;
+		gradient[axis] = match (sample_value(point - offset), sample_value(point + offset)) {
+			(Some(low), Some(high)) => (high - low) / (2.0 * step),
+			(None, Some(high)) => (high - center_value) / step,
+			(Some(low), None) => (center_value - low) / step,
+			(None, None) => 0.0
+		};
+	}
+	Some(gradient)
+}
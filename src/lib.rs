@@ -7,16 +7,59 @@ pub mod voxels;
 pub mod octree_base;
 pub mod spatial_octree_base;
 pub mod morton_based_storage;
+pub mod transactional_octant_storage;
+pub mod sdf;
+pub mod csg;
+pub mod boolean_operations;
+pub mod mesh_voxelizer;
+pub mod point_cloud;
+pub mod vox_format;
+pub mod mesh_export;
+pub mod tree_dump;
+pub mod octree_stats;
+pub mod storage_validation;
+pub mod root_growth;
+pub mod chunked_world;
+pub mod merkle_hashes;
+pub mod delta_replication;
+pub mod data_propagation;
+pub mod data_inheritance;
+pub mod field_sampling;
+pub mod pathfinding;
+#[cfg(feature = "rayon")]
+pub mod parallel_octree;
+#[cfg(feature = "conformance")]
+pub mod conformance;
 
 
 
 use morton_based_storage::hashed_octant_storage::HashedOctantStorage;
+use morton_based_storage::persistent_octant_storage::PersistentOctantStorage;
+use morton_based_storage::paged_octant_storage::PagedOctantStorage;
+use morton_based_storage::dag_octant_storage::DagOctantStorage;
+use transactional_octant_storage::TransactionalOctantStorage;
+use delta_replication::ReplicatedOctantStorage;
 
 pub type Depth = u8;
 
 pub type SparseOctreeHashed<CustomData> = octree_base::OctreeBase<HashedOctantStorage<CustomData>>;
 pub type SpatialSparseOctreeHashed<CustomData> = spatial_octree_base::SpatialOctreeBase<HashedOctantStorage<CustomData>>;
 
+pub type SparseOctreeTransactional<CustomData> = octree_base::OctreeBase<TransactionalOctantStorage<HashedOctantStorage<CustomData>>>;
+pub type SpatialSparseOctreeTransactional<CustomData> = spatial_octree_base::SpatialOctreeBase<TransactionalOctantStorage<HashedOctantStorage<CustomData>>>;
+
+pub type SparseOctreeReplicated<CustomData> = octree_base::OctreeBase<ReplicatedOctantStorage<HashedOctantStorage<CustomData>>>;
+pub type SpatialSparseOctreeReplicated<CustomData> = spatial_octree_base::SpatialOctreeBase<ReplicatedOctantStorage<HashedOctantStorage<CustomData>>>;
+
+pub type SparseOctreePersistent<CustomData> = octree_base::OctreeBase<PersistentOctantStorage<CustomData>>;
+pub type SpatialSparseOctreePersistent<CustomData> = spatial_octree_base::SpatialOctreeBase<PersistentOctantStorage<CustomData>>;
+
+pub type SparseOctreePaged<CustomData> = octree_base::OctreeBase<PagedOctantStorage<CustomData>>;
+pub type SpatialSparseOctreePaged<CustomData> = spatial_octree_base::SpatialOctreeBase<PagedOctantStorage<CustomData>>;
+
+pub type SparseOctreeDag<CustomData> = octree_base::OctreeBase<DagOctantStorage<CustomData>>;
+pub type SpatialSparseOctreeDag<CustomData> = spatial_octree_base::SpatialOctreeBase<DagOctantStorage<CustomData>>;
+
 // default option
 pub type SparseOctree<CustomData> = SparseOctreeHashed<CustomData>;
 pub type SpatialSparseOctree<CustomData> = SpatialSparseOctreeHashed<CustomData>;
\ No newline at end of file
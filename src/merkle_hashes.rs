@@ -0,0 +1,343 @@
+//! Merkle hashes of subtrees for fast comparison and synchronization of trees.
+//!
+//! Hash of octant covers its data and hashes of its children, so equal hashes mean equal subtrees and trees are compared
+//! top-down by descending only into subtrees with different hashes. Hashes are kept next to storage and refreshed
+//! after edits by `update_path`, which rehashes edited octant and its ancestors only.
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use super::morton_based_storage::morton_octant_id::MortonOctantId;
+use super::octant_meta::OctantPlacement;
+use super::octant_storage_trait::{OctantStorage, StorageError, StorageResult};
+use super::octree_base::OctreeBase;
+
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// FNV-1a hasher which writes numbers as little endian, so hashes are equal on every machine and every run.
+#[derive(Clone, Copy, Debug)]
+pub struct StableHasher {
+	state: u64
+}
+
+impl Default for StableHasher {
+	fn default() -> Self {
+		StableHasher{
+			state: FNV_OFFSET_BASIS
+		}
+	}
+}
+
+impl Hasher for StableHasher {
+	fn finish(&self) -> u64 {
+		self.state
+	}
+
+	fn write(&mut self, bytes: &[u8]) {
+		for &byte in bytes {
+			self.state ^= byte as u64;
+			self.state = self.state.wrapping_mul(FNV_PRIME);
+		}
+	}
+
+	fn write_u16(&mut self, value: u16) {
+		self.write(&value.to_le_bytes());
+	}
+
+	fn write_u32(&mut self, value: u32) {
+		self.write(&value.to_le_bytes());
+	}
+
+	fn write_u64(&mut self, value: u64) {
+		self.write(&value.to_le_bytes());
+	}
+
+	fn write_u128(&mut self, value: u128) {
+		self.write(&value.to_le_bytes());
+	}
+
+	fn write_usize(&mut self, value: usize) {
+		self.write_u64(value as u64);
+	}
+
+	fn write_i16(&mut self, value: i16) {
+		self.write_u16(value as u16);
+	}
+
+	fn write_i32(&mut self, value: i32) {
+		self.write_u32(value as u32);
+	}
+
+	fn write_i64(&mut self, value: i64) {
+		self.write_u64(value as u64);
+	}
+
+	fn write_i128(&mut self, value: i128) {
+		self.write_u128(value as u128);
+	}
+
+	fn write_isize(&mut self, value: isize) {
+		self.write_u64(value as u64);
+	}
+}
+
+/// Hash data with `StableHasher`, usable as `hash_data` function of this module.
+pub fn stable_hash<Data: Hash + ?Sized>(data: &Data) -> u64 {
+	let mut hasher = StableHasher::default();
+	data.hash(&mut hasher);
+	hasher.finish()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OctantHash {
+	/// Hash of data of octant alone.
+	pub data: u64,
+	/// Hash of data and hashes of children.
+	pub subtree: u64
+}
+
+/// Subtree hashes of octants of single storage.
+#[derive(Clone, Debug, Default)]
+pub struct MerkleHashes<OctantId: Hash + Eq> {
+	hashes: HashMap<OctantId, OctantHash>
+}
+
+impl<OctantId: Copy + Hash + Eq> MerkleHashes<OctantId> {
+	/// Hash every octant reachable from root.
+	pub fn compute<Storage, F>(storage: &Storage, mut hash_data: F) -> Self
+	where
+		Storage: OctantStorage<OctantId = OctantId>,
+		F: FnMut(&Storage::Data) -> u64
+	{
+		let mut merkle_hashes = MerkleHashes{
+			hashes: HashMap::new()
+		};
+		let root_id: OctantId = storage.get_root_id();
+		if storage.get_octant(&root_id).is_some() {
+			merkle_hashes.hash_branch(storage, &root_id, &mut hash_data);
+		}
+		merkle_hashes
+	}
+
+	pub fn get(&self, octant_id: &OctantId) -> Option<OctantHash> {
+		self.hashes.get(octant_id).copied()
+	}
+
+	pub fn subtree_hash(&self, octant_id: &OctantId) -> Option<u64> {
+		self.get(octant_id).map(|octant_hash| octant_hash.subtree)
+	}
+
+	pub fn len(&self) -> usize {
+		self.hashes.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.hashes.is_empty()
+	}
+
+	/// Rehash octant and all of its ancestors after octant or its branch was edited.
+	/// Children without hash are hashed with their whole branch, so after inserting branch it is enough to update its top.
+	///
+	/// ## Arguments
+	/// * `octant_id` - Edited octant, after removal parent of removed octant, see `forget`.
+	///
+	/// ## Errors
+	/// Returns `StorageError::InvalidOctantId` when octant doesn't exist.
+	pub fn update_path<Storage, F>(&mut self, storage: &Storage, octant_id: &OctantId, mut hash_data: F) -> StorageResult<()>
+	where
+		Storage: OctantStorage<OctantId = OctantId>,
+		F: FnMut(&Storage::Data) -> u64
+	{
+		let ancestors: Storage::ParentIdIterator = storage.get_ancestors_for(octant_id).ok_or(StorageError::InvalidOctantId)?;
+		self.hash_octant(storage, octant_id, &mut hash_data);
+		for ancestor_id in ancestors {
+			self.hash_octant(storage, &ancestor_id, &mut hash_data);
+		}
+		Ok(())
+	}
+
+	/// Drop hashes of octants which don't exist in storage anymore.
+	pub fn retain_existing<Storage>(&mut self, storage: &Storage)
+	where Storage: OctantStorage<OctantId = OctantId> {
+		self.hashes.retain(|octant_id, _| storage.get_octant(octant_id).is_some());
+	}
+
+	fn hash_branch<Storage, F>(&mut self, storage: &Storage, octant_id: &OctantId, hash_data: &mut F) -> Option<OctantHash>
+	where
+		Storage: OctantStorage<OctantId = OctantId>,
+		F: FnMut(&Storage::Data) -> u64
+	{
+		for placement in OctantPlacement::OCTANTS_ORDERED {
+			if let Ok(child_id) = storage.get_existing_child(octant_id, placement) {
+				self.hash_branch(storage, &child_id, hash_data);
+			}
+		}
+		self.hash_octant(storage, octant_id, hash_data)
+	}
+
+	/// Hash octant from hashes of its children, children without hash are hashed first.
+	fn hash_octant<Storage, F>(&mut self, storage: &Storage, octant_id: &OctantId, hash_data: &mut F) -> Option<OctantHash>
+	where
+		Storage: OctantStorage<OctantId = OctantId>,
+		F: FnMut(&Storage::Data) -> u64
+	{
+		let data_hash: u64 = hash_data(storage.get_octant(octant_id)?);
+		let mut hasher = StableHasher::default();
+		hasher.write_u64(data_hash);
+		for placement in OctantPlacement::OCTANTS_ORDERED {
+			let Ok(child_id) = storage.get_existing_child(octant_id, placement) else {
+				continue;
+			};
+			let child_hash: Option<OctantHash> = match self.get(&child_id) {
+				Some(child_hash) => Some(child_hash),
+				None => self.hash_branch(storage, &child_id, hash_data)
+			};
+			hasher.write_u8(placement as u8);
+			hasher.write_u64(child_hash.map_or(0, |child_hash| child_hash.subtree));
+		}
+
+		let octant_hash = OctantHash{
+			data: data_hash,
+			subtree: hasher.finish()
+		};
+		self.hashes.insert(*octant_id, octant_hash);
+		Some(octant_hash)
+	}
+}
+
+impl MerkleHashes<MortonOctantId> {
+	/// Drop hashes of removed octant and its whole branch, so branch inserted at its place later
+	/// isn't hashed from stale hashes of removed descendants.
+	///
+	/// ## Returns
+	/// Hash of removed octant, `None` when it had no hash.
+	pub fn forget(&mut self, octant_id: &MortonOctantId) -> Option<OctantHash> {
+		let octant_hash: OctantHash = self.hashes.remove(octant_id)?;
+		let mut to_be_forgotten: Vec<MortonOctantId> = vec![*octant_id];
+		while let Some(forgotten_id) = to_be_forgotten.pop() {
+			if forgotten_id.compute_depth() >= MortonOctantId::MAX_DEPTH {
+				continue;
+			}
+			for child_id in forgotten_id.children_ids() {
+				if self.hashes.remove(&child_id).is_some() {
+					to_be_forgotten.push(child_id);
+				}
+			}
+		}
+		Some(octant_hash)
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MerkleChange {
+	/// Branch exists only in right tree.
+	Added,
+	/// Branch exists only in left tree.
+	Removed,
+	/// Octant exists in both trees with different data, its children are compared separately.
+	Changed
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleDifference<OctantId> {
+	/// Id from right tree for added branches, otherwise id from left tree.
+	pub octant_id: OctantId,
+	/// Placements leading from root to octant, empty for root.
+	pub path: Vec<OctantPlacement>,
+	pub change: MerkleChange
+}
+
+impl<Storage: OctantStorage> OctreeBase<Storage>
+where Storage::OctantId: Hash + Eq {
+	/// Hash every octant of tree, see `MerkleHashes::compute`.
+	pub fn merkle_hashes<F>(&self, hash_data: F) -> MerkleHashes<Storage::OctantId>
+	where F: FnMut(&Storage::Data) -> u64 {
+		MerkleHashes::compute(&self.octants, hash_data)
+	}
+}
+
+/// Compare trees top-down, descending only into subtrees with different hashes.
+///
+/// ## Returns
+/// Minimal set of differences, added and removed branches are reported only by their top octant,
+/// octants without hash are treated as different.
+pub fn diff_merkle_hashes<Left, Right>(
+	left: &Left,
+	left_hashes: &MerkleHashes<Left::OctantId>,
+	right: &Right,
+	right_hashes: &MerkleHashes<Left::OctantId>
+) -> Vec<MerkleDifference<Left::OctantId>>
+where
+	Left: OctantStorage,
+	Left::OctantId: Hash + Eq,
+	Right: OctantStorage<OctantId = Left::OctantId>
+{
+	let mut differences: Vec<MerkleDifference<Left::OctantId>> = Vec::new();
+	let left_root: Option<Left::OctantId> = Some(left.get_root_id()).filter(|root_id| left.get_octant(root_id).is_some());
+	let right_root: Option<Left::OctantId> = Some(right.get_root_id()).filter(|root_id| right.get_octant(root_id).is_some());
+	let mut comparison = MerkleComparison{
+		left,
+		left_hashes,
+		right,
+		right_hashes,
+		path: Vec::new(),
+		differences: &mut differences
+	};
+	comparison.compare(left_root, right_root);
+	differences
+}
+
+struct MerkleComparison<'a, Left: OctantStorage, Right>
+where Left::OctantId: Hash + Eq {
+	left: &'a Left,
+	left_hashes: &'a MerkleHashes<Left::OctantId>,
+	right: &'a Right,
+	right_hashes: &'a MerkleHashes<Left::OctantId>,
+	path: Vec<OctantPlacement>,
+	differences: &'a mut Vec<MerkleDifference<Left::OctantId>>
+}
+
+impl<Left, Right> MerkleComparison<'_, Left, Right>
+where
+	Left: OctantStorage,
+	Left::OctantId: Hash + Eq,
+	Right: OctantStorage<OctantId = Left::OctantId>
+{
+	fn compare(&mut self, left_id: Option<Left::OctantId>, right_id: Option<Left::OctantId>) {
+		let (left_id, right_id) = match (left_id, right_id) {
+			(Some(left_id), Some(right_id)) => (left_id, right_id),
+			(Some(left_id), None) => return self.report(left_id, MerkleChange::Removed),
+			(None, Some(right_id)) => return self.report(right_id, MerkleChange::Added),
+			(None, None) => return
+		};
+
+		let left_hash: Option<OctantHash> = self.left_hashes.get(&left_id);
+		let right_hash: Option<OctantHash> = self.right_hashes.get(&right_id);
+		if left_hash.is_some() && left_hash == right_hash {
+			return;
+		}
+		let data_equal: bool = left_hash.zip(right_hash).is_some_and(|(left_hash, right_hash)| left_hash.data == right_hash.data);
+		if !data_equal {
+			self.report(left_id, MerkleChange::Changed);
+		}
+
+		for placement in OctantPlacement::OCTANTS_ORDERED {
+			let left_child: Option<Left::OctantId> = self.left.get_existing_child(&left_id, placement).ok();
+			let right_child: Option<Left::OctantId> = self.right.get_existing_child(&right_id, placement).ok();
+			self.path.push(placement);
+			self.compare(left_child, right_child);
+			self.path.pop();
+		}
+	}
+
+	fn report(&mut self, octant_id: Left::OctantId, change: MerkleChange) {
+		self.differences.push(
+			MerkleDifference{
+				octant_id,
+				path: self.path.clone(),
+				change
+			}
+		);
+	}
+}
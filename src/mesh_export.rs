@@ -0,0 +1,209 @@
+//! Export of leaf octants into PLY and OBJ files for viewing in external tools.
+//!
+//! Leaves are exported either as cubes, wireframe boxes or as point cloud of their centers.
+//! OBJ vertex colors use widely supported `v x y z r g b` extension.
+use std::io::{self, Write};
+
+use glam::Vec3A;
+
+use super::morton_based_storage::morton_octant_id::MortonOctantId;
+use super::octant_storage_trait::OctantStorage;
+use super::octree_base::SearchControlFlow;
+use super::spatial_octree_base::SpatialOctreeBase;
+use super::voxels::voxel_cube::VolumetricCube;
+use super::Depth;
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MeshFormat {
+	PlyAscii,
+	PlyBinary,
+	Obj
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CubeStyle {
+	/// 12 edges per leaf.
+	Wireframe,
+	/// 6 quads per leaf, wound counter clockwise when seen from outside.
+	Solid
+}
+
+/// Distinct color for every depth, usable in color callbacks for per-depth coloring.
+pub fn depth_color(depth: Depth) -> [u8; 3] {
+	const DEPTH_COLORS: [[u8; 3]; 8] = [
+		[230, 25, 75],
+		[60, 180, 75],
+		[255, 225, 25],
+		[0, 130, 200],
+		[245, 130, 48],
+		[145, 30, 180],
+		[70, 240, 240],
+		[240, 50, 230]
+	];
+	DEPTH_COLORS[depth as usize % DEPTH_COLORS.len()]
+}
+
+// corners are indexed by [x, y, z] bits same as `OctantPlacement`
+const CUBE_EDGES: [[u32; 2]; 12] = [
+	[0, 1], [2, 3], [4, 5], [6, 7],
+	[0, 2], [1, 3], [4, 6], [5, 7],
+	[0, 4], [1, 5], [2, 6], [3, 7]
+];
+const CUBE_FACES: [[u32; 4]; 6] = [
+	[0, 1, 3, 2], [4, 6, 7, 5],
+	[0, 4, 5, 1], [2, 3, 7, 6],
+	[0, 2, 6, 4], [1, 5, 7, 3]
+];
+
+/// Geometry collected from leaves before it is written in requested format.
+#[derive(Default)]
+struct ExportMesh {
+	vertices: Vec<(Vec3A, [u8; 3])>,
+	edges: Vec<[u32; 2]>,
+	faces: Vec<[u32; 4]>,
+	morton_ids: Vec<u64>
+}
+
+impl<Storage: OctantStorage> SpatialOctreeBase<Storage, VolumetricCube> {
+	/// Write every leaf octant as cube or wireframe box.
+	///
+	/// ## Arguments
+	/// * `vertex_color` - Maps depth and data of leaf to color of its vertices, `depth_color` can be used for per-depth coloring.
+	pub fn export_leaves<W, F>(&self, writer: &mut W, format: MeshFormat, style: CubeStyle, mut vertex_color: F) -> io::Result<()>
+	where
+		W: Write,
+		F: FnMut(Depth, &Storage::Data) -> [u8; 3]
+	{
+		let mut mesh: ExportMesh = ExportMesh::default();
+		for (depth, octant_id, voxel) in self.collect_leaves()? {
+			let color: [u8; 3] = vertex_color(depth, self.octants().get_octant(&octant_id).expect("Leaf was found in storage."));
+			let first_vertex: u32 = mesh.vertices.len() as u32;
+			mesh.vertices.extend(voxel.get_corners().map(|corner| (corner, color)));
+			match style {
+				CubeStyle::Wireframe => mesh.edges.extend(CUBE_EDGES.map(|edge| edge.map(|vertex| vertex + first_vertex))),
+				CubeStyle::Solid => mesh.faces.extend(CUBE_FACES.map(|face| face.map(|vertex| vertex + first_vertex)))
+			}
+		}
+
+		match format {
+			MeshFormat::PlyAscii => write_ply(writer, &mesh, false),
+			MeshFormat::PlyBinary => write_ply(writer, &mesh, true),
+			MeshFormat::Obj => write_obj(writer, &mesh)
+		}
+	}
+
+	fn collect_leaves(&self) -> io::Result<Vec<(Depth, Storage::OctantId, VolumetricCube)>> {
+		let mut leaves: Vec<(Depth, Storage::OctantId, VolumetricCube)> = Vec::new();
+		self.depth_first_search_from_root(
+			&mut |depth, octant_id: &Storage::OctantId, voxel: &VolumetricCube|{
+				let is_leaf: bool = self.octants().get_existing_children(octant_id)
+					.map(|children| children.iter().all(Option::is_none))
+					.unwrap_or(true);
+				if is_leaf {
+					leaves.push((depth, *octant_id, *voxel));
+				}
+				SearchControlFlow::Continue
+			}
+		).map_err(|error| io::Error::other(format!("{:?}", error)))?;
+		Ok(leaves)
+	}
+}
+
+impl<Storage: OctantStorage<OctantId = MortonOctantId>> SpatialOctreeBase<Storage, VolumetricCube> {
+	/// Write centers of leaf octants as PLY point cloud, with `MortonOctantId` of every leaf in `morton_id` vertex property.
+	///
+	/// `morton_id` is stored as `double`, which represents all Morton ids exactly, since they use at most 49 bits.
+	pub fn export_leaf_centers_ply<W, F>(&self, writer: &mut W, binary: bool, mut vertex_color: F) -> io::Result<()>
+	where
+		W: Write,
+		F: FnMut(Depth, &Storage::Data) -> [u8; 3]
+	{
+		let mut mesh: ExportMesh = ExportMesh::default();
+		for (depth, octant_id, voxel) in self.collect_leaves()? {
+			let color: [u8; 3] = vertex_color(depth, self.octants().get_octant(&octant_id).expect("Leaf was found in storage."));
+			mesh.vertices.push((voxel.center(), color));
+			mesh.morton_ids.push(octant_id.as_morton());
+		}
+		write_ply(writer, &mesh, binary)
+	}
+}
+
+fn write_ply<W: Write>(writer: &mut W, mesh: &ExportMesh, binary: bool) -> io::Result<()> {
+	let has_morton_ids: bool = !mesh.morton_ids.is_empty();
+	writeln!(writer, "ply")?;
+	writeln!(writer, "format {} 1.0", if binary { "binary_little_endian" } else { "ascii" })?;
+	writeln!(writer, "element vertex {}", mesh.vertices.len())?;
+	writeln!(writer, "property float x\nproperty float y\nproperty float z")?;
+	writeln!(writer, "property uchar red\nproperty uchar green\nproperty uchar blue")?;
+	if has_morton_ids {
+		writeln!(writer, "property double morton_id")?;
+	}
+	if !mesh.edges.is_empty() {
+		writeln!(writer, "element edge {}", mesh.edges.len())?;
+		writeln!(writer, "property int vertex1\nproperty int vertex2")?;
+	}
+	if !mesh.faces.is_empty() {
+		writeln!(writer, "element face {}", mesh.faces.len())?;
+		writeln!(writer, "property list uchar int vertex_indices")?;
+	}
+	writeln!(writer, "end_header")?;
+
+	for (vertex_index, (position, color)) in mesh.vertices.iter().enumerate() {
+		let morton_id: Option<u64> = mesh.morton_ids.get(vertex_index).copied();
+		if binary {
+			for axis in position.to_array() {
+				writer.write_all(&axis.to_le_bytes())?;
+			}
+			writer.write_all(color)?;
+			if let Some(morton_id) = morton_id {
+				writer.write_all(&(morton_id as f64).to_le_bytes())?;
+			}
+		}
+		else {
+			write!(writer, "{} {} {} {} {} {}", position.x, position.y, position.z, color[0], color[1], color[2])?;
+			if let Some(morton_id) = morton_id {
+				write!(writer, " {}", morton_id)?;
+			}
+			writeln!(writer)?;
+		}
+	}
+
+	for edge in mesh.edges.iter() {
+		if binary {
+			writer.write_all(&(edge[0] as i32).to_le_bytes())?;
+			writer.write_all(&(edge[1] as i32).to_le_bytes())?;
+		}
+		else {
+			writeln!(writer, "{} {}", edge[0], edge[1])?;
+		}
+	}
+
+	for face in mesh.faces.iter() {
+		if binary {
+			writer.write_all(&[face.len() as u8])?;
+			for vertex in face {
+				writer.write_all(&(*vertex as i32).to_le_bytes())?;
+			}
+		}
+		else {
+			writeln!(writer, "{} {} {} {} {}", face.len(), face[0], face[1], face[2], face[3])?;
+		}
+	}
+	Ok(())
+}
+
+fn write_obj<W: Write>(writer: &mut W, mesh: &ExportMesh) -> io::Result<()> {
+	for (position, color) in mesh.vertices.iter() {
+		let [red, green, blue] = color.map(|channel| channel as f32 / 255.0);
+		writeln!(writer, "v {} {} {} {} {} {}", position.x, position.y, position.z, red, green, blue)?;
+	}
+	// OBJ indices start at 1
+	for edge in mesh.edges.iter() {
+		writeln!(writer, "l {} {}", edge[0] + 1, edge[1] + 1)?;
+	}
+	for face in mesh.faces.iter() {
+		writeln!(writer, "f {} {} {} {}", face[0] + 1, face[1] + 1, face[2] + 1, face[3] + 1)?;
+	}
+	Ok(())
+}
@@ -0,0 +1,226 @@
+//! Voxelization of triangle meshes into spatial octree.
+//!
+//! Only octants intersecting triangles are subdivided, optional solid fill marks octants inside
+//! of closed mesh, so interior is represented by few large octants.
+use glam::Vec3A;
+
+use super::octant_meta::OctantPlacement;
+use super::octant_storage_trait::{ModifiableOctantStorage, StorageError, StorageResult};
+use super::spatial_octree_base::SpatialOctreeBase;
+use super::voxel_trait::Voxel;
+use super::voxels::voxel_cube::VolumetricCube;
+use super::Depth;
+
+
+/// Indexed triangle mesh, every three indices form one triangle.
+#[derive(Clone, Copy, Debug)]
+pub struct TriangleMesh<'a> {
+	positions: &'a [Vec3A],
+	indices: &'a [u32]
+}
+
+/// Rule deciding whether point is inside of closed mesh.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FillRule {
+	/// Inside when ray from point crosses odd number of triangles.
+	Parity,
+	/// Inside when triangles crossed by ray from point don't cancel out by their orientation.
+	Winding
+}
+
+/// Octant created by voxelization, passed to closure creating octant data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MeshOctant<'a> {
+	/// Octant intersects triangles with given indices.
+	Surface(&'a [usize]),
+	/// Octant is inside of mesh and doesn't intersect any triangle.
+	Interior
+}
+
+// slightly tilted, so rays don't pass exactly through edges of axis aligned meshes
+const RAY_DIRECTION: Vec3A = Vec3A::new(1.0, 0.000_123_4, 0.000_271_8);
+
+impl<'a> TriangleMesh<'a> {
+	/// ## Panics
+	/// When number of indices is not multiple of 3 or index is out of bounds of positions.
+	pub fn new(positions: &'a [Vec3A], indices: &'a [u32]) -> Self {
+		assert!(indices.len().is_multiple_of(3), "Number of indices must be multiple of 3.");
+		assert!(indices.iter().all(|&index| (index as usize) < positions.len()), "Index out of bounds of positions.");
+		TriangleMesh{
+			positions,
+			indices
+		}
+	}
+
+	pub fn triangle_count(&self) -> usize {
+		self.indices.len() / 3
+	}
+
+	pub fn triangle(&self, triangle_index: usize) -> [Vec3A; 3] {
+		let first_index: usize = triangle_index * 3;
+		[
+			self.positions[self.indices[first_index] as usize],
+			self.positions[self.indices[first_index + 1] as usize],
+			self.positions[self.indices[first_index + 2] as usize]
+		]
+	}
+
+	/// Check whether point is inside of mesh, mesh is expected to be closed.
+	pub fn contains_point(&self, point: Vec3A, fill_rule: FillRule) -> bool {
+		let mut crossings: usize = 0;
+		let mut winding: i32 = 0;
+		for triangle_index in 0..self.triangle_count() {
+			let Some(facing) = ray_crosses_triangle(point, RAY_DIRECTION, self.triangle(triangle_index)) else {
+				continue;
+			};
+			crossings += 1;
+			winding += if facing > 0.0 { 1 } else { -1 };
+		}
+		match fill_rule {
+			FillRule::Parity => crossings % 2 == 1,
+			FillRule::Winding => winding != 0
+		}
+	}
+}
+
+impl<Storage: ModifiableOctantStorage> SpatialOctreeBase<Storage, VolumetricCube> {
+	/// Build tree from triangle mesh, see `voxelize_mesh_from_storage`.
+	///
+	/// ## Examples
+	/// ```
+	/// use glam::Vec3A;
+	/// use modsvo::{mesh_voxelizer::{MeshOctant, TriangleMesh}, voxels::voxel_cube::VolumetricCube, SpatialSparseOctree};
+	///
+	/// let positions = [Vec3A::new(-0.5, -0.5, 0.1), Vec3A::new(0.5, -0.5, 0.1), Vec3A::new(0.0, 0.5, 0.1)];
+	/// let octree = SpatialSparseOctree::<usize>::voxelize_mesh(
+	///     VolumetricCube::new(Vec3A::ZERO, 1.0),
+	///     TriangleMesh::new(&positions, &[0, 1, 2]),
+	///     4,
+	///     None,
+	///     |mesh_octant, _| match mesh_octant {
+	///         MeshOctant::Surface(triangles) => triangles.len(),
+	///         MeshOctant::Interior => 0
+	///     }
+	/// ).unwrap();
+	/// ```
+	pub fn voxelize_mesh<F>(root_voxel: VolumetricCube, mesh: TriangleMesh, max_depth: Depth, fill_rule: Option<FillRule>, mut octant_data: F) -> StorageResult<Self>
+	where
+		Storage: Default,
+		F: FnMut(MeshOctant, &VolumetricCube) -> Storage::Data
+	{
+		let root_triangles: Vec<usize> = (0..mesh.triangle_count())
+			.filter(|&triangle_index| root_voxel.intersects_triangle(mesh.triangle(triangle_index)))
+			.collect();
+		let root_data: Storage::Data = octant_data(MeshOctant::Surface(&root_triangles), &root_voxel);
+		let mut octree: Self = Self::new_with_root(root_voxel, root_data);
+		let root_id: Storage::OctantId = octree.get_root_id();
+		voxelize_mesh_from_storage(octree.octants_mut(), &root_id, &root_voxel, mesh, max_depth, fill_rule, octant_data)?;
+		Ok(octree)
+	}
+}
+
+/// Voxelize triangle mesh into branch of octant, creating only octants which intersect triangles
+/// and with `fill_rule` also octants inside of mesh.
+///
+/// ## Arguments
+/// * `max_depth` - Absolute depth at which surface octants stop being subdivided.
+/// * `fill_rule` - Rule used to find interior octants, `None` voxelizes only surface.
+/// * `octant_data` - Creates data of every created octant, surface octants get indices of intersecting triangles.
+///
+/// ## Errors
+/// Returns `StorageError::OverMaxDepth` when `max_depth` exceeds depth supported by storage.
+#[allow(clippy::too_many_arguments)]
+pub fn voxelize_mesh_from_storage<Storage, F>(
+	storage: &mut Storage,
+	octant_id: &Storage::OctantId,
+	octant_voxel: &VolumetricCube,
+	mesh: TriangleMesh,
+	max_depth: Depth,
+	fill_rule: Option<FillRule>,
+	mut octant_data: F
+) -> StorageResult<()>
+where
+	Storage: ModifiableOctantStorage,
+	F: FnMut(MeshOctant, &VolumetricCube) -> Storage::Data
+{
+	if max_depth > storage.get_max_depth() {
+		return Err(StorageError::OverMaxDepth(storage.get_max_depth()));
+	}
+	let depth: Depth = storage.get_octant_depth(octant_id).ok_or(StorageError::InvalidOctantId)?;
+	let triangles: Vec<usize> = (0..mesh.triangle_count())
+		.filter(|&triangle_index| octant_voxel.intersects_triangle(mesh.triangle(triangle_index)))
+		.collect();
+	let mut voxelizer = Voxelizer{
+		mesh,
+		max_depth,
+		fill_rule,
+		octant_data: &mut octant_data
+	};
+	voxelizer.voxelize_recursive(storage, depth, octant_id, octant_voxel, &triangles)
+}
+
+struct Voxelizer<'a, 'f, F> {
+	mesh: TriangleMesh<'a>,
+	max_depth: Depth,
+	fill_rule: Option<FillRule>,
+	octant_data: &'f mut F
+}
+
+impl<F> Voxelizer<'_, '_, F> {
+	fn voxelize_recursive<Storage>(&mut self, storage: &mut Storage, depth: Depth, octant_id: &Storage::OctantId, voxel: &VolumetricCube, triangles: &[usize]) -> StorageResult<()>
+	where
+		Storage: ModifiableOctantStorage,
+		F: FnMut(MeshOctant, &VolumetricCube) -> Storage::Data
+	{
+		if depth >= self.max_depth {
+			return Ok(());
+		}
+
+		for child_placement in OctantPlacement::OCTANTS_ORDERED {
+			let child_voxel: VolumetricCube = voxel.make_sub_voxel(child_placement);
+			let child_triangles: Vec<usize> = triangles.iter()
+				.copied()
+				.filter(|&triangle_index| child_voxel.intersects_triangle(self.mesh.triangle(triangle_index)))
+				.collect();
+
+			if !child_triangles.is_empty() {
+				let child_data: Storage::Data = (self.octant_data)(MeshOctant::Surface(&child_triangles), &child_voxel);
+				let (child_id, _) = storage.insert_octant(octant_id, child_placement, child_data)?;
+				self.voxelize_recursive(storage, depth + 1, &child_id, &child_voxel, &child_triangles)?;
+			}
+			else if let Some(fill_rule) = self.fill_rule {
+				// octant without triangles is whole on one side of surface, so its center decides
+				if self.mesh.contains_point(child_voxel.center(), fill_rule) {
+					let child_data: Storage::Data = (self.octant_data)(MeshOctant::Interior, &child_voxel);
+					storage.insert_octant(octant_id, child_placement, child_data)?;
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Möller–Trumbore ray/triangle intersection, returns facing of triangle towards ray direction when ray crosses it.
+fn ray_crosses_triangle(origin: Vec3A, direction: Vec3A, triangle: [Vec3A; 3]) -> Option<f32> {
+	let first_edge: Vec3A = triangle[1] - triangle[0];
+	let second_edge: Vec3A = triangle[2] - triangle[0];
+	let direction_cross_edge: Vec3A = direction.cross(second_edge);
+	let determinant: f32 = first_edge.dot(direction_cross_edge);
+	if determinant.abs() <= f32::EPSILON {
+		return None;
+	}
+
+	let inverse_determinant: f32 = 1.0 / determinant;
+	let to_origin: Vec3A = origin - triangle[0];
+	let u: f32 = to_origin.dot(direction_cross_edge) * inverse_determinant;
+	if !(0.0..=1.0).contains(&u) {
+		return None;
+	}
+	let origin_cross_edge: Vec3A = to_origin.cross(first_edge);
+	let v: f32 = direction.dot(origin_cross_edge) * inverse_determinant;
+	if v < 0.0 || u + v > 1.0 {
+		return None;
+	}
+	let distance: f32 = second_edge.dot(origin_cross_edge) * inverse_determinant;
+	(distance > 0.0).then_some(determinant)
+}
@@ -0,0 +1,506 @@
+use std::collections::HashMap;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use super::super::octant_storage_trait::{OctantStorage, ModifiableOctantStorage, StorageError, StorageResult};
+use super::hashed_octant_storage::hashed_morton_map_heap_bytes;
+use super::morton_octant_id::{MortonOctantId, MortonParentIdIterator};
+use super::super::Depth;
+use super::super::octant_meta::OctantPlacement;
+
+
+type HashedMortonMap<Data> = HashMap<MortonOctantId, Data>;
+
+/// Octant storage which can be modified from many threads at once.
+///
+/// Octants are split into shards by their Morton prefix at `shard_depth`, so whole branch below
+/// `shard_depth` always lives in single shard and octants above it live in separate top shard.
+/// Threads working on different branches lock different shards and do not wait on each other.
+///
+/// ## Concurrent removal
+/// `remove_octant` removes octant and cascades through its branch while holding lock of shard containing removed octant,
+/// shards of descendants are swept afterwards. Inserting under octant which is being removed is serialized with removal,
+/// so it either fails with `StorageError::InvalidOctantId` or its octant is removed together with the branch,
+/// removal never leaves orphaned octants behind.
+///
+/// ## Trait access
+/// `OctantStorage` and `ModifiableOctantStorage` hand out references, so they are implemented
+/// for guards returned by `read()` and `write()`, which lock all shards for their lifetime.
+/// Guard returned by `read()` can't change octants, so its `get_octant_mut` always returns `None`.
+pub struct ConcurrentOctantStorage<Data>{
+	shard_depth: Depth,
+	/// shard 0 holds octants above `shard_depth`, others hold branches with Morton prefix at `shard_depth`
+	shards: Vec<RwLock<HashedMortonMap<Data>>>
+}
+
+impl<Data> ConcurrentOctantStorage<Data> {
+	pub const DEFAULT_SHARD_DEPTH: Depth = 2;
+	pub const MAX_SHARD_DEPTH: Depth = 4;
+
+	/// Create empty storage(without root) with `8^shard_depth` branch shards.
+	///
+	/// ## Panics
+	/// When `shard_depth` is `0` or over `MAX_SHARD_DEPTH`.
+	pub fn with_shard_depth(shard_depth: Depth) -> Self {
+		assert!(shard_depth > 0 && shard_depth <= Self::MAX_SHARD_DEPTH, "Shard depth must be in range 1..={}.", Self::MAX_SHARD_DEPTH);
+		let shard_count: usize = 1 + (1_usize << (3 * shard_depth as usize));
+		ConcurrentOctantStorage{
+			shard_depth,
+			shards: (0..shard_count).map(|_| RwLock::new(HashMap::new())).collect()
+		}
+	}
+
+	pub fn new_with_root_and_shard_depth(root_custom_data: Data, shard_depth: Depth) -> Self {
+		let storage = Self::with_shard_depth(shard_depth);
+		storage.insert_root(root_custom_data);
+		storage
+	}
+
+	pub fn shard_depth(&self) -> Depth {
+		self.shard_depth
+	}
+
+	pub fn shard_count(&self) -> usize {
+		self.shards.len()
+	}
+
+	pub fn get_root_id(&self) -> MortonOctantId {
+		MortonOctantId::ROOT_OCTANT_ID
+	}
+
+	pub fn get_max_depth(&self) -> Depth {
+		MortonOctantId::MAX_DEPTH
+	}
+
+	/// Count of all stored octants, might be outdated when storage is modified concurrently.
+	pub fn len(&self) -> usize {
+		self.shards.iter()
+			.map(|shard| read_shard(shard).len())
+			.sum()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Estimate of heap memory held by all shards, might be outdated when storage is modified concurrently.
+	pub fn estimated_heap_bytes(&self) -> usize {
+		self.shards.iter()
+			.map(|shard| hashed_morton_map_heap_bytes(&read_shard(shard)))
+			.sum()
+	}
+
+	pub fn contains_octant(&self, octant_id: &MortonOctantId) -> bool {
+		read_shard(self.shard_of(octant_id)).contains_key(octant_id)
+	}
+
+	/// Call function with reference to octant data while holding read lock of its shard.
+	pub fn with_octant<F, R>(&self, octant_id: &MortonOctantId, read_fn: F) -> Option<R>
+	where F: FnOnce(&Data) -> R {
+		read_shard(self.shard_of(octant_id)).get(octant_id).map(read_fn)
+	}
+
+	/// Call function with mutable reference to octant data while holding write lock of its shard.
+	pub fn with_octant_mut<F, R>(&self, octant_id: &MortonOctantId, modify_fn: F) -> Option<R>
+	where F: FnOnce(&mut Data) -> R {
+		write_shard(self.shard_of(octant_id)).get_mut(octant_id).map(modify_fn)
+	}
+
+	pub fn get_octant_cloned(&self, octant_id: &MortonOctantId) -> Option<Data>
+	where Data: Clone {
+		self.with_octant(octant_id, Data::clone)
+	}
+
+	pub fn get_existing_child(&self, parent_id: &MortonOctantId, child_placement: OctantPlacement) -> StorageResult<MortonOctantId> {
+		if parent_id.compute_depth() >= MortonOctantId::MAX_DEPTH {
+			return Err(StorageError::OverMaxDepth(MortonOctantId::MAX_DEPTH));
+		}
+
+		let child_id: MortonOctantId = parent_id.child_id_by_placement(child_placement);
+		if self.contains_octant(&child_id) {
+			Ok(child_id)
+		}
+		else if self.contains_octant(parent_id) {
+			Err(StorageError::ChildNotFound(Some(child_placement)))
+		}
+		else {
+			Err(StorageError::InvalidOctantId)
+		}
+	}
+
+	pub fn get_existing_children(&self, parent_id: &MortonOctantId) -> StorageResult<[Option<MortonOctantId>; OctantPlacement::OCTANTS_COUNT]> {
+		if parent_id.compute_depth() >= MortonOctantId::MAX_DEPTH {
+			return Err(StorageError::OverMaxDepth(MortonOctantId::MAX_DEPTH));
+		}
+		if !self.contains_octant(parent_id) {
+			return Err(StorageError::InvalidOctantId);
+		}
+
+		let children_ids = parent_id.children_ids();
+		if parent_id.compute_depth() < self.shard_depth {
+			// children at shard depth start separate branches, so each of them lives in its own shard
+			return Ok(children_ids.map(|child_id| self.contains_octant(&child_id).then_some(child_id)));
+		}
+		let children_shard = read_shard(self.shard_of(&children_ids[0]));
+		Ok(children_ids.map(|child_id| children_shard.contains_key(&child_id).then_some(child_id)))
+	}
+
+	/// Initializes or reinitialize root node and returns previously held data if there were any.
+	pub fn insert_root(&self, root_custom_data: Data) -> Option<Data> {
+		let root_id = self.get_root_id();
+		write_shard(self.shard_of(&root_id)).insert(root_id, root_custom_data)
+	}
+
+	/// Create or replace child octant, parent's existence is checked while child's shard is locked,
+	/// so child can't be inserted under parent removed at the same time.
+	///
+	/// ## Errors
+	///   * OverMaxDepth - when created child lies below maximum depth
+	///   * InvalidOctantId - when parent_id is not found in storage
+	pub fn insert_octant(&self, parent_id: &MortonOctantId, child_octant_placement: OctantPlacement, custom_data: Data) -> StorageResult<(MortonOctantId, Option<Data>)> {
+		if parent_id.compute_depth() >= MortonOctantId::MAX_DEPTH {
+			return Err(StorageError::OverMaxDepth(MortonOctantId::MAX_DEPTH));
+		}
+
+		let child_id: MortonOctantId = parent_id.child_id_by_placement(child_octant_placement);
+		let parent_shard_index: usize = self.shard_index(parent_id);
+		let child_shard_index: usize = self.shard_index(&child_id);
+
+		let old_data: Option<Data> = if parent_shard_index == child_shard_index {
+			let mut shard = write_shard(&self.shards[child_shard_index]);
+			let _ = shard.get(parent_id).ok_or(StorageError::InvalidOctantId)?;
+			shard.insert(child_id, custom_data)
+		}
+		else {
+			// lock order is always top shard first, then branch shard
+			let parent_shard = read_shard(&self.shards[parent_shard_index]);
+			let _ = parent_shard.get(parent_id).ok_or(StorageError::InvalidOctantId)?;
+			write_shard(&self.shards[child_shard_index]).insert(child_id, custom_data)
+		};
+
+		Ok((child_id, old_data))
+	}
+
+	/// Create or replace all children of parent.
+	///
+	/// ## Errors
+	///   * OverMaxDepth - when created children lie below maximum depth
+	///   * InvalidOctantId - when parent_id is not found in storage
+	pub fn subdivide<F>(&self, parent_id: &MortonOctantId, mut create_custom_data: F) -> StorageResult<[MortonOctantId; OctantPlacement::OCTANTS_COUNT]>
+	where F: FnMut(OctantPlacement) -> Data {
+		for child_placement in OctantPlacement::OCTANTS_ORDERED {
+			self.insert_octant(parent_id, child_placement, create_custom_data(child_placement))?;
+		}
+		Ok(parent_id.children_ids())
+	}
+
+	/// Removes input octant id and continues down the tree till all nodes of this branch are removed.
+	///
+	/// ## Returns
+	///  `Some(())` when octant id exists and removal was successful, otherwise `None`
+	pub fn remove_octant(&self, octant_id: &MortonOctantId) -> Option<()> {
+		self.remove_branch(octant_id, &mut |_, _|{})
+	}
+
+	/// Same as `remove_octant`, but octant ids and data of removed branch are collected into vector.
+	pub fn remove_octant_and_fill(&self, octant_id: &MortonOctantId, collected_octant_data: &mut Vec<(MortonOctantId, Data)>) -> Option<()> {
+		self.remove_branch(
+			octant_id,
+			&mut |removed_id, removed_data|{
+				collected_octant_data.push((removed_id, removed_data));
+			}
+		)
+	}
+
+	pub fn remove_octant_and_collect(&self, octant_id: &MortonOctantId) -> Option<Vec<(MortonOctantId, Data)>> {
+		let mut collected_octant_data: Vec<(MortonOctantId, Data)> = Vec::new();
+		self.remove_octant_and_fill(octant_id, &mut collected_octant_data)?;
+		Some(collected_octant_data)
+	}
+
+	/// Lock all shards for reading, so storage can be used through `OctantStorage`.
+	pub fn read(&self) -> ConcurrentOctantStorageReadGuard<'_, Data> {
+		ConcurrentOctantStorageReadGuard{
+			shard_depth: self.shard_depth,
+			shards: self.shards.iter().map(read_shard).collect()
+		}
+	}
+
+	/// Lock all shards for writing, so storage can be used through `ModifiableOctantStorage`.
+	pub fn write(&self) -> ConcurrentOctantStorageWriteGuard<'_, Data> {
+		ConcurrentOctantStorageWriteGuard{
+			shard_depth: self.shard_depth,
+			shards: self.shards.iter().map(write_shard).collect()
+		}
+	}
+
+	fn shard_index(&self, octant_id: &MortonOctantId) -> usize {
+		shard_index_for(self.shard_depth, octant_id)
+	}
+
+	fn shard_of(&self, octant_id: &MortonOctantId) -> &RwLock<HashedMortonMap<Data>> {
+		&self.shards[self.shard_index(octant_id)]
+	}
+
+	fn remove_branch<F>(&self, octant_id: &MortonOctantId, on_removed: &mut F) -> Option<()>
+	where F: FnMut(MortonOctantId, Data) {
+		let octant_depth: Depth = octant_id.compute_depth();
+		if octant_depth >= self.shard_depth {
+			let mut shard = write_shard(self.shard_of(octant_id));
+			let _ = shard.get(octant_id)?;
+			remove_from_map_recursive(&mut shard, *octant_id, on_removed);
+			return Some(());
+		}
+
+		// top shard stays locked until all branch shards are swept,
+		// so nothing can be inserted under already removed octants
+		let mut top_shard = write_shard(&self.shards[0]);
+		let _ = top_shard.get(octant_id)?;
+		remove_from_map_recursive(&mut top_shard, *octant_id, on_removed);
+
+		let level_difference: u32 = (self.shard_depth - octant_depth) as u32;
+		let first_prefix: u64 = octant_id.as_morton() << (3 * level_difference);
+		let prefix_count: u64 = 1 << (3 * level_difference);
+		for prefix in first_prefix .. first_prefix + prefix_count {
+			let shard_index: usize = self.shard_index(&MortonOctantId::from_morton_code(prefix));
+			let mut branch_shard = write_shard(&self.shards[shard_index]);
+			remove_from_map_recursive(&mut branch_shard, MortonOctantId::from_morton_code(prefix), on_removed);
+		}
+
+		Some(())
+	}
+}
+
+impl<Data: Default> Default for ConcurrentOctantStorage<Data> {
+	fn default() -> Self {
+		Self::new_with_root_and_shard_depth(Data::default(), Self::DEFAULT_SHARD_DEPTH)
+	}
+}
+
+/// All shards of `ConcurrentOctantStorage` locked for reading.
+pub struct ConcurrentOctantStorageReadGuard<'a, Data> {
+	shard_depth: Depth,
+	shards: Vec<RwLockReadGuard<'a, HashedMortonMap<Data>>>
+}
+
+/// All shards of `ConcurrentOctantStorage` locked for writing.
+pub struct ConcurrentOctantStorageWriteGuard<'a, Data> {
+	shard_depth: Depth,
+	shards: Vec<RwLockWriteGuard<'a, HashedMortonMap<Data>>>
+}
+
+impl<'a, Data> ConcurrentOctantStorageReadGuard<'a, Data> {
+	fn get(&self, octant_id: &MortonOctantId) -> Option<&Data> {
+		self.shards[shard_index_for(self.shard_depth, octant_id)].get(octant_id)
+	}
+}
+
+impl<'a, Data> ConcurrentOctantStorageWriteGuard<'a, Data> {
+	fn get(&self, octant_id: &MortonOctantId) -> Option<&Data> {
+		self.shards[shard_index_for(self.shard_depth, octant_id)].get(octant_id)
+	}
+}
+
+impl<'a, Data> OctantStorage for ConcurrentOctantStorageReadGuard<'a, Data> {
+	type OctantId = MortonOctantId;
+	type ParentIdIterator = MortonParentIdIterator;
+	type Data = Data;
+
+	fn get_max_depth(&self) -> Depth {
+		MortonOctantId::MAX_DEPTH
+	}
+
+	fn get_root_id(&self) -> Self::OctantId {
+		MortonOctantId::ROOT_OCTANT_ID
+	}
+
+	fn get_octant_depth(&self, octant_id: &Self::OctantId) -> Option<Depth> {
+		let _ = self.get(octant_id)?;
+		Some(octant_id.compute_depth())
+	}
+
+	fn get_octant(&self, octant_id: &Self::OctantId) -> Option<&Self::Data> {
+		self.get(octant_id)
+	}
+
+	/// Always `None`, shards are only locked for reading, use `ConcurrentOctantStorage::write()` to change octants.
+	fn get_octant_mut(&mut self, _: &Self::OctantId) -> Option<&mut Self::Data> {
+		None
+	}
+
+	fn get_existing_child(&self, parent_id: &Self::OctantId, child_placement: OctantPlacement) -> StorageResult<Self::OctantId> {
+		existing_child_in(|octant_id| self.get(octant_id).is_some(), parent_id, child_placement)
+	}
+
+	fn get_ancestors_for(&self, octant_id: &Self::OctantId) -> Option<Self::ParentIdIterator> {
+		let _ = self.get(octant_id)?;
+		Some(octant_id.parent_id_iter())
+	}
+
+	fn get_parent(&self, octant_id: &Self::OctantId) -> Option<Self::OctantId> {
+		if octant_id.is_root() {
+			None
+		}
+		else {
+			let _ = self.get(octant_id)?;
+			Some(octant_id.parent_id())
+		}
+	}
+
+	fn which_child_of(&self, parent_id: &Self::OctantId, child_id: &Self::OctantId) -> StorageResult<OctantPlacement> {
+		let _ = self.get(child_id).ok_or(StorageError::InvalidOctantId)?;
+		parent_id.has_child(child_id).ok_or(StorageError::ChildNotFound(None))
+	}
+
+	fn estimated_heap_bytes(&self) -> Option<usize> {
+		Some(self.shards.iter().map(|shard| hashed_morton_map_heap_bytes(shard)).sum())
+	}
+}
+
+impl<'a, Data> OctantStorage for ConcurrentOctantStorageWriteGuard<'a, Data> {
+	type OctantId = MortonOctantId;
+	type ParentIdIterator = MortonParentIdIterator;
+	type Data = Data;
+
+	fn get_max_depth(&self) -> Depth {
+		MortonOctantId::MAX_DEPTH
+	}
+
+	fn get_root_id(&self) -> Self::OctantId {
+		MortonOctantId::ROOT_OCTANT_ID
+	}
+
+	fn get_octant_depth(&self, octant_id: &Self::OctantId) -> Option<Depth> {
+		let _ = self.get(octant_id)?;
+		Some(octant_id.compute_depth())
+	}
+
+	fn get_octant(&self, octant_id: &Self::OctantId) -> Option<&Self::Data> {
+		self.get(octant_id)
+	}
+
+	fn get_octant_mut(&mut self, octant_id: &Self::OctantId) -> Option<&mut Self::Data> {
+		self.shards[shard_index_for(self.shard_depth, octant_id)].get_mut(octant_id)
+	}
+
+	fn get_existing_child(&self, parent_id: &Self::OctantId, child_placement: OctantPlacement) -> StorageResult<Self::OctantId> {
+		existing_child_in(|octant_id| self.get(octant_id).is_some(), parent_id, child_placement)
+	}
+
+	fn get_ancestors_for(&self, octant_id: &Self::OctantId) -> Option<Self::ParentIdIterator> {
+		let _ = self.get(octant_id)?;
+		Some(octant_id.parent_id_iter())
+	}
+
+	fn get_parent(&self, octant_id: &Self::OctantId) -> Option<Self::OctantId> {
+		if octant_id.is_root() {
+			None
+		}
+		else {
+			let _ = self.get(octant_id)?;
+			Some(octant_id.parent_id())
+		}
+	}
+
+	fn which_child_of(&self, parent_id: &Self::OctantId, child_id: &Self::OctantId) -> StorageResult<OctantPlacement> {
+		let _ = self.get(child_id).ok_or(StorageError::InvalidOctantId)?;
+		parent_id.has_child(child_id).ok_or(StorageError::ChildNotFound(None))
+	}
+
+	fn estimated_heap_bytes(&self) -> Option<usize> {
+		Some(self.shards.iter().map(|shard| hashed_morton_map_heap_bytes(shard)).sum())
+	}
+}
+
+impl<'a, Data> ModifiableOctantStorage for ConcurrentOctantStorageWriteGuard<'a, Data> {
+	fn insert_root(&mut self, root_custom_data: Self::Data) -> Option<Self::Data> {
+		let root_id = self.get_root_id();
+		self.shards[shard_index_for(self.shard_depth, &root_id)].insert(root_id, root_custom_data)
+	}
+
+	fn insert_octant(&mut self, parent_id: &Self::OctantId, child_octant_placement: OctantPlacement, custom_data: Self::Data) -> StorageResult<(Self::OctantId, Option<Self::Data>)> {
+		if parent_id.compute_depth() >= MortonOctantId::MAX_DEPTH {
+			return Err(StorageError::OverMaxDepth(MortonOctantId::MAX_DEPTH));
+		}
+
+		let _ = self.get(parent_id).ok_or(StorageError::InvalidOctantId)?;
+		let child_id: MortonOctantId = parent_id.child_id_by_placement(child_octant_placement);
+		let old_data: Option<Data> = self.shards[shard_index_for(self.shard_depth, &child_id)].insert(child_id, custom_data);
+
+		Ok((child_id, old_data))
+	}
+
+	fn remove_octant(&mut self, octant_id: &Self::OctantId) -> Option<()> {
+		self.remove_octant_and_fill(octant_id, &mut Vec::new())
+	}
+
+	fn remove_octant_and_fill(&mut self, octant_id: &Self::OctantId, collected_octant_data: &mut Vec<(Self::OctantId, Self::Data)>) -> Option<()> {
+		let _ = self.get(octant_id)?;
+		let shard_depth: Depth = self.shard_depth;
+		let mut to_be_removed: Vec<MortonOctantId> = vec![*octant_id];
+		while let Some(removed_id) = to_be_removed.pop() {
+			let shard = &mut self.shards[shard_index_for(shard_depth, &removed_id)];
+			let Some(removed_data) = shard.remove(&removed_id) else {
+				continue;
+			};
+			collected_octant_data.push((removed_id, removed_data));
+			if removed_id.compute_depth() < MortonOctantId::MAX_DEPTH {
+				to_be_removed.extend(removed_id.children_ids());
+			}
+		}
+		Some(())
+	}
+}
+
+fn read_shard<Data>(shard: &RwLock<HashedMortonMap<Data>>) -> RwLockReadGuard<'_, HashedMortonMap<Data>> {
+	// map is never left in inconsistent state by panicking thread, so poisoning can be ignored
+	shard.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn write_shard<Data>(shard: &RwLock<HashedMortonMap<Data>>) -> RwLockWriteGuard<'_, HashedMortonMap<Data>> {
+	shard.write().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn shard_index_for(shard_depth: Depth, octant_id: &MortonOctantId) -> usize {
+	let octant_depth: Depth = octant_id.compute_depth();
+	if octant_depth < shard_depth {
+		0
+	}
+	else {
+		let prefix: u64 = octant_id.as_morton() >> (3 * (octant_depth - shard_depth) as u32);
+		let first_prefix: u64 = 1 << (3 * shard_depth as u32);
+		1 + (prefix - first_prefix) as usize
+	}
+}
+
+fn existing_child_in<F>(contains_fn: F, parent_id: &MortonOctantId, child_placement: OctantPlacement) -> StorageResult<MortonOctantId>
+where F: Fn(&MortonOctantId) -> bool {
+	if parent_id.compute_depth() >= MortonOctantId::MAX_DEPTH {
+		return Err(StorageError::OverMaxDepth(MortonOctantId::MAX_DEPTH));
+	}
+
+	let child_id: MortonOctantId = parent_id.child_id_by_placement(child_placement);
+	if contains_fn(&child_id) {
+		Ok(child_id)
+	}
+	else if contains_fn(parent_id) {
+		Err(StorageError::ChildNotFound(Some(child_placement)))
+	}
+	else {
+		Err(StorageError::InvalidOctantId)
+	}
+}
+
+/// Remove octant and its descendants which are stored in the same map.
+fn remove_from_map_recursive<Data, F>(map: &mut HashedMortonMap<Data>, octant_id: MortonOctantId, on_removed: &mut F)
+where F: FnMut(MortonOctantId, Data) {
+	let Some(removed_data) = map.remove(&octant_id) else {
+		return;
+	};
+	on_removed(octant_id, removed_data);
+
+	if octant_id.compute_depth() < MortonOctantId::MAX_DEPTH {
+		for child_id in octant_id.children_ids() {
+			remove_from_map_recursive(map, child_id, on_removed);
+		}
+	}
+}
@@ -0,0 +1,285 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+
+use super::super::octant_storage_trait::{OctantStorage, StorageError, StorageResult};
+use super::super::octree_base::OctreeBase;
+use super::hashed_octant_storage::HashedOctantStorage;
+use super::morton_octant_id::{MortonOctantId, MortonParentIdIterator};
+use super::super::Depth;
+use super::super::octant_meta::OctantPlacement;
+
+
+type NodeIndex = u32;
+const NO_CHILD: NodeIndex = NodeIndex::MAX;
+
+#[derive(Clone, Copy, Hash, PartialEq, Eq)]
+struct DagNode {
+	data_index: u32,
+	children: [NodeIndex; OctantPlacement::OCTANTS_COUNT]
+}
+
+/// Read-only octant storage where identical subtrees are stored only once.
+///
+/// Subtrees are hashed bottom-up when storage is built, so every distinct pair of data and children
+/// becomes single node shared by all places of tree where it appears. Octants are addressed by `MortonOctantId`
+/// of their path from root, lookup walks nodes along this path.
+///
+/// ## Modification
+/// Storage is read-only, shared nodes can't be changed in place, so `get_octant_mut` always returns `None`.
+/// Convert storage with `to_hashed_storage` for editing and build new storage afterwards.
+pub struct DagOctantStorage<Data> {
+	nodes: Vec<DagNode>,
+	data: Vec<Data>,
+	root: Option<NodeIndex>,
+	/// Number of octants of tree before deduplication.
+	octant_count: usize
+}
+
+impl<Data: Clone> DagOctantStorage<Data> {
+	/// Build storage from tree held by other storage, data is compared by user supplied hash and equality.
+	/// Data with different hashes are never compared.
+	///
+	/// ## Errors
+	/// Returns `StorageError::OverMaxDepth` when tree is deeper than `MortonOctantId::MAX_DEPTH`.
+	pub fn from_storage_with<Storage, H, E>(storage: &Storage, hash_fn: H, eq_fn: E) -> StorageResult<Self>
+	where
+		Storage: OctantStorage<Data = Data>,
+		H: FnMut(&Data) -> u64,
+		E: FnMut(&Data, &Data) -> bool
+	{
+		let mut builder = DagBuilder{
+			dag: DagOctantStorage{
+				nodes: Vec::new(),
+				data: Vec::new(),
+				root: None,
+				octant_count: 0
+			},
+			data_indices: HashMap::new(),
+			node_indices: HashMap::new(),
+			hash_fn,
+			eq_fn
+		};
+		let root_id: Storage::OctantId = storage.get_root_id();
+		if storage.get_octant(&root_id).is_some() {
+			builder.dag.root = Some(builder.insert_branch(storage, &root_id, 0)?);
+		}
+		builder.dag.nodes.shrink_to_fit();
+		builder.dag.data.shrink_to_fit();
+		Ok(builder.dag)
+	}
+
+	/// Build storage from tree held by other storage, see `from_storage_with`.
+	pub fn from_storage<Storage>(storage: &Storage) -> StorageResult<Self>
+	where
+		Storage: OctantStorage<Data = Data>,
+		Data: Hash + Eq
+	{
+		let hash_state = RandomState::new();
+		Self::from_storage_with(storage, |data| hash_state.hash_one(data), |a, b| a == b)
+	}
+
+	/// Expand shared nodes back into editable storage.
+	pub fn to_hashed_storage(&self) -> HashedOctantStorage<Data> {
+		let mut octants: HashMap<MortonOctantId, Data> = HashMap::with_capacity(self.octant_count);
+		if let Some(root) = self.root {
+			let mut stack: Vec<(MortonOctantId, NodeIndex)> = vec![(MortonOctantId::ROOT_OCTANT_ID, root)];
+			while let Some((octant_id, node_index)) = stack.pop() {
+				let node: &DagNode = &self.nodes[node_index as usize];
+				octants.insert(octant_id, self.data[node.data_index as usize].clone());
+				stack.extend(
+					OctantPlacement::OCTANTS_ORDERED.into_iter()
+						.filter(|&placement| node.children[placement as usize] != NO_CHILD)
+						.map(|placement| (octant_id.child_id_by_placement(placement), node.children[placement as usize]))
+				);
+			}
+		}
+		HashedOctantStorage::from_octants(octants)
+	}
+}
+
+impl<Data> DagOctantStorage<Data> {
+	/// Number of octants of stored tree, as if no subtree was shared.
+	pub fn octant_count(&self) -> usize {
+		self.octant_count
+	}
+
+	/// Number of distinct subtrees which are actually stored.
+	pub fn node_count(&self) -> usize {
+		self.nodes.len()
+	}
+
+	/// Number of distinct data values.
+	pub fn unique_data_count(&self) -> usize {
+		self.data.len()
+	}
+
+	/// Octants per stored node, `1.0` means nothing was shared.
+	pub fn compression_ratio(&self) -> f64 {
+		if self.nodes.is_empty() {
+			return 1.0;
+		}
+		self.octant_count as f64 / self.nodes.len() as f64
+	}
+
+	fn find_node(&self, octant_id: &MortonOctantId) -> Option<&DagNode> {
+		let mut node: &DagNode = &self.nodes[self.root? as usize];
+		let morton_code: u64 = octant_id.as_morton();
+		for level in (0..octant_id.compute_depth()).rev() {
+			let placement_index: usize = ((morton_code >> (3 * level as u32)) & 0b111) as usize;
+			let child_index: NodeIndex = node.children[placement_index];
+			if child_index == NO_CHILD {
+				return None;
+			}
+			node = &self.nodes[child_index as usize];
+		}
+		Some(node)
+	}
+}
+
+impl<Data> OctantStorage for DagOctantStorage<Data> {
+	type OctantId = MortonOctantId;
+	type ParentIdIterator = MortonParentIdIterator;
+	type Data = Data;
+
+	fn get_max_depth(&self) -> Depth {
+		MortonOctantId::MAX_DEPTH
+	}
+
+	fn get_root_id(&self) -> Self::OctantId {
+		MortonOctantId::ROOT_OCTANT_ID
+	}
+
+	fn get_octant_depth(&self, octant_id: &Self::OctantId) -> Option<Depth> {
+		let _ = self.find_node(octant_id)?;
+		Some(octant_id.compute_depth())
+	}
+
+	fn get_octant(&self, octant_id: &Self::OctantId) -> Option<&Self::Data> {
+		let node: &DagNode = self.find_node(octant_id)?;
+		Some(&self.data[node.data_index as usize])
+	}
+
+	/// Always `None`, data of shared node can't be changed for only one of places where it appears.
+	fn get_octant_mut(&mut self, _: &Self::OctantId) -> Option<&mut Self::Data> {
+		None
+	}
+
+	fn get_existing_child(&self, parent_id: &Self::OctantId, child_placement: OctantPlacement) -> StorageResult<Self::OctantId> {
+		if parent_id.compute_depth() >= MortonOctantId::MAX_DEPTH {
+			return Err(StorageError::OverMaxDepth(MortonOctantId::MAX_DEPTH));
+		}
+
+		let parent_node: &DagNode = self.find_node(parent_id).ok_or(StorageError::InvalidOctantId)?;
+		if parent_node.children[child_placement as usize] == NO_CHILD {
+			Err(StorageError::ChildNotFound(Some(child_placement)))
+		}
+		else {
+			Ok(parent_id.child_id_by_placement(child_placement))
+		}
+	}
+
+	fn get_ancestors_for(&self, octant_id: &Self::OctantId) -> Option<Self::ParentIdIterator> {
+		let _ = self.find_node(octant_id)?;
+		Some(octant_id.parent_id_iter())
+	}
+
+	fn get_parent(&self, octant_id: &Self::OctantId) -> Option<Self::OctantId> {
+		if *octant_id == self.get_root_id() {
+			None
+		}
+		else {
+			let _ = self.find_node(octant_id)?;
+			Some(octant_id.parent_id())
+		}
+	}
+
+	fn which_child_of(&self, parent_id: &Self::OctantId, child_id: &Self::OctantId) -> StorageResult<OctantPlacement> {
+		let _ = self.find_node(child_id).ok_or(StorageError::InvalidOctantId)?;
+		parent_id.has_child(child_id).ok_or(StorageError::ChildNotFound(None))
+	}
+
+	fn estimated_heap_bytes(&self) -> Option<usize> {
+		Some(self.nodes.capacity() * std::mem::size_of::<DagNode>() + self.data.capacity() * std::mem::size_of::<Data>())
+	}
+}
+
+impl<Storage: OctantStorage> OctreeBase<Storage>
+where Storage::Data: Clone {
+	/// Copy tree into DAG storage, see `DagOctantStorage::from_storage_with`.
+	pub fn to_dag_with<H, E>(&self, hash_fn: H, eq_fn: E) -> StorageResult<OctreeBase<DagOctantStorage<Storage::Data>>>
+	where
+		H: FnMut(&Storage::Data) -> u64,
+		E: FnMut(&Storage::Data, &Storage::Data) -> bool
+	{
+		Ok(OctreeBase::new_with_storage(DagOctantStorage::from_storage_with(&self.octants, hash_fn, eq_fn)?))
+	}
+
+	/// Copy tree into DAG storage, see `DagOctantStorage::from_storage`.
+	pub fn to_dag(&self) -> StorageResult<OctreeBase<DagOctantStorage<Storage::Data>>>
+	where Storage::Data: Hash + Eq {
+		Ok(OctreeBase::new_with_storage(DagOctantStorage::from_storage(&self.octants)?))
+	}
+}
+
+impl<Data: Clone> OctreeBase<DagOctantStorage<Data>> {
+	pub fn to_hashed(&self) -> OctreeBase<HashedOctantStorage<Data>> {
+		OctreeBase::new_with_storage(self.octants.to_hashed_storage())
+	}
+}
+
+struct DagBuilder<Data, H, E> {
+	dag: DagOctantStorage<Data>,
+	/// Indices of data with given hash.
+	data_indices: HashMap<u64, Vec<u32>>,
+	node_indices: HashMap<DagNode, NodeIndex>,
+	hash_fn: H,
+	eq_fn: E
+}
+
+impl<Data: Clone, H, E> DagBuilder<Data, H, E>
+where
+	H: FnMut(&Data) -> u64,
+	E: FnMut(&Data, &Data) -> bool
+{
+	/// Insert children first, so node is built from already deduplicated children.
+	fn insert_branch<Storage>(&mut self, storage: &Storage, octant_id: &Storage::OctantId, depth: Depth) -> StorageResult<NodeIndex>
+	where Storage: OctantStorage<Data = Data> {
+		let mut children: [NodeIndex; OctantPlacement::OCTANTS_COUNT] = [NO_CHILD; OctantPlacement::OCTANTS_COUNT];
+		for placement in OctantPlacement::OCTANTS_ORDERED {
+			let Ok(child_id) = storage.get_existing_child(octant_id, placement) else {
+				continue;
+			};
+			if depth >= MortonOctantId::MAX_DEPTH {
+				return Err(StorageError::OverMaxDepth(MortonOctantId::MAX_DEPTH));
+			}
+			children[placement as usize] = self.insert_branch(storage, &child_id, depth + 1)?;
+		}
+
+		let custom_data: &Data = storage.get_octant(octant_id).ok_or(StorageError::InvalidOctantId)?;
+		let node = DagNode{
+			data_index: self.insert_data(custom_data),
+			children
+		};
+		self.dag.octant_count += 1;
+		let next_index: NodeIndex = self.dag.nodes.len() as NodeIndex;
+		let node_index: NodeIndex = *self.node_indices.entry(node).or_insert(next_index);
+		if node_index == next_index {
+			self.dag.nodes.push(node);
+		}
+		Ok(node_index)
+	}
+
+	fn insert_data(&mut self, custom_data: &Data) -> u32 {
+		let same_hash: &mut Vec<u32> = self.data_indices.entry((self.hash_fn)(custom_data)).or_default();
+		for &data_index in same_hash.iter() {
+			if (self.eq_fn)(&self.dag.data[data_index as usize], custom_data) {
+				return data_index;
+			}
+		}
+		let data_index: u32 = self.dag.data.len() as u32;
+		self.dag.data.push(custom_data.clone());
+		same_hash.push(data_index);
+		data_index
+	}
+}
@@ -14,6 +14,13 @@ pub struct HashedOctantStorage<Data>{
 }
 
 impl<Data> HashedOctantStorage<Data> {
+	/// Wrap octants which already form valid tree.
+	pub(crate) fn from_octants(octants: HashedMortonMap<Data>) -> Self {
+		HashedOctantStorage{
+			octants
+		}
+	}
+
 	pub fn iter(&self) -> impl Iterator<Item = (&MortonOctantId, &Data)> {
 		self.octants.iter()
 	}
@@ -89,6 +96,15 @@ impl<Data> OctantStorage for HashedOctantStorage<Data> {
 		let _ = self.get_octant(child_id).ok_or(StorageError::InvalidOctantId)?;
 		parent_id.has_child(child_id).ok_or(StorageError::ChildNotFound(None))
 	}
+
+	fn estimated_heap_bytes(&self) -> Option<usize> {
+		Some(hashed_morton_map_heap_bytes(&self.octants))
+	}
+}
+
+/// Estimate of `HashMap` allocation from its capacity, every slot holds id, data and one control byte.
+pub(crate) fn hashed_morton_map_heap_bytes<Data>(octants: &HashedMortonMap<Data>) -> usize {
+	octants.capacity() * (std::mem::size_of::<MortonOctantId>() + std::mem::size_of::<Data>() + 1)
 }
 
 impl<Data> ModifiableOctantStorage for HashedOctantStorage<Data> {
@@ -1,2 +1,6 @@
 pub mod morton_octant_id;
-pub mod hashed_octant_storage;
\ No newline at end of file
+pub mod hashed_octant_storage;
+pub mod persistent_octant_storage;
+pub mod concurrent_octant_storage;
+pub mod paged_octant_storage;
+pub mod dag_octant_storage;
\ No newline at end of file
@@ -0,0 +1,562 @@
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::super::octant_storage_trait::{OctantStorage, ModifiableOctantStorage, StorageError, StorageResult};
+use super::hashed_octant_storage::hashed_morton_map_heap_bytes;
+use super::morton_octant_id::{MortonOctantId, MortonParentIdIterator};
+use super::super::Depth;
+use super::super::octant_meta::OctantPlacement;
+
+
+type HashedMortonMap<Data> = HashMap<MortonOctantId, Data>;
+
+const MANIFEST_FILE_NAME: &str = "manifest";
+const MANIFEST_MAGIC: &[u8; 8] = b"MSVOPAGE";
+const FORMAT_VERSION: u32 = 1;
+
+/// Binary encoding of octant data inside of page files.
+pub trait PagedData: Sized {
+	fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()>;
+
+	fn read_from<R: Read>(reader: &mut R) -> io::Result<Self>;
+}
+
+macro_rules! impl_paged_data_for_numbers {
+	($($number:ty),*) => {
+		$(
+			impl PagedData for $number {
+				fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+					writer.write_all(&self.to_le_bytes())
+				}
+
+				fn read_from<R: Read>(reader: &mut R) -> io::Result<Self> {
+					let mut bytes = [0_u8; std::mem::size_of::<$number>()];
+					reader.read_exact(&mut bytes)?;
+					Ok(<$number>::from_le_bytes(bytes))
+				}
+			}
+		)*
+	};
+}
+
+impl_paged_data_for_numbers!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+/// Octant storage backed by files in directory, only bounded number of pages is held in memory.
+///
+/// Octants are grouped into pages by their Morton prefix at every `page_depth` levels, so page holds branch
+/// of `page_depth` levels and its deepest octants have children in other pages. Every page is stored in its own file,
+/// directory also holds manifest with page depth and keys of all pages.
+///
+/// ## Page cache
+/// Pages are loaded on first access and least recently used pages are evicted when cache is full,
+/// modified pages are written back on eviction, `flush` and drop. Functions taking `&self` hand out references
+/// into pages, so pages evicted while storage is borrowed immutably are retired and kept alive until next mutable access.
+/// Retired page is brought back into cache when it is accessed again, so while storage is borrowed immutably
+/// at most every page read during the borrow is held in memory, `resident_page_count` counts them.
+///
+/// ## I/O errors
+/// Functions of `OctantStorage` and `ModifiableOctantStorage` can't report I/O errors. Octants of page which can't be
+/// read are treated as missing and page which can't be written back is kept in memory until it is written,
+/// first of these errors is returned by next `flush`.
+pub struct PagedOctantStorage<Data: PagedData> {
+	page_depth: Depth,
+	/// Keys of all pages holding octants, whether they are cached or stored only on disk.
+	page_keys: HashSet<u64>,
+	/// Pages which became empty, their files are deleted by `flush`.
+	deleted_pages: HashSet<u64>,
+	cache: PageCache<Data>
+}
+
+impl<Data: PagedData> PagedOctantStorage<Data> {
+	pub const DEFAULT_PAGE_DEPTH: Depth = 4;
+	pub const DEFAULT_CACHED_PAGE_LIMIT: usize = 64;
+
+	/// Create storage with root in new or empty directory, manifest is written right away.
+	///
+	/// ## Errors
+	/// `io::ErrorKind::AlreadyExists` when directory already holds storage, or any error of creating directory.
+	///
+	/// ## Panics
+	/// When `page_depth` is `0` or over `MortonOctantId::MAX_DEPTH`, or `cached_page_limit` is `0`.
+	pub fn create<P: AsRef<Path>>(directory: P, page_depth: Depth, cached_page_limit: usize, root_custom_data: Data) -> io::Result<Self> {
+		assert!(page_depth > 0 && page_depth <= MortonOctantId::MAX_DEPTH, "Page depth must be in range 1..={}.", MortonOctantId::MAX_DEPTH);
+		let directory: PathBuf = directory.as_ref().to_path_buf();
+		fs::create_dir_all(&directory)?;
+		if directory.join(MANIFEST_FILE_NAME).exists() {
+			return Err(io::Error::new(io::ErrorKind::AlreadyExists, "Directory already holds paged octant storage."));
+		}
+
+		let mut storage = Self::with_pages(directory, page_depth, cached_page_limit, HashSet::new());
+		storage.insert_root(root_custom_data);
+		storage.flush()?;
+		Ok(storage)
+	}
+
+	/// Open storage previously created in directory, pages are loaded lazily.
+	///
+	/// ## Errors
+	/// `io::ErrorKind::InvalidData` when manifest is damaged or written by other format version.
+	///
+	/// ## Panics
+	/// When `cached_page_limit` is `0`.
+	pub fn open<P: AsRef<Path>>(directory: P, cached_page_limit: usize) -> io::Result<Self> {
+		let directory: PathBuf = directory.as_ref().to_path_buf();
+		let mut reader = BufReader::new(File::open(directory.join(MANIFEST_FILE_NAME))?);
+		let mut magic = [0_u8; 8];
+		reader.read_exact(&mut magic)?;
+		if &magic != MANIFEST_MAGIC || u32::read_from(&mut reader)? != FORMAT_VERSION {
+			return Err(invalid_data("Manifest has unknown format."));
+		}
+		let page_depth: Depth = u8::read_from(&mut reader)?;
+		if page_depth == 0 || page_depth > MortonOctantId::MAX_DEPTH {
+			return Err(invalid_data("Manifest has invalid page depth."));
+		}
+		let page_count: u64 = u64::read_from(&mut reader)?;
+		let page_keys: HashSet<u64> = (0..page_count)
+			.map(|_| u64::read_from(&mut reader))
+			.collect::<io::Result<HashSet<u64>>>()?;
+
+		Ok(Self::with_pages(directory, page_depth, cached_page_limit, page_keys))
+	}
+
+	/// Write back all modified pages, delete files of emptied pages and write manifest.
+	///
+	/// ## Errors
+	/// I/O error which happened during access since last `flush`, otherwise first error of writing.
+	pub fn flush(&mut self) -> io::Result<()> {
+		let recorded_error: Option<io::Error> = self.cache.error.get_mut().take();
+		let result: io::Result<()> = self.write_back();
+		match recorded_error {
+			Some(error) => Err(error),
+			None => result
+		}
+	}
+
+	fn write_back(&mut self) -> io::Result<()> {
+		let PageCache{ directory, pages, retired, .. } = &mut self.cache;
+		for (page_key, page) in pages.get_mut().iter().chain(retired.get_mut().iter()) {
+			if page.dirty.get() {
+				write_page(directory, *page_key, page)?;
+				page.dirty.set(false);
+			}
+		}
+		retired.get_mut().clear();
+		for page_key in self.deleted_pages.drain() {
+			if self.page_keys.contains(&page_key) {
+				continue;
+			}
+			match fs::remove_file(page_path(&self.cache.directory, page_key)) {
+				Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+				_ => {}
+			}
+		}
+		self.write_manifest()
+	}
+
+	pub fn directory(&self) -> &Path {
+		&self.cache.directory
+	}
+
+	pub fn page_depth(&self) -> Depth {
+		self.page_depth
+	}
+
+	/// Number of pages, including pages stored only on disk.
+	pub fn page_count(&self) -> usize {
+		self.page_keys.len()
+	}
+
+	pub fn cached_page_limit(&self) -> usize {
+		self.cache.page_limit
+	}
+
+	/// Number of pages held in cache, retired pages are not counted.
+	pub fn cached_page_count(&self) -> usize {
+		self.cache.pages.borrow().len()
+	}
+
+	/// Number of pages held in memory, including retired pages and pages which couldn't be written back.
+	pub fn resident_page_count(&self) -> usize {
+		self.cache.pages.borrow().len() + self.cache.retired.borrow().len()
+	}
+
+	fn with_pages(directory: PathBuf, page_depth: Depth, cached_page_limit: usize, page_keys: HashSet<u64>) -> Self {
+		assert!(cached_page_limit > 0, "Page cache has to hold at least one page.");
+		PagedOctantStorage{
+			page_depth,
+			page_keys,
+			deleted_pages: HashSet::new(),
+			cache: PageCache{
+				directory,
+				page_limit: cached_page_limit,
+				pages: RefCell::new(HashMap::new()),
+				retired: RefCell::new(HashMap::new()),
+				error: RefCell::new(None),
+				access_count: Cell::new(0)
+			}
+		}
+	}
+
+	fn write_manifest(&self) -> io::Result<()> {
+		let mut page_keys: Vec<u64> = self.page_keys.iter().copied().collect();
+		page_keys.sort();
+		let temporary_path: PathBuf = self.cache.directory.join(format!("{}.tmp", MANIFEST_FILE_NAME));
+		{
+			let mut writer = BufWriter::new(File::create(&temporary_path)?);
+			writer.write_all(MANIFEST_MAGIC)?;
+			FORMAT_VERSION.write_to(&mut writer)?;
+			self.page_depth.write_to(&mut writer)?;
+			(page_keys.len() as u64).write_to(&mut writer)?;
+			for page_key in page_keys {
+				page_key.write_to(&mut writer)?;
+			}
+			writer.flush()?;
+		}
+		fs::rename(temporary_path, self.cache.directory.join(MANIFEST_FILE_NAME))
+	}
+
+	/// Morton code of first octant of page holding octant.
+	fn page_key_of(&self, octant_id: &MortonOctantId) -> u64 {
+		let levels_inside_page: Depth = octant_id.compute_depth() % self.page_depth;
+		octant_id.as_morton() >> (3 * levels_inside_page as u32)
+	}
+
+	fn page(&self, octant_id: &MortonOctantId) -> Option<&Page<Data>> {
+		let page_key: u64 = self.page_key_of(octant_id);
+		if !self.page_keys.contains(&page_key) {
+			return None;
+		}
+		let page: *const Page<Data> = Arc::as_ptr(&self.cache.load(page_key)?);
+		// SAFETY: page is kept alive by `Arc` held either in cache or in retired pages, through shared reference
+		// of storage pages are never modified and retired pages are dropped only through mutable reference.
+		Some(unsafe { &*page })
+	}
+
+	fn page_mut(&mut self, octant_id: &MortonOctantId) -> Option<&mut Page<Data>> {
+		let page_key: u64 = self.page_key_of(octant_id);
+		if !self.page_keys.contains(&page_key) {
+			return None;
+		}
+		self.cache.load_mut(page_key)
+	}
+
+	fn page_mut_or_create(&mut self, octant_id: &MortonOctantId) -> Option<&mut Page<Data>> {
+		let page_key: u64 = self.page_key_of(octant_id);
+		if self.page_keys.insert(page_key) {
+			self.cache.insert_new(page_key);
+		}
+		self.cache.load_mut(page_key)
+	}
+
+	fn remove_from_page(&mut self, octant_id: &MortonOctantId) -> Option<Data> {
+		let page: &mut Page<Data> = self.page_mut(octant_id)?;
+		let custom_data: Data = page.octants.remove(octant_id)?;
+		page.dirty.set(true);
+		if page.octants.is_empty() {
+			let page_key: u64 = self.page_key_of(octant_id);
+			self.page_keys.remove(&page_key);
+			self.deleted_pages.insert(page_key);
+			self.cache.pages.get_mut().remove(&page_key);
+		}
+		Some(custom_data)
+	}
+
+	fn remove_branch<F>(&mut self, octant_id: &MortonOctantId, on_removed: &mut F) -> Option<()>
+	where F: FnMut(MortonOctantId, Data) {
+		let custom_data: Data = self.remove_from_page(octant_id)?;
+		on_removed(*octant_id, custom_data);
+		if octant_id.compute_depth() < MortonOctantId::MAX_DEPTH {
+			for child_id in octant_id.children_ids() {
+				self.remove_branch(&child_id, on_removed);
+			}
+		}
+		Some(())
+	}
+
+	/// Remove all octants, page files are deleted by `flush`.
+	fn clear(&mut self) {
+		self.deleted_pages.extend(self.page_keys.drain());
+		self.cache.pages.get_mut().clear();
+		self.cache.retired.get_mut().clear();
+	}
+}
+
+impl<Data: PagedData> Drop for PagedOctantStorage<Data> {
+	fn drop(&mut self) {
+		let _ = self.flush();
+	}
+}
+
+impl<Data: PagedData> OctantStorage for PagedOctantStorage<Data> {
+	type OctantId = MortonOctantId;
+	type ParentIdIterator = MortonParentIdIterator;
+	type Data = Data;
+
+	fn get_max_depth(&self) -> Depth {
+		MortonOctantId::MAX_DEPTH
+	}
+
+	fn get_root_id(&self) -> Self::OctantId {
+		MortonOctantId::ROOT_OCTANT_ID
+	}
+
+	fn get_octant_depth(&self, octant_id: &Self::OctantId) -> Option<Depth> {
+		let _ = self.get_octant(octant_id)?;
+		Some(octant_id.compute_depth())
+	}
+
+	fn get_octant(&self, octant_id: &Self::OctantId) -> Option<&Self::Data> {
+		self.page(octant_id)?.octants.get(octant_id)
+	}
+
+	fn get_octant_mut(&mut self, octant_id: &Self::OctantId) -> Option<&mut Self::Data> {
+		let page: &mut Page<Data> = self.page_mut(octant_id)?;
+		if !page.octants.contains_key(octant_id) {
+			return None;
+		}
+		page.dirty.set(true);
+		page.octants.get_mut(octant_id)
+	}
+
+	fn get_existing_child(&self, parent_id: &Self::OctantId, child_placement: OctantPlacement) -> StorageResult<Self::OctantId> {
+		if parent_id.compute_depth() >= MortonOctantId::MAX_DEPTH{
+			return Err(StorageError::OverMaxDepth(MortonOctantId::MAX_DEPTH));
+		}
+
+		let child_id: MortonOctantId = parent_id.children_ids()[child_placement as usize];
+		if self.get_octant(&child_id).is_some(){
+			Ok(child_id)
+		}
+		else if self.get_octant(parent_id).is_some(){
+			Err(StorageError::ChildNotFound(Some(child_placement)))
+		}
+		else {
+			Err(StorageError::InvalidOctantId)
+		}
+	}
+
+	fn get_ancestors_for(&self, octant_id: &Self::OctantId) -> Option<Self::ParentIdIterator> {
+		let _ = self.get_octant(octant_id)?;
+		Some(octant_id.parent_id_iter())
+	}
+
+	fn get_parent(&self, octant_id: &Self::OctantId) -> Option<Self::OctantId> {
+		if *octant_id == self.get_root_id() {
+			None
+		}
+		else {
+			let _ = self.get_octant(octant_id)?;
+			Some(octant_id.parent_id())
+		}
+	}
+
+	fn which_child_of(&self, parent_id: &Self::OctantId, child_id: &Self::OctantId) -> StorageResult<OctantPlacement> {
+		let _ = self.get_octant(child_id).ok_or(StorageError::InvalidOctantId)?;
+		parent_id.has_child(child_id).ok_or(StorageError::ChildNotFound(None))
+	}
+
+	/// Only pages held in memory are counted.
+	fn estimated_heap_bytes(&self) -> Option<usize> {
+		let page_bytes: usize = self.cache.pages.borrow().values()
+			.chain(self.cache.retired.borrow().values())
+			.map(|page| std::mem::size_of::<Page<Data>>() + hashed_morton_map_heap_bytes(&page.octants))
+			.sum();
+		Some(page_bytes + self.page_keys.capacity() * (std::mem::size_of::<u64>() + 1))
+	}
+}
+
+impl<Data: PagedData> ModifiableOctantStorage for PagedOctantStorage<Data> {
+	fn insert_root(&mut self, root_custom_data: Self::Data) -> Option<Self::Data> {
+		let root_id: MortonOctantId = self.get_root_id();
+		let page: &mut Page<Data> = self.page_mut_or_create(&root_id)?;
+		page.dirty.set(true);
+		page.octants.insert(root_id, root_custom_data)
+	}
+
+	fn insert_octant(&mut self, parent_id: &Self::OctantId, child_octant_placement: OctantPlacement, custom_data: Self::Data) -> StorageResult<(Self::OctantId, Option<Self::Data>)> {
+		if parent_id.compute_depth() >= MortonOctantId::MAX_DEPTH {
+			return Err(StorageError::OverMaxDepth(MortonOctantId::MAX_DEPTH));
+		}
+		let _ = self.get_octant(parent_id).ok_or(StorageError::InvalidOctantId)?;
+		let child_id: MortonOctantId = parent_id.child_id_by_placement(child_octant_placement);
+		let page: &mut Page<Data> = self.page_mut_or_create(&child_id).ok_or(StorageError::InvalidOctantId)?;
+		page.dirty.set(true);
+		let old_data: Option<Data> = page.octants.insert(child_id, custom_data);
+
+		Ok((child_id, old_data))
+	}
+
+	fn remove_octant(&mut self, octant_id: &Self::OctantId) -> Option<()> {
+		let _ = self.get_octant(octant_id)?;
+		if *octant_id == self.get_root_id() {
+			self.clear();
+			return Some(());
+		}
+		self.remove_branch(octant_id, &mut |_, _| {})
+	}
+
+	fn remove_octant_and_fill(&mut self, octant_id: &Self::OctantId, collected_octant_data: &mut Vec<(Self::OctantId, Self::Data)>) -> Option<()> {
+		let _ = self.get_octant(octant_id)?;
+		self.remove_branch(octant_id, &mut |removed_id, custom_data| collected_octant_data.push((removed_id, custom_data)))
+	}
+}
+
+struct Page<Data> {
+	octants: HashedMortonMap<Data>,
+	/// Value of cache access counter at last access of page.
+	last_access: Cell<u64>,
+	dirty: Cell<bool>
+}
+
+struct PageCache<Data> {
+	directory: PathBuf,
+	page_limit: usize,
+	pages: RefCell<HashMap<u64, Arc<Page<Data>>>>,
+	/// Pages evicted during immutable access, references to their data can still exist,
+	/// and modified pages which couldn't be written back.
+	retired: RefCell<HashMap<u64, Arc<Page<Data>>>>,
+	/// First I/O error since last `flush`.
+	error: RefCell<Option<io::Error>>,
+	access_count: Cell<u64>
+}
+
+impl<Data: PagedData> PageCache<Data> {
+	fn next_access(&self) -> u64 {
+		let access: u64 = self.access_count.get() + 1;
+		self.access_count.set(access);
+		access
+	}
+
+	fn record_error(&self, error: io::Error) {
+		self.error.borrow_mut().get_or_insert(error);
+	}
+
+	/// Get cached page, retired page or read it from disk, least recently used pages are retired when cache is full.
+	fn load(&self, page_key: u64) -> Option<Arc<Page<Data>>> {
+		let access: u64 = self.next_access();
+		if let Some(page) = self.pages.borrow().get(&page_key) {
+			page.last_access.set(access);
+			return Some(Arc::clone(page));
+		}
+
+		// retired page is reused, so repeated access doesn't hold more copies of one page
+		let retired_page: Option<Arc<Page<Data>>> = self.retired.borrow_mut().remove(&page_key);
+		let page: Arc<Page<Data>> = match retired_page {
+			Some(page) => page,
+			None => Arc::new(read_page(&self.directory, page_key).map_err(|error| self.record_error(error)).ok()?)
+		};
+		page.last_access.set(access);
+		while self.pages.borrow().len() >= self.page_limit {
+			let (evicted_key, evicted) = self.evict_least_recent();
+			self.retired.borrow_mut().insert(evicted_key, evicted);
+		}
+		self.pages.borrow_mut().insert(page_key, Arc::clone(&page));
+		Some(page)
+	}
+
+	fn load_mut(&mut self, page_key: u64) -> Option<&mut Page<Data>> {
+		self.release_retired();
+		if !self.pages.get_mut().contains_key(&page_key) {
+			let page: Arc<Page<Data>> = match self.retired.get_mut().remove(&page_key) {
+				Some(page) => page,
+				None => Arc::new(read_page(&self.directory, page_key).map_err(|error| self.record_error(error)).ok()?)
+			};
+			self.make_space();
+			self.pages.get_mut().insert(page_key, page);
+		}
+
+		let access: u64 = self.next_access();
+		let page: &mut Page<Data> = self.pages.get_mut().get_mut(&page_key)
+			.and_then(Arc::get_mut)
+			.expect("Page is referenced only by cache.");
+		page.last_access.set(access);
+		Some(page)
+	}
+
+	fn insert_new(&mut self, page_key: u64) {
+		self.release_retired();
+		self.make_space();
+		let page: Page<Data> = Page{
+			octants: HashMap::new(),
+			last_access: Cell::new(0),
+			dirty: Cell::new(true)
+		};
+		self.pages.get_mut().insert(page_key, Arc::new(page));
+	}
+
+	/// Drop retired pages, pages which couldn't be written back are kept.
+	fn release_retired(&mut self) {
+		self.retired.get_mut().retain(|_, page| page.dirty.get());
+	}
+
+	fn make_space(&mut self) {
+		while self.pages.get_mut().len() >= self.page_limit {
+			let (evicted_key, evicted) = self.evict_least_recent();
+			if evicted.dirty.get() {
+				self.retired.get_mut().insert(evicted_key, evicted);
+			}
+		}
+	}
+
+	/// Remove least recently used page from cache, it is written back when it was modified.
+	fn evict_least_recent(&self) -> (u64, Arc<Page<Data>>) {
+		let mut pages = self.pages.borrow_mut();
+		let page_key: u64 = pages.iter()
+			.min_by_key(|(_, page)| page.last_access.get())
+			.map(|(page_key, _)| *page_key)
+			.expect("Cache with zero limit can't evict pages.");
+		let page: Arc<Page<Data>> = pages.remove(&page_key).unwrap();
+		if page.dirty.get() {
+			match write_page(&self.directory, page_key, &page) {
+				Ok(()) => page.dirty.set(false),
+				Err(error) => self.record_error(error)
+			}
+		}
+		(page_key, page)
+	}
+}
+
+fn page_path(directory: &Path, page_key: u64) -> PathBuf {
+	directory.join(format!("page_{:016x}.bin", page_key))
+}
+
+fn read_page<Data: PagedData>(directory: &Path, page_key: u64) -> io::Result<Page<Data>> {
+	let file: File = File::open(page_path(directory, page_key))?;
+	let file_length: u64 = file.metadata()?.len();
+	let mut reader = BufReader::new(file);
+	let octant_count: u64 = u64::read_from(&mut reader)?;
+	// every octant takes at least bytes of its id, so count is checked before allocating for it
+	if octant_count > file_length.saturating_sub(8) / 8 {
+		return Err(invalid_data("Page holds more octants than fit into its file."));
+	}
+	let mut octants: HashedMortonMap<Data> = HashMap::with_capacity(octant_count as usize);
+	for _ in 0..octant_count {
+		let octant_id: MortonOctantId = MortonOctantId::from_morton_code(u64::read_from(&mut reader)?);
+		octants.insert(octant_id, Data::read_from(&mut reader)?);
+	}
+	Ok(
+		Page{
+			octants,
+			last_access: Cell::new(0),
+			dirty: Cell::new(false)
+		}
+	)
+}
+
+fn write_page<Data: PagedData>(directory: &Path, page_key: u64, page: &Page<Data>) -> io::Result<()> {
+	let mut writer = BufWriter::new(File::create(page_path(directory, page_key))?);
+	(page.octants.len() as u64).write_to(&mut writer)?;
+	for (octant_id, custom_data) in page.octants.iter() {
+		octant_id.as_morton().write_to(&mut writer)?;
+		custom_data.write_to(&mut writer)?;
+	}
+	writer.flush()
+}
+
+fn invalid_data(message: &str) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, message)
+}
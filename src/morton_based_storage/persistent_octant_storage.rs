@@ -0,0 +1,274 @@
+use std::sync::Arc;
+
+use super::super::octant_storage_trait::{OctantStorage, ModifiableOctantStorage, StorageError, StorageResult};
+use super::super::octree_base::OctreeBase;
+use super::super::spatial_octree_base::SpatialOctreeBase;
+use super::super::voxel_trait::Voxel;
+use super::morton_octant_id::{MortonOctantId, MortonParentIdIterator};
+use super::super::Depth;
+use super::super::octant_meta::OctantPlacement;
+
+
+#[derive(Clone)]
+struct PersistentOctantNode<Data> {
+	data: Data,
+	children: [Option<Arc<PersistentOctantNode<Data>>>; OctantPlacement::OCTANTS_COUNT]
+}
+
+impl<Data> PersistentOctantNode<Data> {
+	fn new(data: Data) -> Self {
+		PersistentOctantNode{
+			data,
+			children: Default::default()
+		}
+	}
+}
+
+/// Copy-on-write storage where nodes are shared between storage and its snapshots.
+///
+/// `snapshot()` only clones reference to root node, modifications afterwards copy
+/// nodes on the path from root to modified octant, rest of the tree stays shared.
+/// Snapshots are `Send + Sync` when `Data` is, so they can be read from other threads while original is modified.
+pub struct PersistentOctantStorage<Data>{
+	root: Option<Arc<PersistentOctantNode<Data>>>
+}
+
+impl<Data> PersistentOctantStorage<Data> {
+	/// Create immutable view of current state in O(1).
+	pub fn snapshot(&self) -> Self {
+		PersistentOctantStorage{
+			root: self.root.clone()
+		}
+	}
+
+	/// Check whether octant node(and therefore its whole branch) is physically shared with other storage.
+	pub fn shares_octant_with(&self, other: &Self, octant_id: &MortonOctantId) -> bool {
+		match (self.find_node(octant_id), other.find_node(octant_id)) {
+			(Some(node), Some(other_node)) => std::ptr::eq(node, other_node),
+			_ => false
+		}
+	}
+
+	fn find_node(&self, octant_id: &MortonOctantId) -> Option<&PersistentOctantNode<Data>> {
+		if !octant_id.is_valid() {
+			return None;
+		}
+		let mut node: &PersistentOctantNode<Data> = self.root.as_deref()?;
+		for child_index in path_from_root(octant_id) {
+			node = node.children[child_index].as_deref()?;
+		}
+		Some(node)
+	}
+}
+
+impl<Data: Clone> PersistentOctantStorage<Data> {
+	/// Get node for modification, copying every shared node on the path from root.
+	fn find_node_mut(&mut self, octant_id: &MortonOctantId) -> Option<&mut PersistentOctantNode<Data>> {
+		// check existence first, so nothing is copied for missing octants
+		let _ = self.find_node(octant_id)?;
+		let mut node: &mut PersistentOctantNode<Data> = Arc::make_mut(self.root.as_mut()?);
+		for child_index in path_from_root(octant_id) {
+			node = Arc::make_mut(node.children[child_index].as_mut()?);
+		}
+		Some(node)
+	}
+}
+
+impl<Data: Clone> OctantStorage for PersistentOctantStorage<Data> {
+	type OctantId = MortonOctantId;
+	type ParentIdIterator = MortonParentIdIterator;
+	type Data = Data;
+
+	fn get_max_depth(&self) -> Depth {
+		MortonOctantId::MAX_DEPTH
+	}
+
+	fn get_root_id(&self) -> Self::OctantId {
+		MortonOctantId::ROOT_OCTANT_ID
+	}
+
+	fn get_octant_depth(&self, octant_id: &Self::OctantId) -> Option<Depth> {
+		let _ = self.find_node(octant_id)?;
+		Some(octant_id.compute_depth())
+	}
+
+	fn get_octant(&self, octant_id: &Self::OctantId) -> Option<&Self::Data> {
+		self.find_node(octant_id).map(|node| &node.data)
+	}
+
+	fn get_octant_mut(&mut self, octant_id: &Self::OctantId) -> Option<&mut Self::Data> {
+		self.find_node_mut(octant_id).map(|node| &mut node.data)
+	}
+
+	fn get_existing_child(&self, parent_id: &Self::OctantId, child_placement: OctantPlacement) -> StorageResult<Self::OctantId> {
+		if parent_id.compute_depth() >= MortonOctantId::MAX_DEPTH {
+			return Err(StorageError::OverMaxDepth(MortonOctantId::MAX_DEPTH));
+		}
+
+		let parent_node = self.find_node(parent_id).ok_or(StorageError::InvalidOctantId)?;
+		if parent_node.children[child_placement as usize].is_some() {
+			Ok(parent_id.child_id_by_placement(child_placement))
+		}
+		else {
+			Err(StorageError::ChildNotFound(Some(child_placement)))
+		}
+	}
+
+	fn get_existing_children(&self, parent_id: &Self::OctantId) -> StorageResult<[Option<Self::OctantId>; OctantPlacement::OCTANTS_COUNT]> {
+		if parent_id.compute_depth() >= MortonOctantId::MAX_DEPTH {
+			return Err(StorageError::OverMaxDepth(MortonOctantId::MAX_DEPTH));
+		}
+
+		let parent_node = self.find_node(parent_id).ok_or(StorageError::InvalidOctantId)?;
+		let children_ids = parent_id.children_ids();
+		Ok(
+			OctantPlacement::OCTANTS_ORDERED.map(
+				|child_placement|{
+					parent_node.children[child_placement as usize].as_ref()?;
+					Some(children_ids[child_placement as usize])
+				}
+			)
+		)
+	}
+
+	fn get_ancestors_for(&self, octant_id: &Self::OctantId) -> Option<Self::ParentIdIterator> {
+		let _ = self.find_node(octant_id)?;
+		Some(octant_id.parent_id_iter())
+	}
+
+	fn get_parent(&self, octant_id: &Self::OctantId) -> Option<Self::OctantId> {
+		if *octant_id == self.get_root_id() {
+			None
+		}
+		else {
+			let _ = self.find_node(octant_id)?;
+			Some(octant_id.parent_id())
+		}
+	}
+
+	fn which_child_of(&self, parent_id: &Self::OctantId, child_id: &Self::OctantId) -> StorageResult<OctantPlacement> {
+		let _ = self.find_node(child_id).ok_or(StorageError::InvalidOctantId)?;
+		parent_id.has_child(child_id).ok_or(StorageError::ChildNotFound(None))
+	}
+
+	/// Nodes shared with snapshots are counted as if this storage owned them.
+	fn estimated_heap_bytes(&self) -> Option<usize> {
+		// every node lives in its own `Arc` allocation next to strong and weak counters
+		const NODE_BYTES: usize = std::mem::size_of::<PersistentOctantNode<()>>() + 2 * std::mem::size_of::<usize>();
+		let node_bytes: usize = NODE_BYTES + std::mem::size_of::<Data>();
+		Some(self.root.as_deref().map_or(0, count_nodes) * node_bytes)
+	}
+}
+
+fn count_nodes<Data>(node: &PersistentOctantNode<Data>) -> usize {
+	1 + node.children.iter()
+		.flatten()
+		.map(|child| count_nodes(child))
+		.sum::<usize>()
+}
+
+impl<Data: Clone> ModifiableOctantStorage for PersistentOctantStorage<Data> {
+	fn new_with_root(root_custom_data: Self::Data) -> Self
+	where Self: Default {
+		PersistentOctantStorage{
+			root: Some(Arc::new(PersistentOctantNode::new(root_custom_data)))
+		}
+	}
+
+	fn insert_root(&mut self, root_custom_data: Self::Data) -> Option<Self::Data> {
+		match self.root.as_mut() {
+			Some(root_node) => Some(std::mem::replace(&mut Arc::make_mut(root_node).data, root_custom_data)),
+			None => {
+				self.root = Some(Arc::new(PersistentOctantNode::new(root_custom_data)));
+				None
+			}
+		}
+	}
+
+	fn insert_octant(&mut self, parent_id: &Self::OctantId, child_octant_placement: OctantPlacement, custom_data: Self::Data) -> StorageResult<(Self::OctantId, Option<Self::Data>)> {
+		if parent_id.compute_depth() >= MortonOctantId::MAX_DEPTH {
+			return Err(StorageError::OverMaxDepth(MortonOctantId::MAX_DEPTH));
+		}
+
+		let parent_node = self.find_node_mut(parent_id).ok_or(StorageError::InvalidOctantId)?;
+		let child_id: MortonOctantId = parent_id.child_id_by_placement(child_octant_placement);
+		let old_data: Option<Data> = match parent_node.children[child_octant_placement as usize].as_mut() {
+			Some(child_node) => Some(std::mem::replace(&mut Arc::make_mut(child_node).data, custom_data)),
+			None => {
+				parent_node.children[child_octant_placement as usize] = Some(Arc::new(PersistentOctantNode::new(custom_data)));
+				None
+			}
+		};
+
+		Ok((child_id, old_data))
+	}
+
+	fn remove_octant(&mut self, octant_id: &Self::OctantId) -> Option<()> {
+		self.take_branch(octant_id).map(|_| ())
+	}
+
+	fn remove_octant_and_fill(&mut self, octant_id: &Self::OctantId, collected_octant_data: &mut Vec<(Self::OctantId, Self::Data)>) -> Option<()> {
+		let removed_node = self.take_branch(octant_id)?;
+		collect_branch(removed_node, *octant_id, collected_octant_data);
+		Some(())
+	}
+}
+
+impl<Data: Clone> PersistentOctantStorage<Data> {
+	/// Detach branch from tree, nodes shared with snapshots are left untouched.
+	fn take_branch(&mut self, octant_id: &MortonOctantId) -> Option<Arc<PersistentOctantNode<Data>>> {
+		let _ = self.find_node(octant_id)?;
+		if octant_id.is_root() {
+			return self.root.take();
+		}
+
+		let child_placement: OctantPlacement = octant_id.parent_id().has_child(octant_id)?;
+		let parent_node = self.find_node_mut(&octant_id.parent_id())?;
+		parent_node.children[child_placement as usize].take()
+	}
+}
+
+impl<Data: Default> Default for PersistentOctantStorage<Data> {
+	fn default() -> Self {
+		PersistentOctantStorage{
+			root: Some(Arc::new(PersistentOctantNode::new(Data::default())))
+		}
+	}
+}
+
+impl<Data: Clone> OctreeBase<PersistentOctantStorage<Data>> {
+	/// Create immutable view of current tree in O(1), see `PersistentOctantStorage::snapshot()`.
+	pub fn snapshot(&self) -> Self {
+		OctreeBase::new_with_storage(self.octants.snapshot())
+	}
+}
+
+impl<Data: Clone, Volumetric: Voxel> SpatialOctreeBase<PersistentOctantStorage<Data>, Volumetric> {
+	/// Create immutable view of current tree in O(1), see `PersistentOctantStorage::snapshot()`.
+	pub fn snapshot(&self) -> Self {
+		SpatialOctreeBase::new_with_base(*self.get_root_voxel(), self.base.snapshot())
+	}
+}
+
+/// Indices of children leading from root to octant.
+fn path_from_root(octant_id: &MortonOctantId) -> impl Iterator<Item = usize> {
+	let morton_code: u64 = octant_id.as_morton();
+	(0..octant_id.compute_depth() as u32).rev()
+		.map(move |level| ((morton_code >> (level * 3)) & 0b111) as usize)
+}
+
+/// Move data out of detached branch in pre-order, data of nodes still shared with snapshots is cloned.
+fn collect_branch<Data: Clone>(node: Arc<PersistentOctantNode<Data>>, octant_id: MortonOctantId, collected_octant_data: &mut Vec<(MortonOctantId, Data)>) {
+	let children_ids = octant_id.children_ids();
+	let (data, children) = match Arc::try_unwrap(node) {
+		Ok(owned_node) => (owned_node.data, owned_node.children),
+		Err(shared_node) => (shared_node.data.clone(), shared_node.children.clone())
+	};
+
+	collected_octant_data.push((octant_id, data));
+	for (child_node, child_id) in children.into_iter().zip(children_ids) {
+		if let Some(child_node) = child_node {
+			collect_branch(child_node, child_id, collected_octant_data);
+		}
+	}
+}
@@ -60,23 +60,6 @@ impl OctantPlacement {
 			(OctantNeighborDirection::S, OctantPlacement::LOWER_BOTTOM_LEFT),
 			(OctantNeighborDirection::U, OctantPlacement::UPPER_TOP_LEFT)
 		],
-		
-		[// LOWER_BOTTOM_RIGHT
-			// [  *]
-			// [* *]
-			(OctantNeighborDirection::W, OctantPlacement::LOWER_BOTTOM_LEFT),
-			(OctantNeighborDirection::N, OctantPlacement::LOWER_TOP_RIGHT), 
-			(OctantNeighborDirection::U, OctantPlacement::UPPER_BOTTOM_RIGHT)
-		],
-
-		[// LOWER_TOP_RIGHT
-			// [* *]
-			// [  *]
-			(OctantNeighborDirection::W, OctantPlacement::LOWER_TOP_LEFT),
-			(OctantNeighborDirection::S, OctantPlacement::LOWER_BOTTOM_RIGHT),
-			(OctantNeighborDirection::U, OctantPlacement::UPPER_TOP_RIGHT)
-		],
-		
 
 		[// UPPER_BOTTOM_LEFT
 			// [*  ]
@@ -94,6 +77,22 @@ impl OctantPlacement {
 			(OctantNeighborDirection::D, OctantPlacement::LOWER_TOP_LEFT)
 		],
 
+		[// LOWER_BOTTOM_RIGHT
+			// [  *]
+			// [* *]
+			(OctantNeighborDirection::W, OctantPlacement::LOWER_BOTTOM_LEFT),
+			(OctantNeighborDirection::N, OctantPlacement::LOWER_TOP_RIGHT), 
+			(OctantNeighborDirection::U, OctantPlacement::UPPER_BOTTOM_RIGHT)
+		],
+
+		[// LOWER_TOP_RIGHT
+			// [* *]
+			// [  *]
+			(OctantNeighborDirection::W, OctantPlacement::LOWER_TOP_LEFT),
+			(OctantNeighborDirection::S, OctantPlacement::LOWER_BOTTOM_RIGHT),
+			(OctantNeighborDirection::U, OctantPlacement::UPPER_TOP_RIGHT)
+		],
+
 		[// UPPER_BOTTOM_RIGHT
 			// [  *]
 			// [* *]
@@ -108,7 +107,7 @@ impl OctantPlacement {
 			(OctantNeighborDirection::W, OctantPlacement::UPPER_TOP_LEFT),
 			(OctantNeighborDirection::S, OctantPlacement::UPPER_BOTTOM_RIGHT),
 			(OctantNeighborDirection::D, OctantPlacement::LOWER_TOP_RIGHT)
-		],	
+		],
 	];
 
 	pub const OCTANT_DIAGONAL_NEIGHBORS: [[(OctantNeighborDirection, OctantPlacement); Self::OCTANT_DIAGONAL_NEIGHBORS_COUNT] ; Self::OCTANTS_COUNT] = [
@@ -128,6 +127,23 @@ impl OctantPlacement {
 			(OctantNeighborDirection::USE, OctantPlacement::UPPER_BOTTOM_RIGHT),
 			(OctantNeighborDirection::SE, OctantPlacement::LOWER_BOTTOM_RIGHT)
 		],
+		[// UPPER_BOTTOM_LEFT
+			// [* *]
+			// [  *]
+			(OctantNeighborDirection::DN, OctantPlacement::LOWER_TOP_LEFT),
+			(OctantNeighborDirection::DE, OctantPlacement::LOWER_BOTTOM_RIGHT),
+			(OctantNeighborDirection::DNE, OctantPlacement::LOWER_TOP_RIGHT),
+			(OctantNeighborDirection::NE, OctantPlacement::UPPER_TOP_RIGHT)
+		],
+		[// UPPER_TOP_LEFT
+			// [  *]
+			// [* *]
+			(OctantNeighborDirection::DE, OctantPlacement::LOWER_TOP_RIGHT),
+			(OctantNeighborDirection::DS, OctantPlacement::LOWER_BOTTOM_LEFT),
+			(OctantNeighborDirection::DSE, OctantPlacement::LOWER_BOTTOM_RIGHT),
+			(OctantNeighborDirection::SE, OctantPlacement::UPPER_BOTTOM_RIGHT)
+		],
+
 		[// LOWER_BOTTOM_RIGHT
 			// [* *]
 			// [*  ]
@@ -145,23 +161,6 @@ impl OctantPlacement {
 			(OctantNeighborDirection::USW, OctantPlacement::UPPER_BOTTOM_LEFT),
 			(OctantNeighborDirection::SW, OctantPlacement::LOWER_BOTTOM_LEFT)
 		],
-
-		[// UPPER_BOTTOM_LEFT
-			// [* *]
-			// [  *]
-			(OctantNeighborDirection::DN, OctantPlacement::LOWER_TOP_LEFT),
-			(OctantNeighborDirection::DE, OctantPlacement::LOWER_BOTTOM_RIGHT),
-			(OctantNeighborDirection::DNE, OctantPlacement::LOWER_TOP_RIGHT),
-			(OctantNeighborDirection::NE, OctantPlacement::UPPER_TOP_RIGHT)
-		],
-		[// UPPER_TOP_LEFT
-			// [  *]
-			// [* *]
-			(OctantNeighborDirection::DE, OctantPlacement::LOWER_TOP_RIGHT),
-			(OctantNeighborDirection::DS, OctantPlacement::LOWER_BOTTOM_LEFT),
-			(OctantNeighborDirection::DSE, OctantPlacement::LOWER_BOTTOM_RIGHT),
-			(OctantNeighborDirection::SE, OctantPlacement::UPPER_BOTTOM_RIGHT)
-		],
 		[// UPPER_BOTTOM_RIGHT
 			// [* *]
 			// [*  ]
@@ -177,7 +176,7 @@ impl OctantPlacement {
 			(OctantNeighborDirection::DS, OctantPlacement::LOWER_BOTTOM_RIGHT),
 			(OctantNeighborDirection::DSW, OctantPlacement::LOWER_BOTTOM_LEFT),
 			(OctantNeighborDirection::SW, OctantPlacement::UPPER_BOTTOM_LEFT)
-		],	
+		],
 	];
 
 
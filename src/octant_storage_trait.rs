@@ -152,6 +152,26 @@ pub trait OctantStorage{
 		)
 	}
 
+	/// Get existing children paired with their placement, in order of `OctantPlacement::OCTANTS_ORDERED`.
+	/// 
+	/// ## Returns
+	///  `StorageResult` which contains either existing children or `StorageError`, octant at maximum depth has no children.
+	/// ## Errors
+	///   * InvalidOctantId - when parent_id is not valid or found in storage
+	fn get_existing_children_with_placement(&self, parent_id: &Self::OctantId) -> StorageResult<Vec<(Self::OctantId, OctantPlacement)>> {
+		let children = match self.get_existing_children(parent_id) {
+			Ok(children) => children,
+			Err(StorageError::OverMaxDepth(_)) => return Ok(Vec::new()),
+			Err(err) => return Err(err)
+		};
+		Ok(
+			children.into_iter()
+				.zip(OctantPlacement::OCTANTS_ORDERED)
+				.filter_map(|(maybe_child_id, child_placement)| Some((maybe_child_id?, child_placement)))
+				.collect()
+		)
+	}
+
 	/// Get octant/child placement relative to parent using parent id and child id.
 	/// 
 	/// ## Returns
@@ -190,7 +210,15 @@ pub trait OctantStorage{
 			)
 			.ok_or(StorageError::ChildNotFound(None))
 	}
-	
+
+	/// Estimate of heap memory held by storage, used for memory budgeting.
+	///
+	/// ## Returns
+	///  `Some(bytes)` when storage can estimate its allocations, otherwise `None`.
+	fn estimated_heap_bytes(&self) -> Option<usize> {
+		None
+	}
+
 }
 
 
@@ -419,6 +447,10 @@ impl <'a, Storage: OctantStorage> OctantStorage for OctantStorageAccessorMut<'a,
 	fn which_child_of(&self, parent_id: &Self::OctantId, child_id: &Self::OctantId) -> StorageResult<OctantPlacement> {
 		self.storage.which_child_of(parent_id, child_id)
 	}
+
+	fn estimated_heap_bytes(&self) -> Option<usize> {
+		self.storage.estimated_heap_bytes()
+	}
 }
 
 
@@ -479,7 +511,11 @@ impl <'a, Storage: OctantStorage> OctantStorage for OctantStorageAccessor<'a, St
 
 	fn which_child_of(&self, parent_id: &Self::OctantId, child_id: &Self::OctantId) -> StorageResult<OctantPlacement> {
 		self.storage.which_child_of(parent_id, child_id)
-	}	
+	}
+
+	fn estimated_heap_bytes(&self) -> Option<usize> {
+		self.storage.estimated_heap_bytes()
+	}
 }
 
 
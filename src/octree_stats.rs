@@ -0,0 +1,118 @@
+//! Statistics about tree shape and memory usage, used to budget memory and to choose between storage backends.
+use std::collections::BTreeMap;
+
+use super::octant_storage_trait::{OctantStorage, StorageResult};
+use super::octree_base::{depth_first_search_from_storage, OctreeBase, SearchControlFlow};
+use super::spatial_octree_base::SpatialOctreeBase;
+use super::voxels::voxel_cube::VolumetricCube;
+
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OctreeStats {
+	/// Number of octants at every depth, indexed by depth.
+	pub octants_per_depth: Vec<usize>,
+	pub octant_count: usize,
+	pub leaf_count: usize,
+	/// Average number of children of octants which have at least one child.
+	pub average_branching_factor: f32,
+	/// Number of octants with children for every child mask, where bit `OctantPlacement as usize` is set for existing child.
+	pub child_mask_histogram: BTreeMap<u8, usize>,
+	/// Estimate from `OctantStorage::estimated_heap_bytes`, or size of id and data for every octant
+	/// when storage can't estimate its allocations.
+	pub estimated_heap_bytes: usize
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SpatialOctreeStats {
+	pub octree: OctreeStats,
+	/// Volume of all octants at every depth, indexed by depth.
+	pub volume_per_depth: Vec<f32>
+}
+
+impl<Storage: OctantStorage> OctreeBase<Storage> {
+	/// Collect statistics of whole tree, see `stats_from_storage`.
+	///
+	/// ## Examples
+	/// ```
+	/// use modsvo::{octant_storage_trait::{ModifiableOctantStorage, OctantStorage}, SparseOctree};
+	///
+	/// let mut octree = SparseOctree::<u8>::new_with_root(0);
+	/// let root_id = octree.octants.get_root_id();
+	/// octree.octants.subdivide(&root_id, |placement| placement as u8).unwrap();
+	/// let stats = octree.stats().unwrap();
+	/// assert_eq!(stats.octants_per_depth, [1, 8]);
+	/// assert_eq!(stats.child_mask_histogram.get(&0xFF), Some(&1));
+	/// ```
+	pub fn stats(&self) -> StorageResult<OctreeStats> {
+		stats_from_storage(&self.octants)
+	}
+}
+
+impl<Storage: OctantStorage> SpatialOctreeBase<Storage, VolumetricCube> {
+	/// Collect statistics of whole tree together with volume covered by octants at every depth.
+	pub fn stats(&self) -> StorageResult<SpatialOctreeStats> {
+		let octree: OctreeStats = stats_from_storage(self.octants())?;
+		let root_volume: f32 = (self.get_root_voxel().half_extent() * 2.0).powi(3);
+		let volume_per_depth: Vec<f32> = octree.octants_per_depth.iter()
+			.enumerate()
+			.map(|(depth, &octant_count)| octant_count as f32 * root_volume / 8_f32.powi(depth as i32))
+			.collect();
+		Ok(
+			SpatialOctreeStats{
+				octree,
+				volume_per_depth
+			}
+		)
+	}
+}
+
+/// Collect statistics of tree starting at root of storage.
+///
+/// ## Errors
+/// Returns `StorageError` when traversal of storage fails.
+pub fn stats_from_storage<Storage: OctantStorage>(storage: &Storage) -> StorageResult<OctreeStats> {
+	let mut stats: OctreeStats = OctreeStats::default();
+	if storage.get_octant(&storage.get_root_id()).is_none() {
+		stats.estimated_heap_bytes = storage.estimated_heap_bytes().unwrap_or(0);
+		return Ok(stats);
+	}
+
+	let mut child_count: usize = 0;
+	depth_first_search_from_storage(
+		storage,
+		&storage.get_root_id(),
+		|depth, octant_id|{
+			if stats.octants_per_depth.len() <= depth as usize {
+				stats.octants_per_depth.resize(depth as usize + 1, 0);
+			}
+			stats.octants_per_depth[depth as usize] += 1;
+
+			// octants at max depth can't have children
+			let child_mask: u8 = storage.get_existing_children(octant_id)
+				.map(
+					|children| children.iter()
+						.enumerate()
+						.filter(|(_, child)| child.is_some())
+						.fold(0, |mask, (child_index, _)| mask | (1 << child_index))
+				)
+				.unwrap_or(0);
+			if child_mask == 0 {
+				stats.leaf_count += 1;
+			}
+			else {
+				child_count += child_mask.count_ones() as usize;
+				*stats.child_mask_histogram.entry(child_mask).or_insert(0) += 1;
+			}
+			SearchControlFlow::Continue
+		}
+	)?;
+
+	stats.octant_count = stats.octants_per_depth.iter().sum();
+	let internal_count: usize = stats.octant_count - stats.leaf_count;
+	if internal_count > 0 {
+		stats.average_branching_factor = child_count as f32 / internal_count as f32;
+	}
+	stats.estimated_heap_bytes = storage.estimated_heap_bytes()
+		.unwrap_or(stats.octant_count * (std::mem::size_of::<Storage::OctantId>() + std::mem::size_of::<Storage::Data>()));
+	Ok(stats)
+}
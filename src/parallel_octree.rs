@@ -0,0 +1,541 @@
+//! Parallel traversal and subdivision built on `rayon`, enabled by `rayon` feature.
+//!
+//! Work is split at `split_depth`: octants above it are visited on calling thread and every
+//! branch starting at `split_depth` is processed as independent task. Results are merged in the
+//! same order in which sequential traversal would visit octants, so they do not depend on scheduling.
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rayon::prelude::*;
+
+use super::morton_based_storage::morton_octant_id::MortonOctantId;
+use super::octant_meta::OctantPlacement;
+use super::octant_storage_trait::{ModifiableOctantStorage, OctantStorage, StorageError, StorageResult};
+use super::octree_base::{OctreeBase, SearchControlFlow, SearchControlFlowResult, SubdivisionControlFlow};
+use super::spatial_octree_base::SpatialOctreeBase;
+use super::voxel_trait::Voxel;
+use super::Depth;
+
+
+impl<Storage> OctreeBase<Storage>
+where
+	Storage: OctantStorage + Sync,
+	Storage::OctantId: Send + Sync
+{
+	/// Parallel version of `depth_first_search`, returned result is the same as sequential search would return.
+	/// #### Note: after `Break`, search function might still be called for octants which sequential search would not visit.
+	pub fn par_depth_first_search<F>(&self, octant_id: &Storage::OctantId, split_depth: Depth, search_func: F) -> StorageResult<SearchControlFlowResult<Storage::OctantId>>
+	where F: Fn(Depth, &Storage::OctantId) -> SearchControlFlow + Sync {
+		par_depth_first_search_from_storage(&self.octants, octant_id, split_depth, search_func)
+	}
+
+	pub fn par_depth_first_search_from_root<F>(&self, split_depth: Depth, search_func: F) -> StorageResult<SearchControlFlowResult<Storage::OctantId>>
+	where F: Fn(Depth, &Storage::OctantId) -> SearchControlFlow + Sync {
+		let root_id = self.octants.get_root_id();
+		self.par_depth_first_search(&root_id, split_depth, search_func)
+	}
+
+	/// Collect all octants without children in depth first order.
+	pub fn par_leaves(&self, split_depth: Depth) -> Vec<(Depth, Storage::OctantId)> {
+		par_leaves_from_storage(&self.octants, &self.octants.get_root_id(), split_depth)
+	}
+}
+
+impl<Storage> OctreeBase<Storage>
+where
+	Storage: ModifiableOctantStorage<OctantId = MortonOctantId> + Sync,
+	Storage::Data: Send
+{
+	/// Parallel version of `subdivide_if`, every branch starting at `split_depth` is evaluated as independent task
+	/// and subdivisions are applied afterwards in breadth first order, so resulting tree is identical to the one created by `subdivide_if`.
+	///
+	/// Predicate gets read-only storage as it was when branches were split off,
+	/// octants which are created below `split_depth` by the same call are not in it yet.
+	pub fn par_subdivide_if<F, U>(&mut self, octant_id: &Storage::OctantId, split_depth: Depth, subdivide_predicate: F) -> StorageResult<SearchControlFlowResult<Storage::OctantId>>
+	where
+		F: Fn(Depth, &Storage::OctantId, &Storage) -> SubdivisionControlFlow<U> + Sync,
+		U: FnMut(OctantPlacement) -> Storage::Data
+	{
+		par_subdivide_if_from_storage(&mut self.octants, octant_id, split_depth, subdivide_predicate)
+	}
+
+	pub fn par_subdivide_if_from_root<F, U>(&mut self, split_depth: Depth, subdivide_predicate: F) -> StorageResult<SearchControlFlowResult<Storage::OctantId>>
+	where
+		F: Fn(Depth, &Storage::OctantId, &Storage) -> SubdivisionControlFlow<U> + Sync,
+		U: FnMut(OctantPlacement) -> Storage::Data
+	{
+		let root_id = self.octants.get_root_id();
+		self.par_subdivide_if(&root_id, split_depth, subdivide_predicate)
+	}
+}
+
+impl<Storage, Volumetric> SpatialOctreeBase<Storage, Volumetric>
+where
+	Storage: OctantStorage + Sync,
+	Storage::OctantId: Send + Sync,
+	Volumetric: Voxel + Send + Sync
+{
+	pub fn par_depth_first_search<F>(&self, octant_id: &Storage::OctantId, split_depth: Depth, search_func: F) -> StorageResult<SearchControlFlowResult<Storage::OctantId>>
+	where F: Fn(Depth, &Storage::OctantId, &Volumetric) -> SearchControlFlow + Sync {
+		let voxel: Volumetric = self.get_voxel_by_id(octant_id).ok_or(StorageError::InvalidOctantId)?;
+		par_depth_first_search_with_voxel(self.octants(), octant_id, voxel, split_depth, search_func)
+	}
+
+	pub fn par_depth_first_search_from_root<F>(&self, split_depth: Depth, search_func: F) -> StorageResult<SearchControlFlowResult<Storage::OctantId>>
+	where F: Fn(Depth, &Storage::OctantId, &Volumetric) -> SearchControlFlow + Sync {
+		let root_id = self.get_root_id();
+		self.par_depth_first_search(&root_id, split_depth, search_func)
+	}
+
+	/// Collect all octants without children together with their voxels in depth first order.
+	pub fn par_leaves(&self, split_depth: Depth) -> Vec<(Depth, Storage::OctantId, Volumetric)> {
+		par_leaves_with_voxel(self.octants(), &self.get_root_id(), *self.get_root_voxel(), split_depth)
+	}
+}
+
+impl<Storage, Volumetric> SpatialOctreeBase<Storage, Volumetric>
+where
+	Storage: ModifiableOctantStorage<OctantId = MortonOctantId> + Sync,
+	Storage::Data: Send,
+	Volumetric: Voxel + Send + Sync
+{
+	/// Parallel version of `subdivide_if`, see `OctreeBase::par_subdivide_if`.
+	pub fn par_subdivide_if<F, U>(&mut self, octant_id: &Storage::OctantId, split_depth: Depth, subdivide_predicate: F) -> StorageResult<SearchControlFlowResult<Storage::OctantId>>
+	where
+		F: Fn(Depth, &Storage::OctantId, &Volumetric, &Storage) -> SubdivisionControlFlow<U> + Sync,
+		U: FnMut(OctantPlacement) -> Storage::Data
+	{
+		let voxel: Volumetric = self.get_voxel_by_id(octant_id).ok_or(StorageError::InvalidOctantId)?;
+		par_subdivide_if_with_voxel(self.octants_mut(), octant_id, voxel, split_depth, subdivide_predicate)
+	}
+
+	pub fn par_subdivide_if_from_root<F, U>(&mut self, split_depth: Depth, subdivide_predicate: F) -> StorageResult<SearchControlFlowResult<Storage::OctantId>>
+	where
+		F: Fn(Depth, &Storage::OctantId, &Volumetric, &Storage) -> SubdivisionControlFlow<U> + Sync,
+		U: FnMut(OctantPlacement) -> Storage::Data
+	{
+		let root_id = self.get_root_id();
+		self.par_subdivide_if(&root_id, split_depth, subdivide_predicate)
+	}
+}
+
+pub fn par_depth_first_search_from_storage<Storage, F>(storage: &Storage, octant_id: &Storage::OctantId, split_depth: Depth, search_func: F) -> StorageResult<SearchControlFlowResult<Storage::OctantId>>
+where
+	Storage: OctantStorage + Sync,
+	Storage::OctantId: Send + Sync,
+	F: Fn(Depth, &Storage::OctantId) -> SearchControlFlow + Sync
+{
+	par_depth_first_search_with_payload(storage, octant_id, (), &|_, _| (), split_depth, &|depth, id, _| search_func(depth, id))
+}
+
+pub fn par_depth_first_search_with_voxel<Storage, Volumetric, F>(storage: &Storage, octant_id: &Storage::OctantId, voxel: Volumetric, split_depth: Depth, search_func: F) -> StorageResult<SearchControlFlowResult<Storage::OctantId>>
+where
+	Storage: OctantStorage + Sync,
+	Storage::OctantId: Send + Sync,
+	Volumetric: Voxel + Send + Sync,
+	F: Fn(Depth, &Storage::OctantId, &Volumetric) -> SearchControlFlow + Sync
+{
+	par_depth_first_search_with_payload(storage, octant_id, voxel, &Volumetric::make_sub_voxel, split_depth, &search_func)
+}
+
+pub fn par_leaves_from_storage<Storage>(storage: &Storage, octant_id: &Storage::OctantId, split_depth: Depth) -> Vec<(Depth, Storage::OctantId)>
+where
+	Storage: OctantStorage + Sync,
+	Storage::OctantId: Send + Sync
+{
+	par_leaves_with_payload(storage, octant_id, (), &|_, _| (), split_depth)
+		.into_iter()
+		.map(|(depth, octant_id, _)| (depth, octant_id))
+		.collect()
+}
+
+pub fn par_leaves_with_voxel<Storage, Volumetric>(storage: &Storage, octant_id: &Storage::OctantId, voxel: Volumetric, split_depth: Depth) -> Vec<(Depth, Storage::OctantId, Volumetric)>
+where
+	Storage: OctantStorage + Sync,
+	Storage::OctantId: Send + Sync,
+	Volumetric: Voxel + Send + Sync
+{
+	par_leaves_with_payload(storage, octant_id, voxel, &Volumetric::make_sub_voxel, split_depth)
+}
+
+pub fn par_subdivide_if_from_storage<Storage, F, U>(storage: &mut Storage, octant_id: &Storage::OctantId, split_depth: Depth, subdivide_predicate: F) -> StorageResult<SearchControlFlowResult<Storage::OctantId>>
+where
+	Storage: ModifiableOctantStorage<OctantId = MortonOctantId> + Sync,
+	Storage::Data: Send,
+	F: Fn(Depth, &Storage::OctantId, &Storage) -> SubdivisionControlFlow<U> + Sync,
+	U: FnMut(OctantPlacement) -> Storage::Data
+{
+	par_subdivide_if_with_payload(storage, octant_id, (), &|_, _| (), split_depth, &|depth, id, _, storage| subdivide_predicate(depth, id, storage))
+}
+
+pub fn par_subdivide_if_with_voxel<Storage, Volumetric, F, U>(storage: &mut Storage, octant_id: &Storage::OctantId, voxel: Volumetric, split_depth: Depth, subdivide_predicate: F) -> StorageResult<SearchControlFlowResult<Storage::OctantId>>
+where
+	Storage: ModifiableOctantStorage<OctantId = MortonOctantId> + Sync,
+	Storage::Data: Send,
+	Volumetric: Voxel + Send + Sync,
+	F: Fn(Depth, &Storage::OctantId, &Volumetric, &Storage) -> SubdivisionControlFlow<U> + Sync,
+	U: FnMut(OctantPlacement) -> Storage::Data
+{
+	par_subdivide_if_with_payload(storage, octant_id, voxel, &Volumetric::make_sub_voxel, split_depth, &subdivide_predicate)
+}
+
+/// Part of depth first visit order, either already visited octant above split depth or branch handed to parallel task.
+enum VisitSegment<OctantId, Payload> {
+	Visited(StorageResult<SearchControlFlowResult<OctantId>>),
+	Branch(Depth, OctantId, Payload)
+}
+
+fn par_depth_first_search_with_payload<Storage, P, D, F>(
+	storage: &Storage,
+	octant_id: &Storage::OctantId,
+	payload: P,
+	descend_fn: &D,
+	split_depth: Depth,
+	search_func: &F
+) -> StorageResult<SearchControlFlowResult<Storage::OctantId>>
+where
+	Storage: OctantStorage + Sync,
+	Storage::OctantId: Send + Sync,
+	P: Copy + Send + Sync,
+	D: Fn(&P, OctantPlacement) -> P + Sync,
+	F: Fn(Depth, &Storage::OctantId, &P) -> SearchControlFlow + Sync
+{
+	let start_from_depth: Depth = storage.get_octant_depth(octant_id).ok_or(StorageError::InvalidOctantId)?;
+	let mut visit_segments: Vec<VisitSegment<Storage::OctantId, P>> = Vec::new();
+	split_depth_first(storage, start_from_depth, octant_id, payload, descend_fn, split_depth, search_func, &mut visit_segments);
+
+	// index of first branch which ended with `Break` or error, branches after it can stop early
+	let first_stopped_branch = AtomicUsize::new(usize::MAX);
+	let branch_results: Vec<Option<StorageResult<SearchControlFlowResult<Storage::OctantId>>>> = visit_segments.par_iter()
+		.enumerate()
+		.map(
+			|(segment_index, visit_segment)|{
+				let VisitSegment::Branch(depth, branch_id, branch_payload) = visit_segment else {
+					return None;
+				};
+				let branch_result = depth_first_search_with_payload(
+					storage,
+					*depth,
+					branch_id,
+					branch_payload,
+					descend_fn,
+					&|depth, id, payload|{
+						if first_stopped_branch.load(Ordering::Relaxed) < segment_index {
+							return SearchControlFlow::Break;
+						}
+						search_func(depth, id, payload)
+					}
+				);
+				if !matches!(branch_result, Ok(SearchControlFlowResult::Continue(_)) | Ok(SearchControlFlowResult::Skip(_))) {
+					first_stopped_branch.fetch_min(segment_index, Ordering::Relaxed);
+				}
+				Some(branch_result)
+			}
+		)
+		.collect();
+
+	let mut last_result: Option<SearchControlFlowResult<Storage::OctantId>> = None;
+	for (visit_segment, branch_result) in visit_segments.into_iter().zip(branch_results) {
+		let segment_result = match (visit_segment, branch_result) {
+			(VisitSegment::Visited(visited_result), _) => visited_result,
+			(VisitSegment::Branch(..), Some(branch_result)) => branch_result,
+			(VisitSegment::Branch(..), None) => unreachable!("Every branch segment produces result.")
+		};
+		match segment_result? {
+			SearchControlFlowResult::Break(break_id) => return Ok(SearchControlFlowResult::Break(break_id)),
+			other_result => last_result = Some(other_result)
+		}
+	}
+	last_result.ok_or(StorageError::InvalidOctantId)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn split_depth_first<Storage, P, D, F>(
+	storage: &Storage,
+	depth: Depth,
+	octant_id: &Storage::OctantId,
+	payload: P,
+	descend_fn: &D,
+	split_depth: Depth,
+	search_func: &F,
+	visit_segments: &mut Vec<VisitSegment<Storage::OctantId, P>>
+) -> bool
+where
+	Storage: OctantStorage,
+	P: Copy,
+	D: Fn(&P, OctantPlacement) -> P,
+	F: Fn(Depth, &Storage::OctantId, &P) -> SearchControlFlow
+{
+	if depth >= split_depth {
+		visit_segments.push(VisitSegment::Branch(depth, *octant_id, payload));
+		return true;
+	}
+
+	let next_step: SearchControlFlow = search_func(depth, octant_id, &payload);
+	visit_segments.push(VisitSegment::Visited(Ok(next_step.to_result(*octant_id))));
+	match next_step {
+		SearchControlFlow::Continue => {
+			let max_depth: Depth = storage.get_max_depth();
+			if depth >= max_depth {
+				visit_segments.push(VisitSegment::Visited(Err(StorageError::OverMaxDepth(max_depth))));
+				return false;
+			}
+			for child_placement in OctantPlacement::OCTANTS_ORDERED {
+				let Ok(child_id) = storage.get_existing_child(octant_id, child_placement) else {
+					continue;
+				};
+				let child_payload: P = descend_fn(&payload, child_placement);
+				if !split_depth_first(storage, depth + 1, &child_id, child_payload, descend_fn, split_depth, search_func, visit_segments) {
+					return false;
+				}
+			}
+			true
+		},
+		SearchControlFlow::Skip => true,
+		SearchControlFlow::Break => false
+	}
+}
+
+fn depth_first_search_with_payload<Storage, P, D, F>(
+	storage: &Storage,
+	depth: Depth,
+	octant_id: &Storage::OctantId,
+	payload: &P,
+	descend_fn: &D,
+	search_func: &F
+) -> StorageResult<SearchControlFlowResult<Storage::OctantId>>
+where
+	Storage: OctantStorage,
+	D: Fn(&P, OctantPlacement) -> P,
+	F: Fn(Depth, &Storage::OctantId, &P) -> SearchControlFlow
+{
+	let next_step: SearchControlFlow = search_func(depth, octant_id, payload);
+	let SearchControlFlow::Continue = next_step else {
+		return Ok(next_step.to_result(*octant_id));
+	};
+
+	let max_depth: Depth = storage.get_max_depth();
+	if depth >= max_depth {
+		return Err(StorageError::OverMaxDepth(max_depth));
+	}
+
+	let mut last_result: SearchControlFlowResult<Storage::OctantId> = next_step.to_result(*octant_id);
+	for child_placement in OctantPlacement::OCTANTS_ORDERED {
+		let Ok(child_id) = storage.get_existing_child(octant_id, child_placement) else {
+			continue;
+		};
+		let child_payload: P = descend_fn(payload, child_placement);
+		match depth_first_search_with_payload(storage, depth + 1, &child_id, &child_payload, descend_fn, search_func)? {
+			SearchControlFlowResult::Break(step_id) => return Ok(SearchControlFlowResult::Break(step_id)),
+			child_result => last_result = child_result
+		}
+	}
+	Ok(last_result)
+}
+
+fn par_leaves_with_payload<Storage, P, D>(
+	storage: &Storage,
+	octant_id: &Storage::OctantId,
+	payload: P,
+	descend_fn: &D,
+	split_depth: Depth
+) -> Vec<(Depth, Storage::OctantId, P)>
+where
+	Storage: OctantStorage + Sync,
+	Storage::OctantId: Send + Sync,
+	P: Copy + Send + Sync,
+	D: Fn(&P, OctantPlacement) -> P + Sync
+{
+	let Some(start_from_depth) = storage.get_octant_depth(octant_id) else {
+		return Vec::new();
+	};
+
+	// octants above split depth which are leaves are kept as their own branch, so order is preserved
+	let mut branches: Vec<(Depth, Storage::OctantId, P)> = Vec::new();
+	let mut to_be_visited: Vec<(Depth, Storage::OctantId, P)> = vec![(start_from_depth, *octant_id, payload)];
+	while let Some((depth, branch_id, branch_payload)) = to_be_visited.pop() {
+		let children = storage.get_existing_children_with_placement(&branch_id).unwrap_or_default();
+		if depth >= split_depth || children.is_empty() {
+			branches.push((depth, branch_id, branch_payload));
+			continue;
+		}
+		for (child_id, child_placement) in children.into_iter().rev() {
+			to_be_visited.push((depth + 1, child_id, descend_fn(&branch_payload, child_placement)));
+		}
+	}
+
+	branches.par_iter()
+		.map(
+			|(depth, branch_id, branch_payload)|{
+				let mut leaves: Vec<(Depth, Storage::OctantId, P)> = Vec::new();
+				collect_leaves(storage, *depth, branch_id, *branch_payload, descend_fn, &mut leaves);
+				leaves
+			}
+		)
+		.flatten_iter()
+		.collect()
+}
+
+fn collect_leaves<Storage, P, D>(storage: &Storage, depth: Depth, octant_id: &Storage::OctantId, payload: P, descend_fn: &D, leaves: &mut Vec<(Depth, Storage::OctantId, P)>)
+where
+	Storage: OctantStorage,
+	P: Copy,
+	D: Fn(&P, OctantPlacement) -> P
+{
+	let children = storage.get_existing_children_with_placement(octant_id).unwrap_or_default();
+	if children.is_empty() {
+		leaves.push((depth, *octant_id, payload));
+		return;
+	}
+	for (child_id, child_placement) in children {
+		collect_leaves(storage, depth + 1, &child_id, descend_fn(&payload, child_placement), descend_fn, leaves);
+	}
+}
+
+/// Decision of subdivision predicate with children data already created.
+enum SubdivisionDecision<Data> {
+	Subdivide([Data; OctantPlacement::OCTANTS_COUNT]),
+	Skip,
+	Break
+}
+
+/// Evaluated octant of branch, index of branch orders octants of the same depth like breadth first search would.
+struct PlannedSubdivision<Data> {
+	depth: Depth,
+	branch_index: usize,
+	octant_id: MortonOctantId,
+	decision: SubdivisionDecision<Data>
+}
+
+fn par_subdivide_if_with_payload<Storage, P, D, F, U>(
+	storage: &mut Storage,
+	octant_id: &MortonOctantId,
+	payload: P,
+	descend_fn: &D,
+	split_depth: Depth,
+	subdivide_predicate: &F
+) -> StorageResult<SearchControlFlowResult<MortonOctantId>>
+where
+	Storage: ModifiableOctantStorage<OctantId = MortonOctantId> + Sync,
+	Storage::Data: Send,
+	P: Copy + Send + Sync,
+	D: Fn(&P, OctantPlacement) -> P + Sync,
+	F: Fn(Depth, &MortonOctantId, &P, &Storage) -> SubdivisionControlFlow<U> + Sync,
+	U: FnMut(OctantPlacement) -> Storage::Data
+{
+	let start_from_depth: Depth = storage.get_octant_depth(octant_id).ok_or(StorageError::InvalidOctantId)?;
+	let mut to_be_visited: VecDeque<(Depth, MortonOctantId, P)> = VecDeque::from([(start_from_depth, *octant_id, payload)]);
+	let mut last_visited_step: Option<SearchControlFlowResult<MortonOctantId>> = None;
+
+	// octants above split depth are subdivided on calling thread in the same way as `subdivide_if` does it
+	while let Some(&(depth, _, _)) = to_be_visited.front() {
+		if depth >= split_depth {
+			break;
+		}
+		let (depth, current_id, current_payload) = to_be_visited.pop_front().expect("Front octant exists.");
+		match decide_subdivision(storage, depth, &current_id, &current_payload, subdivide_predicate) {
+			SubdivisionDecision::Subdivide(children_data) => {
+				last_visited_step = Some(SearchControlFlowResult::Continue(current_id));
+				let children_ids = subdivide_with_data(storage, &current_id, children_data)?;
+				for (child_id, child_placement) in children_ids.into_iter().zip(OctantPlacement::OCTANTS_ORDERED) {
+					to_be_visited.push_back((depth + 1, child_id, descend_fn(&current_payload, child_placement)));
+				}
+			},
+			SubdivisionDecision::Skip => last_visited_step = Some(SearchControlFlowResult::Skip(current_id)),
+			SubdivisionDecision::Break => return Ok(SearchControlFlowResult::Break(current_id))
+		}
+	}
+
+	// every branch is evaluated as its own task against storage which isn't modified meanwhile
+	let branches: Vec<(Depth, MortonOctantId, P)> = to_be_visited.into();
+	let first_break = AtomicUsize::new(usize::MAX);
+	let read_only_storage: &Storage = storage;
+	let mut planned: Vec<PlannedSubdivision<Storage::Data>> = branches.par_iter()
+		.enumerate()
+		.map(
+			|(branch_index, &(depth, branch_id, branch_payload))|{
+				plan_branch_subdivision(read_only_storage, branch_index, branches.len(), (depth, branch_id, branch_payload), descend_fn, subdivide_predicate, &first_break)
+			}
+		)
+		.flatten_iter()
+		.collect();
+
+	// stable sort keeps breadth first order of octants inside of every branch
+	planned.sort_by_key(|step| (step.depth, step.branch_index));
+	for step in planned {
+		match step.decision {
+			SubdivisionDecision::Subdivide(children_data) => {
+				last_visited_step = Some(SearchControlFlowResult::Continue(step.octant_id));
+				subdivide_with_data(storage, &step.octant_id, children_data)?;
+			},
+			SubdivisionDecision::Skip => last_visited_step = Some(SearchControlFlowResult::Skip(step.octant_id)),
+			SubdivisionDecision::Break => return Ok(SearchControlFlowResult::Break(step.octant_id))
+		}
+	}
+
+	last_visited_step.ok_or(StorageError::InvalidOctantId)
+}
+
+fn decide_subdivision<Storage, P, F, U>(storage: &Storage, depth: Depth, octant_id: &MortonOctantId, payload: &P, subdivide_predicate: &F) -> SubdivisionDecision<Storage::Data>
+where
+	Storage: OctantStorage<OctantId = MortonOctantId>,
+	F: Fn(Depth, &MortonOctantId, &P, &Storage) -> SubdivisionControlFlow<U>,
+	U: FnMut(OctantPlacement) -> Storage::Data
+{
+	match subdivide_predicate(depth, octant_id, payload, storage) {
+		SubdivisionControlFlow::Continue(mut create_data_fn) => SubdivisionDecision::Subdivide(OctantPlacement::OCTANTS_ORDERED.map(&mut create_data_fn)),
+		SubdivisionControlFlow::Skip => SubdivisionDecision::Skip,
+		SubdivisionControlFlow::Break => SubdivisionDecision::Break
+	}
+}
+
+fn subdivide_with_data<Storage: ModifiableOctantStorage>(storage: &mut Storage, octant_id: &Storage::OctantId, children_data: [Storage::Data; OctantPlacement::OCTANTS_COUNT]) -> StorageResult<[Storage::OctantId; OctantPlacement::OCTANTS_COUNT]> {
+	let mut children_data = children_data.map(Some);
+	storage.subdivide(
+		octant_id,
+		|child_placement| children_data[child_placement as usize].take().expect("Every placement is subdivided once.")
+	)
+}
+
+/// Evaluate predicate for branch in breadth first order without modifying storage, ids of children are derived from Morton code.
+///
+/// `first_break` holds order key of earliest `Break` seen by any branch,
+/// octants ordered after it would never be visited by sequential subdivision, so they aren't evaluated.
+fn plan_branch_subdivision<Storage, P, D, F, U>(
+	storage: &Storage,
+	branch_index: usize,
+	branch_count: usize,
+	(depth, branch_id, payload): (Depth, MortonOctantId, P),
+	descend_fn: &D,
+	subdivide_predicate: &F,
+	first_break: &AtomicUsize
+) -> Vec<PlannedSubdivision<Storage::Data>>
+where
+	Storage: OctantStorage<OctantId = MortonOctantId>,
+	D: Fn(&P, OctantPlacement) -> P,
+	F: Fn(Depth, &MortonOctantId, &P, &Storage) -> SubdivisionControlFlow<U>,
+	U: FnMut(OctantPlacement) -> Storage::Data
+{
+	let order_key = |depth: Depth| depth as usize * branch_count + branch_index;
+	let mut planned: Vec<PlannedSubdivision<Storage::Data>> = Vec::new();
+	let mut to_be_visited: VecDeque<(Depth, MortonOctantId, P)> = VecDeque::from([(depth, branch_id, payload)]);
+	while let Some((depth, octant_id, payload)) = to_be_visited.pop_front() {
+		if order_key(depth) > first_break.load(Ordering::Relaxed) {
+			break;
+		}
+		let decision = decide_subdivision(storage, depth, &octant_id, &payload, subdivide_predicate);
+		let is_break: bool = matches!(decision, SubdivisionDecision::Break);
+		// children below max depth can't be created, storage reports it when plan is applied
+		if matches!(decision, SubdivisionDecision::Subdivide(_)) && depth < storage.get_max_depth() {
+			for child_placement in OctantPlacement::OCTANTS_ORDERED {
+				to_be_visited.push_back((depth + 1, octant_id.child_id_by_placement(child_placement), descend_fn(&payload, child_placement)));
+			}
+		}
+		planned.push(PlannedSubdivision{depth, branch_index, octant_id, decision});
+		if is_break {
+			first_break.fetch_min(order_key(depth), Ordering::Relaxed);
+			break;
+		}
+	}
+	planned
+}
@@ -0,0 +1,321 @@
+//! A* pathfinding through free space of spatial octree.
+//!
+//! Leaves whose data passes walkable predicate are nodes of graph, octants of different depths are connected
+//! when they touch in one of directions chosen by `NeighborConnectivity`. Space of missing octants is never walkable.
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+
+use glam::Vec3A;
+
+use super::field_sampling::{descend_towards_point_from_storage, octant_at_point_from_storage};
+use super::octant_meta::{OctantNeighborDirection, OctantPlacement};
+use super::octant_storage_trait::OctantStorage;
+use super::spatial_octree_base::{existing_children_with_voxel_from_storage, SpatialOctreeBase};
+use super::voxel_trait::Voxel;
+use super::voxels::voxel_cube::VolumetricCube;
+
+
+/// Which touching octants are connected, every category includes the previous ones.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NeighborConnectivity {
+	/// Octants sharing face, `OctantNeighborDirection::FACING_DIRECTIONS`.
+	#[default]
+	Faces,
+	/// Octants sharing face or edge, directions made of at most two facing directions.
+	Edges,
+	/// Octants sharing face, edge or corner, `OctantNeighborDirection::ALL_DIRECTIONS`.
+	Corners
+}
+
+impl NeighborConnectivity {
+	const EDGE_DIRECTIONS_COUNT: usize = 12;
+
+	pub fn directions(&self) -> &'static [OctantNeighborDirection] {
+		match self {
+			Self::Faces => &OctantNeighborDirection::FACING_DIRECTIONS,
+			Self::Edges => &OctantNeighborDirection::ALL_DIRECTIONS[..OctantNeighborDirection::FACING_NEIGHBOR_DIRECTIONS_COUNT + Self::EDGE_DIRECTIONS_COUNT],
+			Self::Corners => &OctantNeighborDirection::ALL_DIRECTIONS
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PathfindingOptions {
+	pub connectivity: NeighborConnectivity,
+	/// Drop waypoints which can be skipped by straight line through walkable octants.
+	pub smooth: bool
+}
+
+#[derive(Clone, Debug)]
+pub struct OctreePath<OctantId> {
+	/// Walkable leaves from octant of start to octant of goal.
+	pub octants: Vec<OctantId>,
+	/// Start, centers of octants between start and goal, and goal.
+	pub waypoints: Vec<Vec3A>,
+	/// Length of polyline through waypoints.
+	pub length: f32
+}
+
+impl<Storage: OctantStorage> SpatialOctreeBase<Storage, VolumetricCube>
+where Storage::OctantId: Hash + Eq {
+	/// Find shortest path between two positions, see `find_path_from_storage`.
+	pub fn find_path<F>(&self, start: Vec3A, goal: Vec3A, options: PathfindingOptions, walkable: F) -> Option<OctreePath<Storage::OctantId>>
+	where F: Fn(&Storage::Data, &VolumetricCube) -> bool {
+		find_path_from_storage(self.octants(), self.get_root_voxel(), start, goal, options, walkable)
+	}
+}
+
+impl<Storage: OctantStorage> SpatialOctreeBase<Storage, VolumetricCube> {
+	/// Leaves touching octant, see `leaf_neighbors_from_storage`.
+	pub fn leaf_neighbors(&self, voxel: &VolumetricCube, connectivity: NeighborConnectivity) -> Vec<(Storage::OctantId, VolumetricCube)> {
+		leaf_neighbors_from_storage(self.octants(), self.get_root_voxel(), voxel, connectivity)
+	}
+}
+
+/// Run A* between leaves containing start and goal, cost of step is distance between centers of octants.
+///
+/// ## Returns
+/// Path, `None` when start or goal lies outside of root or in octant which isn't walkable, or when goal can't be reached.
+pub fn find_path_from_storage<Storage, F>(
+	storage: &Storage,
+	root_voxel: &VolumetricCube,
+	start: Vec3A,
+	goal: Vec3A,
+	options: PathfindingOptions,
+	walkable: F
+) -> Option<OctreePath<Storage::OctantId>>
+where
+	Storage: OctantStorage,
+	Storage::OctantId: Hash + Eq,
+	F: Fn(&Storage::Data, &VolumetricCube) -> bool
+{
+	let is_walkable_leaf = |octant_id: &Storage::OctantId, voxel: &VolumetricCube| -> bool {
+		storage.get_octant(octant_id).is_some_and(|data| walkable(data, voxel))
+	};
+	let (start_id, start_voxel) = walkable_leaf_at_point(storage, root_voxel, start, &is_walkable_leaf)?;
+	let (goal_id, goal_voxel) = walkable_leaf_at_point(storage, root_voxel, goal, &is_walkable_leaf)?;
+
+	let mut open: BinaryHeap<OpenOctant<Storage::OctantId>> = BinaryHeap::new();
+	let mut nodes: HashMap<Storage::OctantId, PathNode<Storage::OctantId>> = HashMap::new();
+	nodes.insert(start_id, PathNode{cost: 0.0, previous: None, voxel: start_voxel});
+	open.push(OpenOctant{estimate: start_voxel.center().distance(goal_voxel.center()), cost: 0.0, octant_id: start_id});
+
+	while let Some(OpenOctant{cost, octant_id, ..}) = open.pop() {
+		if octant_id == goal_id {
+			break;
+		}
+		let voxel: VolumetricCube = nodes[&octant_id].voxel;
+		if cost > nodes[&octant_id].cost {
+			continue;
+		}
+		for (neighbor_id, neighbor_voxel) in leaf_neighbors_from_storage(storage, root_voxel, &voxel, options.connectivity) {
+			if !is_walkable_leaf(&neighbor_id, &neighbor_voxel) {
+				continue;
+			}
+			let neighbor_cost: f32 = cost + voxel.center().distance(neighbor_voxel.center());
+			if nodes.get(&neighbor_id).is_some_and(|node| node.cost <= neighbor_cost) {
+				continue;
+			}
+			nodes.insert(neighbor_id, PathNode{cost: neighbor_cost, previous: Some(octant_id), voxel: neighbor_voxel});
+			open.push(OpenOctant{
+				estimate: neighbor_cost + neighbor_voxel.center().distance(goal_voxel.center()),
+				cost: neighbor_cost,
+				octant_id: neighbor_id
+			});
+		}
+	}
+
+	let mut octants: Vec<Storage::OctantId> = vec![goal_id];
+	while let Some(previous_id) = nodes.get(octants.last()?)?.previous {
+		octants.push(previous_id);
+	}
+	if *octants.last()? != start_id {
+		return None;
+	}
+	octants.reverse();
+
+	let mut waypoints: Vec<Vec3A> = octants.iter().map(|octant_id| nodes[octant_id].voxel.center()).collect();
+	waypoints[0] = start;
+	if waypoints.len() == 1 {
+		waypoints.push(goal);
+	}
+	else {
+		*waypoints.last_mut()? = goal;
+	}
+	if options.smooth {
+		waypoints = pull_string(storage, root_voxel, &waypoints, &is_walkable_leaf);
+	}
+	let length: f32 = waypoints.windows(2).map(|segment| segment[0].distance(segment[1])).sum();
+	Some(OctreePath{octants, waypoints, length})
+}
+
+/// Collect leaves touching octant in directions of connectivity, leaves can be larger or smaller than octant.
+///
+/// ## Arguments
+/// * `voxel` - Voxel of octant, octant itself doesn't have to exist.
+pub fn leaf_neighbors_from_storage<Storage: OctantStorage>(
+	storage: &Storage,
+	root_voxel: &VolumetricCube,
+	voxel: &VolumetricCube,
+	connectivity: NeighborConnectivity
+) -> Vec<(Storage::OctantId, VolumetricCube)> {
+	let mut neighbors: Vec<(Storage::OctantId, VolumetricCube)> = Vec::new();
+	for &direction in connectivity.directions() {
+		let neighbor_center: Vec3A = voxel.center() + VolumetricCube::get_spatial_neighbor_direction(direction) * (2.0 * voxel.half_extent());
+		let Some((octant_id, octant_voxel)) = descend_towards_point_from_storage(storage, root_voxel, neighbor_center, voxel.subdivision_depth(root_voxel.half_extent())) else {
+			continue;
+		};
+		let is_leaf: bool = existing_children_with_voxel_from_storage(storage, &octant_id, &octant_voxel).is_empty();
+		// larger inner octant means that space of neighbor is missing
+		if !is_leaf && octant_voxel.half_extent() > voxel.half_extent() * 1.5 {
+			continue;
+		}
+
+		let mut touching: Vec<(Storage::OctantId, VolumetricCube)> = Vec::new();
+		collect_touching_leaves(storage, octant_id, octant_voxel, voxel, &mut touching);
+		for (leaf_id, leaf_voxel) in touching {
+			if !neighbors.iter().any(|(neighbor_id, _)| *neighbor_id == leaf_id) {
+				neighbors.push((leaf_id, leaf_voxel));
+			}
+		}
+	}
+	neighbors
+}
+
+#[derive(Clone, Copy)]
+struct PathNode<OctantId> {
+	cost: f32,
+	previous: Option<OctantId>,
+	voxel: VolumetricCube
+}
+
+/// Entry of open set, ordered so that `BinaryHeap` pops lowest estimate first.
+struct OpenOctant<OctantId> {
+	estimate: f32,
+	cost: f32,
+	octant_id: OctantId
+}
+
+impl<OctantId> PartialEq for OpenOctant<OctantId> {
+	fn eq(&self, other: &Self) -> bool {
+		self.cmp(other) == Ordering::Equal
+	}
+}
+
+impl<OctantId> Eq for OpenOctant<OctantId> {}
+
+impl<OctantId> PartialOrd for OpenOctant<OctantId> {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl<OctantId> Ord for OpenOctant<OctantId> {
+	fn cmp(&self, other: &Self) -> Ordering {
+		other.estimate.total_cmp(&self.estimate)
+	}
+}
+
+fn walkable_leaf_at_point<Storage: OctantStorage, W>(storage: &Storage, root_voxel: &VolumetricCube, point: Vec3A, is_walkable_leaf: &W) -> Option<(Storage::OctantId, VolumetricCube)>
+where W: Fn(&Storage::OctantId, &VolumetricCube) -> bool {
+	let (octant_id, voxel) = octant_at_point_from_storage(storage, root_voxel, point)?;
+	let is_leaf: bool = existing_children_with_voxel_from_storage(storage, &octant_id, &voxel).is_empty();
+	if is_leaf && is_walkable_leaf(&octant_id, &voxel) {
+		Some((octant_id, voxel))
+	}
+	else {
+		None
+	}
+}
+
+/// Boxes overlap or share face, edge or corner.
+fn touches(voxel: &VolumetricCube, other: &VolumetricCube) -> bool {
+	let tolerance: f32 = voxel.half_extent().min(other.half_extent()) * 1e-3;
+	voxel.min().cmple(other.max() + tolerance).all() && voxel.max().cmpge(other.min() - tolerance).all()
+}
+
+fn collect_touching_leaves<Storage: OctantStorage>(
+	storage: &Storage,
+	octant_id: Storage::OctantId,
+	voxel: VolumetricCube,
+	target: &VolumetricCube,
+	leaves: &mut Vec<(Storage::OctantId, VolumetricCube)>
+) {
+	if !touches(&voxel, target) {
+		return;
+	}
+	let children: Vec<(Storage::OctantId, VolumetricCube)> = existing_children_with_voxel_from_storage(storage, &octant_id, &voxel);
+	if children.is_empty() {
+		leaves.push((octant_id, voxel));
+	}
+	for (child_id, child_voxel) in children {
+		collect_touching_leaves(storage, child_id, child_voxel, target, leaves);
+	}
+}
+
+/// Keep only waypoints where straight line to next kept waypoint would leave walkable octants.
+fn pull_string<Storage: OctantStorage, W>(storage: &Storage, root_voxel: &VolumetricCube, waypoints: &[Vec3A], is_walkable_leaf: &W) -> Vec<Vec3A>
+where W: Fn(&Storage::OctantId, &VolumetricCube) -> bool {
+	let mut pulled: Vec<Vec3A> = vec![waypoints[0]];
+	let mut current: usize = 0;
+	while current + 1 < waypoints.len() {
+		let next: usize = (current + 2..waypoints.len()).rev()
+			.find(|&candidate| {
+				let root_id: Storage::OctantId = storage.get_root_id();
+				is_segment_walkable(storage, root_id, *root_voxel, waypoints[current], waypoints[candidate], is_walkable_leaf)
+			})
+			.unwrap_or(current + 1);
+		pulled.push(waypoints[next]);
+		current = next;
+	}
+	pulled
+}
+
+/// Every leaf crossed by segment is walkable and segment doesn't cross space of missing octants.
+fn is_segment_walkable<Storage: OctantStorage, W>(
+	storage: &Storage,
+	octant_id: Storage::OctantId,
+	voxel: VolumetricCube,
+	start: Vec3A,
+	end: Vec3A,
+	is_walkable_leaf: &W
+) -> bool
+where W: Fn(&Storage::OctantId, &VolumetricCube) -> bool {
+	if !segment_intersects_voxel(start, end, &voxel) {
+		return true;
+	}
+	let children: Vec<(Storage::OctantId, VolumetricCube)> = existing_children_with_voxel_from_storage(storage, &octant_id, &voxel);
+	if children.is_empty() {
+		return is_walkable_leaf(&octant_id, &voxel);
+	}
+	OctantPlacement::OCTANTS_ORDERED.into_iter().all(|placement| {
+		let child_voxel: VolumetricCube = voxel.make_sub_voxel(placement);
+		match storage.get_existing_child(&octant_id, placement) {
+			Ok(child_id) => is_segment_walkable(storage, child_id, child_voxel, start, end, is_walkable_leaf),
+			Err(_) => !segment_intersects_voxel(start, end, &child_voxel)
+		}
+	})
+}
+
+/// Slab test of segment against closed box.
+fn segment_intersects_voxel(start: Vec3A, end: Vec3A, voxel: &VolumetricCube) -> bool {
+	let direction: Vec3A = end - start;
+	let (mut entry, mut exit): (f32, f32) = (0.0, 1.0);
+	for axis in 0..3 {
+		if direction[axis] == 0.0 {
+			if start[axis] < voxel.min()[axis] || start[axis] > voxel.max()[axis] {
+				return false;
+			}
+			continue;
+		}
+		let first: f32 = (voxel.min()[axis] - start[axis]) / direction[axis];
+		let second: f32 = (voxel.max()[axis] - start[axis]) / direction[axis];
+		entry = entry.max(first.min(second));
+		exit = exit.min(first.max(second));
+		if entry > exit {
+			return false;
+		}
+	}
+	true
+}
@@ -0,0 +1,245 @@
+//! Building spatial octree from point clouds with per-octant aggregation of points.
+//!
+//! Points are sorted by Morton code at max depth, so every octant covers contiguous range of sorted points
+//! and tree is built bottom-up in one pass over them, leaves reduce their points and parents merge their children.
+use glam::Vec3A;
+
+use super::morton_based_storage::morton_octant_id::MortonOctantId;
+use super::octant_meta::OctantPlacement;
+use super::octant_storage_trait::{ModifiableOctantStorage, StorageError, StorageResult};
+use super::spatial_octree_base::SpatialOctreeBase;
+use super::voxels::voxel_cube::VolumetricCube;
+use super::Depth;
+
+
+pub trait CloudPoint {
+	fn position(&self) -> Vec3A;
+
+	/// Color of point, black for points without color.
+	fn color(&self) -> Vec3A {
+		Vec3A::ZERO
+	}
+}
+
+impl CloudPoint for Vec3A {
+	fn position(&self) -> Vec3A {
+		*self
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColoredPoint {
+	pub position: Vec3A,
+	pub color: Vec3A
+}
+
+impl CloudPoint for ColoredPoint {
+	fn position(&self) -> Vec3A {
+		self.position
+	}
+
+	fn color(&self) -> Vec3A {
+		self.color
+	}
+}
+
+/// Aggregates points into octant data.
+pub trait PointReducer<Point> {
+	type Data;
+
+	/// Create data of leaf octant from points inside of it, points are in Morton order.
+	fn reduce_points(&mut self, points: &[&Point]) -> Self::Data;
+
+	/// Create data of parent octant from data of its non-empty children.
+	fn merge_children(&mut self, children: &[&Self::Data]) -> Self::Data;
+}
+
+/// Statistics of points inside of octant.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PointStatistics {
+	pub count: usize,
+	pub centroid: Vec3A,
+	pub color_average: Vec3A,
+	pub bounds_min: Vec3A,
+	pub bounds_max: Vec3A
+}
+
+impl Default for PointStatistics {
+	fn default() -> Self {
+		PointStatistics{
+			count: 0,
+			centroid: Vec3A::ZERO,
+			color_average: Vec3A::ZERO,
+			bounds_min: Vec3A::INFINITY,
+			bounds_max: Vec3A::NEG_INFINITY
+		}
+	}
+}
+
+impl PointStatistics {
+	/// Merge statistics, weighting averages by number of points.
+	pub fn merge(&self, other: &Self) -> Self {
+		let count: usize = self.count + other.count;
+		if count == 0 {
+			return PointStatistics::default();
+		}
+		let (self_weight, other_weight) = (self.count as f32 / count as f32, other.count as f32 / count as f32);
+		PointStatistics{
+			count,
+			centroid: self.centroid * self_weight + other.centroid * other_weight,
+			color_average: self.color_average * self_weight + other.color_average * other_weight,
+			bounds_min: self.bounds_min.min(other.bounds_min),
+			bounds_max: self.bounds_max.max(other.bounds_max)
+		}
+	}
+}
+
+/// Reducer computing `PointStatistics` for every octant.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PointStatisticsReducer;
+
+impl<Point: CloudPoint> PointReducer<Point> for PointStatisticsReducer {
+	type Data = PointStatistics;
+
+	fn reduce_points(&mut self, points: &[&Point]) -> PointStatistics {
+		if points.is_empty() {
+			return PointStatistics::default();
+		}
+		let mut statistics: PointStatistics = PointStatistics{
+			count: points.len(),
+			..Default::default()
+		};
+		for point in points {
+			let position: Vec3A = point.position();
+			statistics.centroid += position;
+			statistics.color_average += point.color();
+			statistics.bounds_min = statistics.bounds_min.min(position);
+			statistics.bounds_max = statistics.bounds_max.max(position);
+		}
+		statistics.centroid /= points.len() as f32;
+		statistics.color_average /= points.len() as f32;
+		statistics
+	}
+
+	fn merge_children(&mut self, children: &[&PointStatistics]) -> PointStatistics {
+		children.iter().fold(PointStatistics::default(), |merged, child| merged.merge(child))
+	}
+}
+
+impl<Storage: ModifiableOctantStorage> SpatialOctreeBase<Storage, VolumetricCube> {
+	/// Build tree from point cloud, subdividing only octants with enough points, so tree adapts to density.
+	///
+	/// Points outside of root voxel are ignored.
+	///
+	/// ## Arguments
+	/// * `max_depth` - Depth at which octants are not subdivided anymore.
+	/// * `min_points_to_subdivide` - Octants holding fewer points stay leaves.
+	/// * `reducer` - Aggregates points into octant data.
+	///
+	/// ## Errors
+	/// Returns `StorageError::OverMaxDepth` when `max_depth` exceeds depth supported by storage or Morton code.
+	///
+	/// ## Examples
+	/// ```
+	/// use glam::Vec3A;
+	/// use modsvo::{octant_storage_trait::OctantStorage, point_cloud::{PointStatistics, PointStatisticsReducer}, voxels::voxel_cube::VolumetricCube, SpatialSparseOctree};
+	///
+	/// let points = [Vec3A::new(0.5, 0.5, 0.5), Vec3A::new(-0.5, 0.5, 0.5), Vec3A::new(-0.5, -0.5, 0.5)];
+	/// let octree = SpatialSparseOctree::<PointStatistics>::from_point_cloud(VolumetricCube::new(Vec3A::ZERO, 1.0), &points, 8, 2, &mut PointStatisticsReducer).unwrap();
+	/// assert_eq!(octree.octants().get_octant(&octree.get_root_id()).unwrap().count, 3);
+	/// ```
+	pub fn from_point_cloud<Point, R>(root_voxel: VolumetricCube, points: &[Point], max_depth: Depth, min_points_to_subdivide: usize, reducer: &mut R) -> StorageResult<Self>
+	where
+		Storage: Default,
+		Point: CloudPoint,
+		R: PointReducer<Point, Data = Storage::Data>
+	{
+		if max_depth > MortonOctantId::MAX_DEPTH {
+			return Err(StorageError::OverMaxDepth(MortonOctantId::MAX_DEPTH));
+		}
+		let sorted_points: Vec<(u64, &Point)> = sort_points_by_morton_code(&root_voxel, points, max_depth);
+		let builder = PointCloudBuilder{
+			sorted_points: &sorted_points,
+			max_depth,
+			min_points_to_subdivide
+		};
+		let root_octant: BuiltOctant<Storage::Data> = builder.build_recursive(0, 0..sorted_points.len(), reducer);
+
+		let mut octree: Self = Self::new_with_root(root_voxel, root_octant.data);
+		let root_id: Storage::OctantId = octree.get_root_id();
+		insert_built_children(octree.octants_mut(), &root_id, root_octant.children)?;
+		Ok(octree)
+	}
+}
+
+/// Morton code of points at given depth relative to voxel, sorted, so octants form contiguous ranges.
+fn sort_points_by_morton_code<'a, Point: CloudPoint>(voxel: &VolumetricCube, points: &'a [Point], depth: Depth) -> Vec<(u64, &'a Point)> {
+	let grid_size: f32 = (1_u32 << depth) as f32;
+	let max_coordinate: f32 = grid_size - 1.0;
+	let mut sorted_points: Vec<(u64, &Point)> = points.iter()
+		.filter(|point| voxel.contains_point(point.position()))
+		.map(
+			|point|{
+				let grid_position: Vec3A = ((point.position() - voxel.min()) / (voxel.half_extent() * 2.0) * grid_size)
+					.floor()
+					.clamp(Vec3A::ZERO, Vec3A::splat(max_coordinate));
+				let morton_id: MortonOctantId = MortonOctantId::from_xyz(grid_position.x as u16, grid_position.y as u16, grid_position.z as u16, depth)
+					.expect("Grid position is clamped to depth.");
+				(morton_id.as_morton(), point)
+			}
+		)
+		.collect();
+	sorted_points.sort_unstable_by_key(|(morton_code, _)| *morton_code);
+	sorted_points
+}
+
+struct BuiltOctant<Data> {
+	data: Data,
+	children: Vec<(OctantPlacement, BuiltOctant<Data>)>
+}
+
+struct PointCloudBuilder<'a, Point> {
+	sorted_points: &'a [(u64, &'a Point)],
+	max_depth: Depth,
+	min_points_to_subdivide: usize
+}
+
+impl<Point> PointCloudBuilder<'_, Point> {
+	fn build_recursive<R>(&self, depth: Depth, range: std::ops::Range<usize>, reducer: &mut R) -> BuiltOctant<R::Data>
+	where R: PointReducer<Point> {
+		let points_in_octant = &self.sorted_points[range.clone()];
+		if depth >= self.max_depth || points_in_octant.len() < self.min_points_to_subdivide {
+			let points: Vec<&Point> = points_in_octant.iter().map(|(_, point)| *point).collect();
+			return BuiltOctant{
+				data: reducer.reduce_points(&points),
+				children: Vec::new()
+			};
+		}
+
+		// points are sorted, so every child is contiguous sub range
+		let placement_shift: u32 = (self.max_depth - depth - 1) as u32 * 3;
+		let placement_of = |morton_code: u64| ((morton_code >> placement_shift) & 0b111) as usize;
+		let mut children: Vec<(OctantPlacement, BuiltOctant<R::Data>)> = Vec::new();
+		let mut child_start: usize = range.start;
+		while child_start < range.end {
+			let placement_index: usize = placement_of(self.sorted_points[child_start].0);
+			let child_end: usize = child_start + self.sorted_points[child_start..range.end].partition_point(|(morton_code, _)| placement_of(*morton_code) == placement_index);
+			children.push((OctantPlacement::OCTANTS_ORDERED[placement_index], self.build_recursive(depth + 1, child_start..child_end, reducer)));
+			child_start = child_end;
+		}
+
+		let children_data: Vec<&R::Data> = children.iter().map(|(_, child)| &child.data).collect();
+		BuiltOctant{
+			data: reducer.merge_children(&children_data),
+			children
+		}
+	}
+}
+
+fn insert_built_children<Storage: ModifiableOctantStorage>(storage: &mut Storage, parent_id: &Storage::OctantId, children: Vec<(OctantPlacement, BuiltOctant<Storage::Data>)>) -> StorageResult<()> {
+	for (child_placement, child) in children {
+		let (child_id, _) = storage.insert_octant(parent_id, child_placement, child.data)?;
+		insert_built_children(storage, &child_id, child.children)?;
+	}
+	Ok(())
+}
@@ -0,0 +1,238 @@
+//! Growing and shrinking of root voxel of spatial tree.
+//!
+//! Growing creates new root twice the size of old one and old tree becomes one of its children,
+//! shrinking drops outer levels which have only single child. Octants are moved through storage interface,
+//! so storage assigns new ids to all of them, e.g. every `MortonOctantId` gets new placement prefix.
+use glam::Vec3A;
+
+use super::octant_meta::OctantPlacement;
+use super::octant_storage_trait::{ModifiableOctantStorage, StorageError, StorageResult};
+use super::spatial_octree_base::SpatialOctreeBase;
+use super::voxel_trait::Voxel;
+use super::voxels::voxel_cube::VolumetricCube;
+use super::Depth;
+
+
+impl<Storage: ModifiableOctantStorage> SpatialOctreeBase<Storage, VolumetricCube> {
+	/// Double root voxel towards point and move whole tree under new root.
+	///
+	/// ## Returns
+	/// Placement of old root inside of new root.
+	///
+	/// ## Errors
+	/// * `OverMaxDepth` - when deepest octant would be moved below max depth of storage, tree is not modified.
+	/// * `InvalidOctantId` - when storage has no root.
+	pub fn grow_root(&mut self, point: Vec3A, new_root_data: Storage::Data) -> StorageResult<OctantPlacement> {
+		let root_id: Storage::OctantId = self.get_root_id();
+		let branch_layout: BranchLayout<Storage::OctantId> = BranchLayout::of(self.octants(), root_id)?;
+		if branch_layout.depth >= self.octants().get_max_depth() {
+			return Err(StorageError::OverMaxDepth(self.octants().get_max_depth()));
+		}
+
+		let mut root_voxel: VolumetricCube = *self.get_root_voxel();
+		let old_root_placement: OctantPlacement = root_voxel.expand_in_direction(point.into());
+		let branch: Vec<(usize, OctantPlacement, Storage::Data)> = branch_layout.detach(self.octants_mut());
+		self.octants_mut().insert_root(new_root_data);
+		let new_root_id: Storage::OctantId = self.get_root_id();
+		attach_branch(self.octants_mut(), Some((new_root_id, old_root_placement)), branch)?;
+		self.set_root_voxel(root_voxel);
+		Ok(old_root_placement)
+	}
+
+	/// Grow root until root voxel contains point, see `grow_root`.
+	///
+	/// ## Returns
+	/// Number of levels added above old root.
+	///
+	/// ## Panics
+	/// When point is not finite.
+	pub fn grow_to_contain<F>(&mut self, point: Vec3A, mut new_root_data: F) -> StorageResult<Depth>
+	where F: FnMut(&VolumetricCube) -> Storage::Data {
+		assert!(point.is_finite(), "Root can't grow to contain point which is not finite.");
+		let mut added_levels: Depth = 0;
+		while !self.get_root_voxel().contains_point(point) {
+			let mut new_root_voxel: VolumetricCube = *self.get_root_voxel();
+			new_root_voxel.expand_in_direction(point.into());
+			self.grow_root(point, new_root_data(&new_root_voxel))?;
+			added_levels += 1;
+		}
+		Ok(added_levels)
+	}
+
+	/// Insert data into octant containing point, growing root when point lies outside of it.
+	///
+	/// ## Arguments
+	/// * `depth` - Depth of inserted octant in tree before growth, so size of inserted octant doesn't depend on growth.
+	/// * `fill_data` - Creates data of new roots and of missing octants on the way to inserted octant.
+	///
+	/// ## Errors
+	/// Returns `StorageError::OverMaxDepth` when inserted octant would lie below max depth of storage, root doesn't grow then.
+	///
+	/// ## Panics
+	/// When point is not finite.
+	///
+	/// ## Examples
+	/// ```
+	/// use glam::Vec3A;
+	/// use modsvo::{voxels::voxel_cube::VolumetricCube, SpatialSparseOctree};
+	///
+	/// let mut octree = SpatialSparseOctree::<u8>::new_with_root(VolumetricCube::new(Vec3A::ZERO, 1.0), 0);
+	/// let octant_id = octree.insert_at_point(Vec3A::new(2.5, 0.5, 0.5), 2, 7, |_| 0).unwrap();
+	/// assert_eq!(octree.get_root_voxel().half_extent(), 2.0);
+	/// assert!(octree.get_voxel_by_id(&octant_id).unwrap().contains_point(Vec3A::new(2.5, 0.5, 0.5)));
+	/// ```
+	pub fn insert_at_point<F>(&mut self, point: Vec3A, depth: Depth, data: Storage::Data, mut fill_data: F) -> StorageResult<Storage::OctantId>
+	where F: FnMut(&VolumetricCube) -> Storage::Data {
+		// growth rewrites ids of whole tree, so depth of inserted octant is checked before it
+		let max_depth: Depth = self.octants().get_max_depth();
+		let target_depth: Depth = Depth::try_from(depth as usize + levels_to_contain(self.get_root_voxel(), point))
+			.ok()
+			.filter(|&target_depth| target_depth <= max_depth)
+			.ok_or(StorageError::OverMaxDepth(max_depth))?;
+		self.grow_to_contain(point, &mut fill_data)?;
+		let root_voxel: VolumetricCube = *self.get_root_voxel();
+		insert_towards_point(self.octants_mut(), &root_voxel, point, target_depth, data, &mut fill_data)
+	}
+
+	/// Drop outer levels of tree while root has only single child, which becomes new root.
+	/// Data of dropped roots is discarded.
+	///
+	/// ## Returns
+	/// Number of dropped levels.
+	pub fn shrink_to_fit(&mut self) -> StorageResult<Depth> {
+		let mut dropped_levels: Depth = 0;
+		loop {
+			let root_id: Storage::OctantId = self.get_root_id();
+			let children: Vec<(OctantPlacement, Storage::OctantId)> = OctantPlacement::OCTANTS_ORDERED.into_iter()
+				.filter_map(|placement| self.octants().get_existing_child(&root_id, placement).ok().map(|child_id| (placement, child_id)))
+				.collect();
+			let [(placement, child_id)] = children[..] else {
+				return Ok(dropped_levels);
+			};
+
+			let branch: Vec<(usize, OctantPlacement, Storage::Data)> = BranchLayout::of(self.octants(), child_id)?.detach(self.octants_mut());
+			attach_branch(self.octants_mut(), None, branch)?;
+			let root_voxel: VolumetricCube = self.get_root_voxel().make_sub_voxel(placement);
+			self.set_root_voxel(root_voxel);
+			dropped_levels += 1;
+		}
+	}
+}
+
+/// Number of times root voxel has to be doubled towards point until it contains point.
+fn levels_to_contain(root_voxel: &VolumetricCube, point: Vec3A) -> usize {
+	assert!(point.is_finite(), "Root can't grow to contain point which is not finite.");
+	let mut voxel: VolumetricCube = *root_voxel;
+	let mut levels: usize = 0;
+	while !voxel.contains_point(point) {
+		voxel.expand_in_direction(point.into());
+		levels += 1;
+	}
+	levels
+}
+
+/// Insert data into octant at depth on path from root towards point, missing octants on the way are created by `fill_data`.
+/// Point is not checked against root voxel, points outside of it lead to closest octant on the border.
+pub(crate) fn insert_towards_point<Storage, F>(
+	storage: &mut Storage,
+	root_voxel: &VolumetricCube,
+	point: Vec3A,
+	depth: Depth,
+	data: Storage::Data,
+	fill_data: &mut F
+) -> StorageResult<Storage::OctantId>
+where
+	Storage: ModifiableOctantStorage,
+	F: FnMut(&VolumetricCube) -> Storage::Data
+{
+	if depth > storage.get_max_depth() {
+		return Err(StorageError::OverMaxDepth(storage.get_max_depth()));
+	}
+	if depth == 0 {
+		storage.insert_root(data);
+		return Ok(storage.get_root_id());
+	}
+
+	let mut octant_id: Storage::OctantId = storage.get_root_id();
+	let mut voxel: VolumetricCube = *root_voxel;
+	for _ in 1..depth {
+		let placement: OctantPlacement = voxel.guess_octant(point);
+		voxel = voxel.make_sub_voxel(placement);
+		octant_id = match storage.get_existing_child(&octant_id, placement) {
+			Ok(child_id) => child_id,
+			Err(_) => storage.insert_octant(&octant_id, placement, fill_data(&voxel))?.0
+		};
+	}
+	let (inserted_id, _) = storage.insert_octant(&octant_id, voxel.guess_octant(point), data)?;
+	Ok(inserted_id)
+}
+
+/// Ids of branch in breadth first order, each with index of its parent and placement relative to it.
+/// First octant is top of the branch and its parent index and placement are not used.
+struct BranchLayout<OctantId> {
+	octants: Vec<(usize, OctantPlacement, OctantId)>,
+	/// Absolute depth of deepest octant.
+	depth: Depth
+}
+
+impl<OctantId: Copy> BranchLayout<OctantId> {
+	fn of<Storage>(storage: &Storage, top_id: OctantId) -> StorageResult<Self>
+	where Storage: ModifiableOctantStorage<OctantId = OctantId> {
+		let top_depth: Depth = storage.get_octant_depth(&top_id).ok_or(StorageError::InvalidOctantId)?;
+		let mut octants: Vec<(usize, OctantPlacement, OctantId)> = vec![(0, OctantPlacement::LOWER_BOTTOM_LEFT, top_id)];
+		let mut depths: Vec<Depth> = vec![top_depth];
+		let mut octant_index: usize = 0;
+		while octant_index < octants.len() {
+			let octant_id: OctantId = octants[octant_index].2;
+			for placement in OctantPlacement::OCTANTS_ORDERED {
+				if let Ok(child_id) = storage.get_existing_child(&octant_id, placement) {
+					octants.push((octant_index, placement, child_id));
+					depths.push(depths[octant_index] + 1);
+				}
+			}
+			octant_index += 1;
+		}
+		Ok(
+			BranchLayout{
+				octants,
+				depth: depths.last().copied().unwrap_or(top_depth)
+			}
+		)
+	}
+
+	/// Remove branch from storage and take its data, children are removed before their parents,
+	/// so every removal collects only data of single octant.
+	fn detach<Storage>(self, storage: &mut Storage) -> Vec<(usize, OctantPlacement, Storage::Data)>
+	where Storage: ModifiableOctantStorage<OctantId = OctantId> {
+		let mut branch: Vec<(usize, OctantPlacement, Storage::Data)> = Vec::with_capacity(self.octants.len());
+		for (parent_index, placement, octant_id) in self.octants.into_iter().rev() {
+			let (_, data) = storage.remove_octant_and_collect(&octant_id)
+				.and_then(|mut removed| removed.pop())
+				.expect("Octant of branch layout exists in storage.");
+			branch.push((parent_index, placement, data));
+		}
+		branch.reverse();
+		branch
+	}
+}
+
+/// Insert detached branch under parent at placement, or as root when `attach_to` is `None`.
+fn attach_branch<Storage: ModifiableOctantStorage>(
+	storage: &mut Storage,
+	attach_to: Option<(Storage::OctantId, OctantPlacement)>,
+	branch: Vec<(usize, OctantPlacement, Storage::Data)>
+) -> StorageResult<()> {
+	let mut octant_ids: Vec<Storage::OctantId> = Vec::with_capacity(branch.len());
+	for (octant_index, (parent_index, placement, data)) in branch.into_iter().enumerate() {
+		let octant_id: Storage::OctantId = match (octant_index, attach_to) {
+			(0, Some((parent_id, top_placement))) => storage.insert_octant(&parent_id, top_placement, data)?.0,
+			(0, None) => {
+				storage.insert_root(data);
+				storage.get_root_id()
+			},
+			_ => storage.insert_octant(&octant_ids[parent_index], placement, data)?.0
+		};
+		octant_ids.push(octant_id);
+	}
+	Ok(())
+}
@@ -0,0 +1,192 @@
+//! Signed distance functions and procedural generation of spatial octrees from them.
+//!
+//! Distance is negative inside of shape, positive outside and zero on its surface.
+//! Primitives and combinators return closures, so they can be freely nested:
+//! ```
+//! use glam::Vec3A;
+//! use modsvo::sdf;
+//!
+//! let shape = sdf::subtract(
+//!     sdf::sphere(Vec3A::ZERO, 1.0),
+//!     sdf::cuboid(Vec3A::new(1.0, 0.0, 0.0), Vec3A::splat(0.5))
+//! );
+//! assert!(shape(Vec3A::new(-0.5, 0.0, 0.0)) < 0.0);
+//! assert!(shape(Vec3A::new(0.75, 0.0, 0.0)) > 0.0);
+//! ```
+use glam::Vec3A;
+
+use super::octant_meta::OctantPlacement;
+use super::octant_storage_trait::{ModifiableOctantStorage, StorageError, StorageResult};
+use super::octree_base::SubdivisionControlFlow;
+use super::spatial_octree_base::{self, SpatialOctreeBase};
+use super::voxel_trait::Voxel;
+use super::voxels::voxel_cube::VolumetricCube;
+use super::Depth;
+
+
+/// Where octant is located in relation to surface of signed distance function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SdfRegion {
+	/// Whole octant is inside of shape.
+	Inside,
+	/// Whole octant is outside of shape.
+	Outside,
+	/// Surface might pass through octant.
+	Surface
+}
+
+impl SdfRegion {
+	/// Classify voxel by distance at its center, using Lipschitz bound of 1 (distance can't change faster than position).
+	pub fn classify(voxel: &VolumetricCube, distance_at_center: f32) -> Self {
+		let bounding_radius: f32 = voxel.half_extent() * SQRT_3;
+		if distance_at_center > bounding_radius {
+			SdfRegion::Outside
+		}
+		else if distance_at_center < -bounding_radius {
+			SdfRegion::Inside
+		}
+		else {
+			SdfRegion::Surface
+		}
+	}
+}
+
+const SQRT_3: f32 = 1.732_050_8;
+
+impl<Storage: ModifiableOctantStorage> SpatialOctreeBase<Storage, VolumetricCube> {
+	/// Build tree from signed distance function, only octants through which surface might pass are subdivided.
+	///
+	/// ## Arguments
+	/// * `sdf` - Signed distance function, expected to be 1-Lipschitz (exact or underestimating distance).
+	/// * `max_depth` - Maximum depth of subdivision relative to root.
+	/// * `tolerance` - Octants whose bounding radius is not larger than tolerance are not subdivided further.
+	/// * `region_data` - Creates data of octant based on its region, called for every created octant.
+	///
+	/// ## Errors
+	/// Returns `StorageError::OverMaxDepth` when `max_depth` exceeds depth supported by storage.
+	///
+	/// ## Examples
+	/// ```
+	/// use glam::Vec3A;
+	/// use modsvo::{octant_storage_trait::OctantStorage, sdf::{self, SdfRegion}, voxels::voxel_cube::VolumetricCube, SpatialSparseOctree};
+	///
+	/// let octree = SpatialSparseOctree::<u8>::generate_from_sdf(
+	///     VolumetricCube::new(Vec3A::ZERO, 1.0),
+	///     sdf::sphere(Vec3A::ZERO, 0.5),
+	///     4,
+	///     0.0,
+	///     |region, _| region as u8
+	/// ).unwrap();
+	/// assert_eq!(octree.octants().get_octant(&octree.get_root_id()), Some(&(SdfRegion::Surface as u8)));
+	/// ```
+	pub fn generate_from_sdf<S, R>(root_voxel: VolumetricCube, sdf: S, max_depth: Depth, tolerance: f32, mut region_data: R) -> StorageResult<Self>
+	where
+		Storage: Default,
+		S: Fn(Vec3A) -> f32,
+		R: FnMut(SdfRegion, &VolumetricCube) -> Storage::Data
+	{
+		let root_region: SdfRegion = SdfRegion::classify(&root_voxel, sdf(root_voxel.center()));
+		let root_data: Storage::Data = region_data(root_region, &root_voxel);
+		let mut octree: Self = Self::new_with_root(root_voxel, root_data);
+		let root_id: Storage::OctantId = octree.get_root_id();
+		generate_from_sdf_from_storage(octree.octants_mut(), &root_id, &root_voxel, sdf, max_depth, tolerance, region_data)?;
+		Ok(octree)
+	}
+}
+
+/// Subdivide octant and its descendants along surface of signed distance function, see `SpatialOctreeBase::generate_from_sdf`.
+/// `max_depth` is relative to depth of `start_from_id`, data of `start_from_id` is kept.
+pub fn generate_from_sdf_from_storage<Storage, S, R>(
+	storage: &mut Storage,
+	start_from_id: &Storage::OctantId,
+	octant_voxel: &VolumetricCube,
+	sdf: S,
+	max_depth: Depth,
+	tolerance: f32,
+	mut region_data: R
+) -> StorageResult<()>
+where
+	Storage: ModifiableOctantStorage,
+	S: Fn(Vec3A) -> f32,
+	R: FnMut(SdfRegion, &VolumetricCube) -> Storage::Data
+{
+	let start_from_depth: Depth = storage.get_octant_depth(start_from_id).ok_or(StorageError::InvalidOctantId)?;
+	let last_depth: Depth = start_from_depth.checked_add(max_depth).ok_or(StorageError::OverMaxDepth(storage.get_max_depth()))?;
+	if last_depth > storage.get_max_depth() {
+		return Err(StorageError::OverMaxDepth(storage.get_max_depth()));
+	}
+
+	spatial_octree_base::subdivide_if_from_storage(
+		storage,
+		start_from_id,
+		octant_voxel,
+		|depth, _, voxel, _|{
+			let is_too_small: bool = voxel.half_extent() * SQRT_3 <= tolerance;
+			if depth >= last_depth || is_too_small || SdfRegion::classify(voxel, sdf(voxel.center())) != SdfRegion::Surface {
+				return SubdivisionControlFlow::Skip;
+			}
+
+			let mut children_data = OctantPlacement::OCTANTS_ORDERED.map(
+				|child_placement|{
+					let child_voxel: VolumetricCube = voxel.make_sub_voxel(child_placement);
+					let child_region: SdfRegion = SdfRegion::classify(&child_voxel, sdf(child_voxel.center()));
+					Some(region_data(child_region, &child_voxel))
+				}
+			);
+			SubdivisionControlFlow::Continue(
+				move |child_placement: OctantPlacement| children_data[child_placement as usize].take().expect("Every placement is subdivided once.")
+			)
+		}
+	)?;
+	Ok(())
+}
+
+/// Sphere with given center and radius.
+pub fn sphere(center: Vec3A, radius: f32) -> impl Fn(Vec3A) -> f32 + Clone {
+	move |point| (point - center).length() - radius
+}
+
+/// Axis aligned box with given center and half size along each axis.
+pub fn cuboid(center: Vec3A, half_size: Vec3A) -> impl Fn(Vec3A) -> f32 + Clone {
+	move |point|{
+		let distance_to_faces: Vec3A = (point - center).abs() - half_size;
+		distance_to_faces.max(Vec3A::ZERO).length() + distance_to_faces.max_element().min(0.0)
+	}
+}
+
+/// Infinite plane going through point, `normal` points to outside half-space.
+pub fn plane(point_on_plane: Vec3A, normal: Vec3A) -> impl Fn(Vec3A) -> f32 + Clone {
+	let normal: Vec3A = normal.normalize();
+	move |point| (point - point_on_plane).dot(normal)
+}
+
+/// Shape containing both shapes.
+pub fn union<A, B>(a: A, b: B) -> impl Fn(Vec3A) -> f32 + Clone
+where A: Fn(Vec3A) -> f32 + Clone, B: Fn(Vec3A) -> f32 + Clone {
+	move |point| a(point).min(b(point))
+}
+
+/// Shape `a` with shape `b` cut out of it.
+pub fn subtract<A, B>(a: A, b: B) -> impl Fn(Vec3A) -> f32 + Clone
+where A: Fn(Vec3A) -> f32 + Clone, B: Fn(Vec3A) -> f32 + Clone {
+	move |point| a(point).max(-b(point))
+}
+
+/// Shape shared by both shapes.
+pub fn intersect<A, B>(a: A, b: B) -> impl Fn(Vec3A) -> f32 + Clone
+where A: Fn(Vec3A) -> f32 + Clone, B: Fn(Vec3A) -> f32 + Clone {
+	move |point| a(point).max(b(point))
+}
+
+/// Union with blended seam, `smoothness` is distance over which shapes are blended(0 equals to `union`).
+pub fn smooth_union<A, B>(a: A, b: B, smoothness: f32) -> impl Fn(Vec3A) -> f32 + Clone
+where A: Fn(Vec3A) -> f32 + Clone, B: Fn(Vec3A) -> f32 + Clone {
+	move |point|{
+		let (distance_a, distance_b) = (a(point), b(point));
+		if smoothness <= 0.0 {
+			return distance_a.min(distance_b);
+		}
+		let blend: f32 = (0.5 + 0.5 * (distance_b - distance_a) / smoothness).clamp(0.0, 1.0);
+		distance_b + (distance_a - distance_b) * blend - smoothness * blend * (1.0 - blend)
+	}
+}
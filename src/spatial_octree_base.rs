@@ -34,6 +34,11 @@ impl<Storage: OctantStorage, Volumetric: Voxel>  SpatialOctreeBase<Storage, Volu
 	pub fn get_root_voxel(&self) -> &Volumetric {
 		&self.root_voxel
 	}
+
+	/// Replace root voxel, used when tree is re-rooted and root covers different volume.
+	pub(crate) fn set_root_voxel(&mut self, root_voxel: Volumetric) {
+		self.root_voxel = root_voxel;
+	}
 	pub fn get_voxel_by_id(&self, octant_id: &Storage::OctantId) -> Option<Volumetric> {
 		let root_voxel: &Volumetric = self.get_root_voxel();
 		Self::get_voxel_by_id_from_storage(self.octants(), octant_id, root_voxel)
@@ -360,6 +365,20 @@ pub fn compute_voxel_by_id<Storage: OctantStorage, Volumetric: Voxel>(
 }
 
 
+/// Existing children of octant together with their voxels, empty for leaves and missing octants.
+pub fn existing_children_with_voxel_from_storage<Storage: OctantStorage, Volumetric: Voxel>(
+	storage: &Storage,
+	octant_id: &Storage::OctantId,
+	voxel: &Volumetric
+) -> Vec<(Storage::OctantId, Volumetric)> {
+	storage.get_existing_children_with_placement(octant_id)
+		.unwrap_or_default()
+		.into_iter()
+		.map(|(child_id, child_placement)| (child_id, voxel.make_sub_voxel(child_placement)))
+		.collect()
+}
+
+
 fn compute_voxel_by_id_recursive<Storage: OctantStorage, Volumetric: Voxel>(
 	storage_accessor: &Storage,
 	child_id: &Storage::OctantId,
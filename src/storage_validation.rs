@@ -0,0 +1,191 @@
+//! Structural checks for `OctantStorage` implementations.
+//!
+//! Tree is walked from root using only `get_existing_child`, every reached octant is then checked
+//! against answers of other trait functions, so inconsistent custom storages are caught early.
+use std::collections::HashSet;
+use std::hash::Hash;
+
+use super::octant_meta::OctantPlacement;
+use super::octant_storage_trait::OctantStorage;
+use super::octree_base::OctreeBase;
+use super::Depth;
+
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StorageViolation<OctantId> {
+	/// Root id has no data.
+	MissingRoot,
+	/// `get_existing_child` returned id which has no data.
+	DanglingChild{
+		parent_id: OctantId,
+		placement: OctantPlacement
+	},
+	/// `get_octant_depth` disagrees with depth at which octant was reached from root.
+	DepthMismatch{
+		octant_id: OctantId,
+		expected: Depth,
+		found: Option<Depth>
+	},
+	/// Octant was reached below maximum depth of storage.
+	OverMaxDepth{
+		octant_id: OctantId,
+		depth: Depth
+	},
+	/// `get_parent` disagrees with parent from which octant was reached, root has to have no parent.
+	ParentMismatch{
+		octant_id: OctantId,
+		expected: Option<OctantId>,
+		found: Option<OctantId>
+	},
+	/// `which_child_of` disagrees with placement used in `get_existing_child`.
+	PlacementMismatch{
+		octant_id: OctantId,
+		expected: OctantPlacement,
+		found: Option<OctantPlacement>
+	},
+	/// `get_existing_children` is not ordered by `OctantPlacement::OCTANTS_ORDERED` or disagrees with `get_existing_child`.
+	ChildrenMismatch{
+		parent_id: OctantId,
+		placement: OctantPlacement
+	},
+	/// `get_ancestors_for` doesn't list path from parent to root.
+	AncestorsMismatch{
+		octant_id: OctantId
+	},
+	/// Octant has data, but can't be reached from root.
+	Orphan{
+		octant_id: OctantId
+	}
+}
+
+impl<Storage: OctantStorage> OctreeBase<Storage> {
+	/// Check consistency of storage, see `validate`.
+	pub fn validate(&self) -> Vec<StorageViolation<Storage::OctantId>> {
+		validate(&self.octants)
+	}
+}
+
+/// Check that octants reachable from root are consistently reported by all functions of `OctantStorage`.
+///
+/// ## Returns
+/// All found violations, empty when storage is consistent.
+pub fn validate<Storage: OctantStorage>(storage: &Storage) -> Vec<StorageViolation<Storage::OctantId>> {
+	let mut violations: Vec<StorageViolation<Storage::OctantId>> = Vec::new();
+	validate_reachable(storage, &mut violations, |_| {});
+	violations
+}
+
+/// Same as `validate`, additionally reports octants from `octant_ids` which are not reachable from root.
+///
+/// ## Arguments
+/// * `octant_ids` - Ids of all octants held by storage, usually taken from storage's own iteration.
+pub fn validate_with_octant_ids<Storage, I>(storage: &Storage, octant_ids: I) -> Vec<StorageViolation<Storage::OctantId>>
+where
+	Storage: OctantStorage,
+	Storage::OctantId: Eq + Hash,
+	I: IntoIterator<Item = Storage::OctantId>
+{
+	let mut violations: Vec<StorageViolation<Storage::OctantId>> = Vec::new();
+	let mut reachable: HashSet<Storage::OctantId> = HashSet::new();
+	validate_reachable(storage, &mut violations, |octant_id| { reachable.insert(octant_id); });
+	violations.extend(
+		octant_ids.into_iter()
+			.filter(|octant_id| !reachable.contains(octant_id))
+			.map(|octant_id| StorageViolation::Orphan{octant_id})
+	);
+	violations
+}
+
+fn validate_reachable<Storage, F>(storage: &Storage, violations: &mut Vec<StorageViolation<Storage::OctantId>>, mut on_reached: F)
+where
+	Storage: OctantStorage,
+	F: FnMut(Storage::OctantId)
+{
+	let root_id: Storage::OctantId = storage.get_root_id();
+	if storage.get_octant(&root_id).is_none() {
+		violations.push(StorageViolation::MissingRoot);
+		return;
+	}
+	let mut validator = Validator{
+		storage,
+		violations,
+		on_reached: &mut on_reached,
+		path: Vec::new()
+	};
+	validator.validate_octant(root_id, None);
+}
+
+struct Validator<'a, Storage: OctantStorage, F> {
+	storage: &'a Storage,
+	violations: &'a mut Vec<StorageViolation<Storage::OctantId>>,
+	on_reached: &'a mut F,
+	/// Ids from root to currently validated octant, exclusive.
+	path: Vec<Storage::OctantId>
+}
+
+impl<Storage, F> Validator<'_, Storage, F>
+where
+	Storage: OctantStorage,
+	F: FnMut(Storage::OctantId)
+{
+	fn validate_octant(&mut self, octant_id: Storage::OctantId, reached_by: Option<(Storage::OctantId, OctantPlacement)>) {
+		(self.on_reached)(octant_id);
+		let depth: Depth = self.path.len() as Depth;
+		let found_depth: Option<Depth> = self.storage.get_octant_depth(&octant_id);
+		if found_depth != Some(depth) {
+			self.violations.push(StorageViolation::DepthMismatch{octant_id, expected: depth, found: found_depth});
+		}
+
+		let expected_parent: Option<Storage::OctantId> = reached_by.map(|(parent_id, _)| parent_id);
+		let found_parent: Option<Storage::OctantId> = self.storage.get_parent(&octant_id);
+		if found_parent != expected_parent {
+			self.violations.push(StorageViolation::ParentMismatch{octant_id, expected: expected_parent, found: found_parent});
+		}
+
+		if let Some((parent_id, placement)) = reached_by {
+			let found_placement: Option<OctantPlacement> = self.storage.which_child_of(&parent_id, &octant_id).ok();
+			if found_placement != Some(placement) {
+				self.violations.push(StorageViolation::PlacementMismatch{octant_id, expected: placement, found: found_placement});
+			}
+		}
+
+		let ancestors_match: bool = self.storage.get_ancestors_for(&octant_id)
+			.is_some_and(|ancestors| ancestors.eq(self.path.iter().rev().copied()));
+		if !ancestors_match {
+			self.violations.push(StorageViolation::AncestorsMismatch{octant_id});
+		}
+
+		if depth > self.storage.get_max_depth() {
+			self.violations.push(StorageViolation::OverMaxDepth{octant_id, depth});
+			return;
+		}
+		self.validate_children(octant_id);
+	}
+
+	fn validate_children(&mut self, parent_id: Storage::OctantId) {
+		let existing_children: Option<[Option<Storage::OctantId>; OctantPlacement::OCTANTS_COUNT]> = self.storage.get_existing_children(&parent_id).ok();
+		let mut children: Vec<(OctantPlacement, Storage::OctantId)> = Vec::new();
+		for placement in OctantPlacement::OCTANTS_ORDERED {
+			let child_id: Option<Storage::OctantId> = self.storage.get_existing_child(&parent_id, placement).ok();
+			let listed_child_id: Option<Storage::OctantId> = existing_children.and_then(|existing_children| existing_children[placement as usize]);
+			if child_id != listed_child_id {
+				self.violations.push(StorageViolation::ChildrenMismatch{parent_id, placement});
+			}
+
+			let Some(child_id) = child_id else {
+				continue;
+			};
+			if self.storage.get_octant(&child_id).is_none() {
+				self.violations.push(StorageViolation::DanglingChild{parent_id, placement});
+				continue;
+			}
+			children.push((placement, child_id));
+		}
+
+		self.path.push(parent_id);
+		for (placement, child_id) in children {
+			self.validate_octant(child_id, Some((parent_id, placement)));
+		}
+		self.path.pop();
+	}
+}
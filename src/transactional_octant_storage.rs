@@ -0,0 +1,569 @@
+use std::collections::VecDeque;
+use std::mem::size_of;
+
+use super::octant_meta::OctantPlacement;
+use super::octant_storage_trait::{ModifiableOctantStorage, OctantStorage, StorageError, StorageResult};
+use super::Depth;
+
+/// Single reversible modification of octant storage.
+/// Applying operation to storage produces another operation which reverts it.
+#[derive(Debug, Clone)]
+pub enum EditOperation<OctantId, Data> {
+	/// Set or replace root data.
+	InsertRoot(Data),
+	/// Create or replace child of parent at placement.
+	InsertOctant(OctantId, OctantPlacement, Data),
+	/// Replace data of already existing octant.
+	SetData(OctantId, Data),
+	/// Remove octant together with its whole branch.
+	RemoveOctant(OctantId),
+	/// Re-create previously removed branch.
+	RestoreBranch(RemovedBranch<OctantId, Data>),
+}
+
+/// Branch removed from storage, kept in form which allows it to be inserted back.
+#[derive(Debug, Clone)]
+pub struct RemovedBranch<OctantId, Data> {
+	/// Parent and placement of removed branch, `None` when branch started at root.
+	attach_to: Option<(OctantId, OctantPlacement)>,
+	/// Removed octants in pre-order, each with index of its parent inside this vector and placement relative to it.
+	/// First octant is top of the branch and its parent index and placement are not used.
+	octants: Vec<(usize, OctantPlacement, Data)>,
+}
+
+impl<OctantId, Data> RemovedBranch<OctantId, Data> {
+	pub fn len(&self) -> usize {
+		self.octants.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.octants.is_empty()
+	}
+}
+
+impl<OctantId, Data> EditOperation<OctantId, Data> {
+	/// Rough estimation of heap and inline bytes held by operation.
+	pub fn estimated_bytes(&self) -> usize {
+		let heap_bytes = match self {
+			Self::RestoreBranch(removed_branch) => removed_branch.octants.capacity() * size_of::<(usize, OctantPlacement, Data)>(),
+			_ => 0
+		};
+		size_of::<Self>() + heap_bytes
+	}
+}
+
+/// Named group of operations which are undone and redone together.
+#[derive(Debug, Clone)]
+pub struct EditTransaction<OctantId, Data> {
+	name: String,
+	operations: Vec<EditOperation<OctantId, Data>>,
+	estimated_bytes: usize
+}
+
+impl<OctantId, Data> EditTransaction<OctantId, Data> {
+	fn new(name: String) -> Self {
+		EditTransaction{
+			name,
+			operations: Vec::new(),
+			estimated_bytes: 0
+		}
+	}
+
+	fn push(&mut self, operation: EditOperation<OctantId, Data>) {
+		self.estimated_bytes += operation.estimated_bytes();
+		self.operations.push(operation);
+	}
+
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	pub fn operations(&self) -> &[EditOperation<OctantId, Data>] {
+		&self.operations
+	}
+
+	pub fn len(&self) -> usize {
+		self.operations.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.operations.is_empty()
+	}
+
+	pub fn estimated_bytes(&self) -> usize {
+		size_of::<Self>() + self.name.capacity() + self.estimated_bytes
+	}
+}
+
+/// Storage wrapper which records inverse of every modification, so changes can be undone and redone.
+///
+/// Modifications are grouped into named transactions using `begin_transaction()` and `commit_transaction()`,
+/// modifications done outside of transaction are committed as their own single transaction.
+/// Data handed out by `get_octant_mut()` is recorded before it can be changed, which is why `Data: Clone` is required.
+///
+/// ## Examples
+/// ```
+/// use modsvo::morton_based_storage::hashed_octant_storage::HashedOctantStorage;
+/// use modsvo::octant_storage_trait::{ModifiableOctantStorage, OctantStorage};
+/// use modsvo::octree_base::OctreeBase;
+/// use modsvo::transactional_octant_storage::TransactionalOctantStorage;
+///
+/// let mut octree: OctreeBase<TransactionalOctantStorage<HashedOctantStorage<u32>>> = OctreeBase::default();
+/// let root_id = octree.octants.get_root_id();
+/// octree.octants.begin_transaction("carve tunnel");
+/// octree.octants.subdivide(&root_id, |_| 0).unwrap();
+/// octree.octants.commit_transaction();
+/// assert_eq!(octree.octants.undo_name(), Some("carve tunnel"));
+///
+/// assert!(octree.octants.undo().unwrap());
+/// assert_eq!(octree.octants.get_octant(&root_id.children_ids()[0]), None);
+/// assert_eq!(octree.octants.redo_name(), Some("carve tunnel"));
+/// ```
+pub struct TransactionalOctantStorage<Storage: OctantStorage> {
+	storage: Storage,
+	open_transaction: Option<EditTransaction<Storage::OctantId, Storage::Data>>,
+	undo_stack: VecDeque<EditTransaction<Storage::OctantId, Storage::Data>>,
+	redo_stack: Vec<EditTransaction<Storage::OctantId, Storage::Data>>,
+	memory_budget: usize,
+	memory_usage: usize
+}
+
+impl<Storage: ModifiableOctantStorage> TransactionalOctantStorage<Storage>
+where Storage::Data: Clone {
+	pub fn new(storage: Storage) -> Self {
+		TransactionalOctantStorage{
+			storage,
+			open_transaction: None,
+			undo_stack: VecDeque::new(),
+			redo_stack: Vec::new(),
+			memory_budget: usize::MAX,
+			memory_usage: 0
+		}
+	}
+
+	/// Limit estimated bytes held by undo and redo history, oldest transactions are forgotten first.
+	pub fn with_memory_budget(mut self, memory_budget: usize) -> Self {
+		self.set_memory_budget(memory_budget);
+		self
+	}
+
+	pub fn set_memory_budget(&mut self, memory_budget: usize) {
+		self.memory_budget = memory_budget;
+		self.enforce_memory_budget();
+	}
+
+	pub fn memory_budget(&self) -> usize {
+		self.memory_budget
+	}
+
+	/// Estimated bytes held by committed undo and redo history.
+	pub fn memory_usage(&self) -> usize {
+		self.memory_usage
+	}
+
+	pub fn storage(&self) -> &Storage {
+		&self.storage
+	}
+
+	/// Returns wrapped storage and drops recorded history.
+	pub fn into_inner(self) -> Storage {
+		self.storage
+	}
+
+	/// Start recording modifications into named transaction, already open transaction is committed first.
+	pub fn begin_transaction(&mut self, name: impl Into<String>) {
+		self.commit_transaction();
+		self.open_transaction = Some(EditTransaction::new(name.into()));
+	}
+
+	/// Finish open transaction and make it available to `undo()`.
+	///
+	/// ## Returns
+	///  `true` when transaction was open and contained at least one modification, otherwise `false`.
+	pub fn commit_transaction(&mut self) -> bool {
+		let Some(transaction) = self.open_transaction.take() else {
+			return false;
+		};
+		if transaction.is_empty() {
+			return false;
+		}
+
+		self.clear_redo();
+		self.memory_usage += transaction.estimated_bytes();
+		self.undo_stack.push_back(transaction);
+		self.enforce_memory_budget();
+		true
+	}
+
+	/// Revert all modifications done in open transaction and discard it.
+	pub fn abort_transaction(&mut self) -> StorageResult<()> {
+		let Some(transaction) = self.open_transaction.take() else {
+			return Ok(());
+		};
+		for operation in transaction.operations.into_iter().rev() {
+			apply_edit_operation(&mut self.storage, operation)?;
+		}
+		Ok(())
+	}
+
+	pub fn is_transaction_open(&self) -> bool {
+		self.open_transaction.is_some()
+	}
+
+	pub fn can_undo(&self) -> bool {
+		!self.undo_stack.is_empty() || self.open_transaction.as_ref().is_some_and(|transaction| !transaction.is_empty())
+	}
+
+	pub fn can_redo(&self) -> bool {
+		!self.redo_stack.is_empty()
+	}
+
+	/// Name of transaction which will be reverted by next `undo()`.
+	pub fn undo_name(&self) -> Option<&str> {
+		match &self.open_transaction {
+			Some(transaction) if !transaction.is_empty() => Some(transaction.name()),
+			_ => self.undo_stack.back().map(EditTransaction::name)
+		}
+	}
+
+	/// Name of transaction which will be re-applied by next `redo()`.
+	pub fn redo_name(&self) -> Option<&str> {
+		self.redo_stack.last().map(EditTransaction::name)
+	}
+
+	/// Revert last committed transaction, open transaction is committed before undoing.
+	///
+	/// ## Returns
+	///  `StorageResult` with `true` when transaction was reverted or `false` when there was nothing to undo.
+	/// ## Errors
+	///   * any `StorageError` produced by storage while reverting, reverted transaction is lost in such case
+	pub fn undo(&mut self) -> StorageResult<bool> {
+		self.commit_transaction();
+		let Some(transaction) = self.undo_stack.pop_back() else {
+			return Ok(false);
+		};
+		self.memory_usage -= transaction.estimated_bytes();
+
+		let inverse_transaction = apply_edit_transaction(&mut self.storage, transaction)?;
+		self.memory_usage += inverse_transaction.estimated_bytes();
+		self.redo_stack.push(inverse_transaction);
+		self.enforce_memory_budget();
+		Ok(true)
+	}
+
+	/// Re-apply last undone transaction.
+	///
+	/// ## Returns
+	///  `StorageResult` with `true` when transaction was re-applied or `false` when there was nothing to redo.
+	/// ## Errors
+	///   * any `StorageError` produced by storage while re-applying, transaction is lost in such case
+	pub fn redo(&mut self) -> StorageResult<bool> {
+		self.commit_transaction();
+		let Some(transaction) = self.redo_stack.pop() else {
+			return Ok(false);
+		};
+		self.memory_usage -= transaction.estimated_bytes();
+
+		let inverse_transaction = apply_edit_transaction(&mut self.storage, transaction)?;
+		self.memory_usage += inverse_transaction.estimated_bytes();
+		self.undo_stack.push_back(inverse_transaction);
+		self.enforce_memory_budget();
+		Ok(true)
+	}
+
+	/// Forget all recorded history including open transaction, storage is left unchanged.
+	pub fn clear_history(&mut self) {
+		self.open_transaction = None;
+		self.undo_stack.clear();
+		self.redo_stack.clear();
+		self.memory_usage = 0;
+	}
+
+	fn clear_redo(&mut self) {
+		for transaction in self.redo_stack.drain(..) {
+			self.memory_usage -= transaction.estimated_bytes();
+		}
+	}
+
+	fn enforce_memory_budget(&mut self) {
+		while self.memory_usage > self.memory_budget {
+			if let Some(transaction) = self.undo_stack.pop_front() {
+				self.memory_usage -= transaction.estimated_bytes();
+			}
+			else if !self.redo_stack.is_empty() {
+				let transaction = self.redo_stack.remove(0);
+				self.memory_usage -= transaction.estimated_bytes();
+			}
+			else {
+				break;
+			}
+		}
+	}
+
+	fn record(&mut self, operation_name: &str, inverse_operation: EditOperation<Storage::OctantId, Storage::Data>) {
+		match &mut self.open_transaction {
+			Some(transaction) => transaction.push(inverse_operation),
+			None => {
+				let mut transaction = EditTransaction::new(operation_name.to_string());
+				transaction.push(inverse_operation);
+				self.open_transaction = Some(transaction);
+				self.commit_transaction();
+			}
+		}
+	}
+}
+
+impl<Storage: ModifiableOctantStorage> OctantStorage for TransactionalOctantStorage<Storage>
+where Storage::Data: Clone {
+	type OctantId = Storage::OctantId;
+	type ParentIdIterator = Storage::ParentIdIterator;
+	type Data = Storage::Data;
+
+	fn get_root_id(&self) -> Self::OctantId {
+		self.storage.get_root_id()
+	}
+
+	fn get_max_depth(&self) -> Depth {
+		self.storage.get_max_depth()
+	}
+
+	fn get_octant_depth(&self, octant_id: &Self::OctantId) -> Option<Depth> {
+		self.storage.get_octant_depth(octant_id)
+	}
+
+	fn get_octant(&self, octant_id: &Self::OctantId) -> Option<&Self::Data> {
+		self.storage.get_octant(octant_id)
+	}
+
+	fn get_octant_mut(&mut self, octant_id: &Self::OctantId) -> Option<&mut Self::Data> {
+		let previous_data: Self::Data = self.storage.get_octant(octant_id)?.clone();
+		self.record("set_data", EditOperation::SetData(*octant_id, previous_data));
+		self.storage.get_octant_mut(octant_id)
+	}
+
+	fn get_existing_child(&self, parent_id: &Self::OctantId, child_placement: OctantPlacement) -> StorageResult<Self::OctantId> {
+		self.storage.get_existing_child(parent_id, child_placement)
+	}
+
+	fn get_parent(&self, octant_id: &Self::OctantId) -> Option<Self::OctantId> {
+		self.storage.get_parent(octant_id)
+	}
+
+	fn get_ancestors_for(&self, octant_id: &Self::OctantId) -> Option<Self::ParentIdIterator> {
+		self.storage.get_ancestors_for(octant_id)
+	}
+
+	fn get_existing_children(&self, parent_id: &Self::OctantId) -> StorageResult<[Option<Self::OctantId>; OctantPlacement::OCTANTS_COUNT]> {
+		self.storage.get_existing_children(parent_id)
+	}
+
+	fn which_child_of(&self, parent_id: &Self::OctantId, child_id: &Self::OctantId) -> StorageResult<OctantPlacement> {
+		self.storage.which_child_of(parent_id, child_id)
+	}
+
+	/// Estimate of wrapped storage together with memory used by undo/redo history.
+	fn estimated_heap_bytes(&self) -> Option<usize> {
+		Some(self.storage.estimated_heap_bytes()? + self.memory_usage)
+	}
+}
+
+impl<Storage: ModifiableOctantStorage> ModifiableOctantStorage for TransactionalOctantStorage<Storage>
+where Storage::Data: Clone {
+	fn insert_root(&mut self, custom_data: Self::Data) -> Option<Self::Data> {
+		let old_data: Option<Self::Data> = self.storage.insert_root(custom_data);
+		let inverse_operation = match &old_data {
+			Some(old_data) => EditOperation::InsertRoot(old_data.clone()),
+			None => EditOperation::RemoveOctant(self.storage.get_root_id())
+		};
+		self.record("insert_root", inverse_operation);
+		old_data
+	}
+
+	fn insert_octant(&mut self, parent_id: &Self::OctantId, child_octant_placement: OctantPlacement, custom_data: Self::Data) -> StorageResult<(Self::OctantId, Option<Self::Data>)> {
+		let (child_id, old_data) = self.storage.insert_octant(parent_id, child_octant_placement, custom_data)?;
+		let inverse_operation = match &old_data {
+			Some(old_data) => EditOperation::InsertOctant(*parent_id, child_octant_placement, old_data.clone()),
+			None => EditOperation::RemoveOctant(child_id)
+		};
+		self.record("insert_octant", inverse_operation);
+		Ok((child_id, old_data))
+	}
+
+	fn remove_octant(&mut self, octant_id: &Self::OctantId) -> Option<()> {
+		let removed_branch = remove_branch(&mut self.storage, octant_id)?;
+		self.record("remove_octant", EditOperation::RestoreBranch(removed_branch));
+		Some(())
+	}
+
+	fn remove_octant_and_fill(&mut self, octant_id: &Self::OctantId, collected_octant_data: &mut Vec<(Self::OctantId, Self::Data)>) -> Option<()> {
+		let octant_ids: Vec<Self::OctantId> = collect_branch_ids(&self.storage, octant_id)?;
+		let removed_branch = remove_branch(&mut self.storage, octant_id)?;
+		collected_octant_data.extend(
+			octant_ids.into_iter()
+				.zip(removed_branch.octants.iter())
+				.map(|(removed_id, (_, _, data))| (removed_id, data.clone()))
+		);
+		self.record("remove_octant", EditOperation::RestoreBranch(removed_branch));
+		Some(())
+	}
+
+	fn subdivide<F>(&mut self, parent_id: &Self::OctantId, mut create_custom_data: F) -> StorageResult<[Self::OctantId; OctantPlacement::OCTANTS_COUNT]>
+	where F: FnMut(OctantPlacement) -> Self::Data {
+		let implicit_transaction = !self.is_transaction_open();
+		if implicit_transaction {
+			self.begin_transaction("subdivide");
+		}
+
+		let mut children_ids: [Option<Self::OctantId>; OctantPlacement::OCTANTS_COUNT] = [None; OctantPlacement::OCTANTS_COUNT];
+		let mut subdivision_result: StorageResult<()> = Ok(());
+		for (child_slot, child_placement) in children_ids.iter_mut().zip(OctantPlacement::OCTANTS_ORDERED) {
+			match self.insert_octant(parent_id, child_placement, create_custom_data(child_placement)) {
+				Ok((child_id, _)) => *child_slot = Some(child_id),
+				Err(error) => {
+					subdivision_result = Err(error);
+					break;
+				}
+			}
+		}
+
+		if implicit_transaction {
+			self.commit_transaction();
+		}
+		subdivision_result?;
+
+		Ok(children_ids.map(|child_id| child_id.expect("All children are created when subdivision succeeds.")))
+	}
+}
+
+impl<Storage: ModifiableOctantStorage + Default> Default for TransactionalOctantStorage<Storage>
+where Storage::Data: Clone {
+	fn default() -> Self {
+		Self::new(Storage::default())
+	}
+}
+
+/// Apply single operation to storage.
+///
+/// ## Returns
+///  `StorageResult` which contains operation reverting the applied one.
+/// ## Errors
+///   * InvalidOctantId - when operation refers to octant missing in storage
+///   * OverMaxDepth - when re-created octant would lie below maximum depth
+pub fn apply_edit_operation<Storage: ModifiableOctantStorage>(
+	storage: &mut Storage,
+	operation: EditOperation<Storage::OctantId, Storage::Data>
+) -> StorageResult<EditOperation<Storage::OctantId, Storage::Data>> {
+	match operation {
+		EditOperation::InsertRoot(data) => {
+			match storage.insert_root(data) {
+				Some(old_data) => Ok(EditOperation::InsertRoot(old_data)),
+				None => Ok(EditOperation::RemoveOctant(storage.get_root_id()))
+			}
+		},
+		EditOperation::InsertOctant(parent_id, child_placement, data) => {
+			let (child_id, old_data) = storage.insert_octant(&parent_id, child_placement, data)?;
+			match old_data {
+				Some(old_data) => Ok(EditOperation::InsertOctant(parent_id, child_placement, old_data)),
+				None => Ok(EditOperation::RemoveOctant(child_id))
+			}
+		},
+		EditOperation::SetData(octant_id, mut data) => {
+			let current_data: &mut Storage::Data = storage.get_octant_mut(&octant_id).ok_or(StorageError::InvalidOctantId)?;
+			std::mem::swap(current_data, &mut data);
+			Ok(EditOperation::SetData(octant_id, data))
+		},
+		EditOperation::RemoveOctant(octant_id) => {
+			let removed_branch = remove_branch(storage, &octant_id).ok_or(StorageError::InvalidOctantId)?;
+			Ok(EditOperation::RestoreBranch(removed_branch))
+		},
+		EditOperation::RestoreBranch(removed_branch) => {
+			let mut restored_ids: Vec<Storage::OctantId> = Vec::with_capacity(removed_branch.octants.len());
+			for (parent_index, child_placement, data) in removed_branch.octants {
+				let restored_id: Storage::OctantId = if let Some(parent_id) = restored_ids.get(parent_index) {
+					storage.insert_octant(parent_id, child_placement, data)?.0
+				}
+				else if let Some((attach_to_id, attach_to_placement)) = removed_branch.attach_to {
+					storage.insert_octant(&attach_to_id, attach_to_placement, data)?.0
+				}
+				else {
+					storage.insert_root(data);
+					storage.get_root_id()
+				};
+				restored_ids.push(restored_id);
+			}
+			let top_id: Storage::OctantId = *restored_ids.first().ok_or(StorageError::InvalidOctantId)?;
+			Ok(EditOperation::RemoveOctant(top_id))
+		}
+	}
+}
+
+fn apply_edit_transaction<Storage: ModifiableOctantStorage>(
+	storage: &mut Storage,
+	transaction: EditTransaction<Storage::OctantId, Storage::Data>
+) -> StorageResult<EditTransaction<Storage::OctantId, Storage::Data>> {
+	let mut inverse_transaction = EditTransaction::new(transaction.name);
+	for operation in transaction.operations.into_iter().rev() {
+		inverse_transaction.push(apply_edit_operation(storage, operation)?);
+	}
+	Ok(inverse_transaction)
+}
+
+/// Collect ids of branch in pre-order.
+fn collect_branch_ids<Storage: OctantStorage>(storage: &Storage, octant_id: &Storage::OctantId) -> Option<Vec<Storage::OctantId>> {
+	Some(collect_branch_layout(storage, octant_id)?.into_iter().map(|(branch_id, _, _)| branch_id).collect())
+}
+
+/// Collect branch in pre-order as `(OctantId, parent index, placement)`.
+fn collect_branch_layout<Storage: OctantStorage>(storage: &Storage, octant_id: &Storage::OctantId) -> Option<Vec<(Storage::OctantId, usize, OctantPlacement)>> {
+	let _ = storage.get_octant(octant_id)?;
+	let mut branch_layout: Vec<(Storage::OctantId, usize, OctantPlacement)> = vec![(*octant_id, 0, OctantPlacement::LOWER_BOTTOM_LEFT)];
+	let mut to_be_visited: Vec<usize> = vec![0];
+	while let Some(parent_index) = to_be_visited.pop() {
+		let parent_id = branch_layout[parent_index].0;
+		let Ok(children) = storage.get_existing_children(&parent_id) else {
+			continue;
+		};
+		// reversed, so children are visited in `OctantPlacement::OCTANTS_ORDERED` order
+		for (maybe_child_id, child_placement) in children.into_iter().zip(OctantPlacement::OCTANTS_ORDERED).rev() {
+			let Some(child_id) = maybe_child_id else {
+				continue;
+			};
+			branch_layout.push((child_id, parent_index, child_placement));
+			to_be_visited.push(branch_layout.len() - 1);
+		}
+	}
+	Some(branch_layout)
+}
+
+/// Remove branch octant by octant from leaves upwards, so data of every octant is paired with its place in branch.
+fn remove_branch<Storage: ModifiableOctantStorage>(storage: &mut Storage, octant_id: &Storage::OctantId) -> Option<RemovedBranch<Storage::OctantId, Storage::Data>> {
+	let branch_layout = collect_branch_layout(storage, octant_id)?;
+	let attach_to: Option<(Storage::OctantId, OctantPlacement)> = storage.get_parent(octant_id)
+		.and_then(
+			|parent_id|{
+				let child_placement = storage.which_child_of(&parent_id, octant_id).ok()?;
+				Some((parent_id, child_placement))
+			}
+		);
+
+	let mut removed_data: Vec<Storage::Data> = Vec::with_capacity(branch_layout.len());
+	let mut collected_octant_data: Vec<(Storage::OctantId, Storage::Data)> = Vec::with_capacity(1);
+	for (removed_id, _, _) in branch_layout.iter().rev() {
+		storage.remove_octant_and_fill(removed_id, &mut collected_octant_data)?;
+		let (_, data) = collected_octant_data.pop()?;
+		collected_octant_data.clear();
+		removed_data.push(data);
+	}
+
+	let octants = branch_layout.into_iter()
+		.zip(removed_data.into_iter().rev())
+		.map(|((_, parent_index, child_placement), data)| (parent_index, child_placement, data))
+		.collect();
+
+	Some(
+		RemovedBranch{
+			attach_to,
+			octants
+		}
+	)
+}
@@ -0,0 +1,218 @@
+//! Textual dump of tree shape and structural diff between two trees, meant for debugging and test assertions.
+//!
+//! Octants are identified by path of `OctantPlacement`s from root, so trees are compared by their shape
+//! and not by how storage assigns ids. `assert_octrees_eq!` panics with diff and dumps of both trees.
+use std::fmt::{self, Debug, Display, Write};
+
+use super::octant_meta::OctantPlacement;
+use super::octant_storage_trait::OctantStorage;
+use super::octree_base::OctreeBase;
+
+
+/// How octant differs between left and right tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OctantChange<'a, Data> {
+	/// Octant exists only in right tree.
+	Added(&'a Data),
+	/// Octant exists only in left tree.
+	Removed(&'a Data),
+	/// Octant exists in both trees with different data.
+	Changed{
+		old: &'a Data,
+		new: &'a Data
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OctantDiff<'a, OctantId, Data> {
+	/// Id from right tree for added octants, otherwise id from left tree.
+	pub octant_id: OctantId,
+	/// Placements leading from root to octant, empty for root.
+	pub path: Vec<OctantPlacement>,
+	pub change: OctantChange<'a, Data>
+}
+
+/// Differences between two trees in depth first order, every octant of added or removed branch is listed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TreeDiff<'a, OctantId, Data> {
+	pub octants: Vec<OctantDiff<'a, OctantId, Data>>
+}
+
+impl<OctantId, Data> TreeDiff<'_, OctantId, Data> {
+	pub fn is_empty(&self) -> bool {
+		self.octants.is_empty()
+	}
+}
+
+impl<OctantId: Debug, Data: Debug> Display for TreeDiff<'_, OctantId, Data> {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for octant_diff in self.octants.iter() {
+			let path: String = format_path(&octant_diff.path);
+			match octant_diff.change {
+				OctantChange::Added(data) => writeln!(formatter, "+ {} {:?}: {:?}", path, octant_diff.octant_id, data)?,
+				OctantChange::Removed(data) => writeln!(formatter, "- {} {:?}: {:?}", path, octant_diff.octant_id, data)?,
+				OctantChange::Changed{old, new} => writeln!(formatter, "~ {} {:?}: {:?} -> {:?}", path, octant_diff.octant_id, old, new)?
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Path of placements joined by `/`, root is `/`.
+pub fn format_path(path: &[OctantPlacement]) -> String {
+	if path.is_empty() {
+		return "/".to_string();
+	}
+	path.iter().fold(String::new(), |mut formatted, placement| {
+		let _ = write!(formatted, "/{:?}", placement);
+		formatted
+	})
+}
+
+impl<Storage: OctantStorage> OctreeBase<Storage> {
+	/// Render tree as indented text with data formatted by `Debug`, see `dump_tree_with`.
+	///
+	/// ## Examples
+	/// ```
+	/// use modsvo::{octant_storage_trait::{ModifiableOctantStorage, OctantStorage}, SparseOctree};
+	///
+	/// let mut octree = SparseOctree::<u8>::new_with_root(0);
+	/// let root_id = octree.octants.get_root_id();
+	/// octree.octants.subdivide(&root_id, |placement| placement as u8).unwrap();
+	/// assert!(octree.dump_tree().contains("└─ UPPER_TOP_RIGHT: 7"));
+	/// ```
+	pub fn dump_tree(&self) -> String
+	where Storage::Data: Debug {
+		self.dump_tree_with(|data| Some(format!("{:?}", data)))
+	}
+
+	/// Render tree as indented text, one octant per line named by its `OctantPlacement`, root is named `ROOT`.
+	///
+	/// ## Arguments
+	/// * `format_data` - Formats data shown after placement, `None` shows only placement.
+	pub fn dump_tree_with<F>(&self, mut format_data: F) -> String
+	where F: FnMut(&Storage::Data) -> Option<String> {
+		let mut dump: String = String::new();
+		let root_id: Storage::OctantId = self.octants.get_root_id();
+		write_octant_line(&mut dump, "", "ROOT", self.octants.get_octant(&root_id).and_then(&mut format_data));
+		dump_children(&self.octants, &root_id, "", &mut format_data, &mut dump);
+		dump
+	}
+
+	/// Compare this tree as left with other tree as right, octants are matched by their placement path.
+	pub fn diff<'a, Right>(&'a self, other: &'a OctreeBase<Right>) -> TreeDiff<'a, Storage::OctantId, Storage::Data>
+	where
+		Right: OctantStorage<OctantId = Storage::OctantId, Data = Storage::Data>,
+		Storage::Data: PartialEq
+	{
+		diff_from_storage(&self.octants, &other.octants)
+	}
+}
+
+/// Structural diff of two storages starting at their roots, see `OctreeBase::diff`.
+pub fn diff_from_storage<'a, Left, Right>(left: &'a Left, right: &'a Right) -> TreeDiff<'a, Left::OctantId, Left::Data>
+where
+	Left: OctantStorage,
+	Right: OctantStorage<OctantId = Left::OctantId, Data = Left::Data>,
+	Left::Data: PartialEq
+{
+	let mut tree_diff: TreeDiff<'a, Left::OctantId, Left::Data> = TreeDiff{
+		octants: Vec::new()
+	};
+	diff_recursive(left, right, Some(left.get_root_id()), Some(right.get_root_id()), &mut Vec::new(), &mut tree_diff);
+	tree_diff
+}
+
+fn write_octant_line(dump: &mut String, prefix: &str, name: &str, formatted_data: Option<String>) {
+	match formatted_data {
+		Some(formatted_data) => {
+			let _ = writeln!(dump, "{}{}: {}", prefix, name, formatted_data);
+		},
+		None => {
+			let _ = writeln!(dump, "{}{}", prefix, name);
+		}
+	}
+}
+
+fn dump_children<Storage, F>(storage: &Storage, parent_id: &Storage::OctantId, indent: &str, format_data: &mut F, dump: &mut String)
+where
+	Storage: OctantStorage,
+	F: FnMut(&Storage::Data) -> Option<String>
+{
+	let children: Vec<(OctantPlacement, Storage::OctantId)> = OctantPlacement::OCTANTS_ORDERED.into_iter()
+		.filter_map(|placement| storage.get_existing_child(parent_id, placement).ok().map(|child_id| (placement, child_id)))
+		.collect();
+	for (child_index, (placement, child_id)) in children.iter().enumerate() {
+		let is_last: bool = child_index + 1 == children.len();
+		let prefix: String = format!("{}{}", indent, if is_last { "└─ " } else { "├─ " });
+		write_octant_line(dump, &prefix, &format!("{:?}", placement), storage.get_octant(child_id).and_then(&mut *format_data));
+		let child_indent: String = format!("{}{}", indent, if is_last { "   " } else { "│  " });
+		dump_children(storage, child_id, &child_indent, format_data, dump);
+	}
+}
+
+fn diff_recursive<'a, Left, Right>(
+	left: &'a Left,
+	right: &'a Right,
+	left_id: Option<Left::OctantId>,
+	right_id: Option<Left::OctantId>,
+	path: &mut Vec<OctantPlacement>,
+	tree_diff: &mut TreeDiff<'a, Left::OctantId, Left::Data>
+)
+where
+	Left: OctantStorage,
+	Right: OctantStorage<OctantId = Left::OctantId, Data = Left::Data>,
+	Left::Data: PartialEq
+{
+	let left_data: Option<&Left::Data> = left_id.as_ref().and_then(|octant_id| left.get_octant(octant_id));
+	let right_data: Option<&Left::Data> = right_id.as_ref().and_then(|octant_id| right.get_octant(octant_id));
+	let change: Option<(Left::OctantId, OctantChange<'a, Left::Data>)> = match (left_data, right_data) {
+		(Some(old), Some(new)) => (old != new).then(|| (left_id.expect("Left octant exists."), OctantChange::Changed{old, new})),
+		(Some(old), None) => Some((left_id.expect("Left octant exists."), OctantChange::Removed(old))),
+		(None, Some(new)) => Some((right_id.expect("Right octant exists."), OctantChange::Added(new))),
+		(None, None) => return
+	};
+	if let Some((octant_id, change)) = change {
+		tree_diff.octants.push(
+			OctantDiff{
+				octant_id,
+				path: path.clone(),
+				change
+			}
+		);
+	}
+
+	for placement in OctantPlacement::OCTANTS_ORDERED {
+		// octants at max depth report error instead of missing child, both mean there is nothing to compare
+		let left_child: Option<Left::OctantId> = left_data.and(left_id.as_ref()).and_then(|octant_id| left.get_existing_child(octant_id, placement).ok());
+		let right_child: Option<Left::OctantId> = right_data.and(right_id.as_ref()).and_then(|octant_id| right.get_existing_child(octant_id, placement).ok());
+		if left_child.is_none() && right_child.is_none() {
+			continue;
+		}
+		path.push(placement);
+		diff_recursive(left, right, left_child, right_child, path, tree_diff);
+		path.pop();
+	}
+}
+
+/// Assert that two `OctreeBase`s have the same shape and data, on failure panics with their diff and dumps.
+///
+/// ## Examples
+/// ```
+/// use modsvo::{assert_octrees_eq, SparseOctree};
+///
+/// let left = SparseOctree::<u8>::new_with_root(1);
+/// let right = SparseOctree::<u8>::new_with_root(1);
+/// assert_octrees_eq!(left, right);
+/// ```
+#[macro_export]
+macro_rules! assert_octrees_eq {
+	($left:expr, $right:expr $(,)?) => {
+		{
+			let tree_diff = $left.diff(&$right);
+			if !tree_diff.is_empty() {
+				panic!("Octrees differ:\n{}\nleft:\n{}\nright:\n{}", tree_diff, $left.dump_tree(), $right.dump_tree());
+			}
+		}
+	};
+}
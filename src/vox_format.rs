@@ -0,0 +1,314 @@
+//! Reading and writing of MagicaVoxel `.vox` files.
+//!
+//! Voxel at `[x, y, z]` of model is placed into octant `MortonOctantId::from_xyz(x, y, z, depth)`, where depth is
+//! smallest depth fitting size of model. Axes are kept as they are in file, so z axis points up like in MagicaVoxel.
+//! Only first model of file is read, scene graph, materials and other chunks are skipped.
+use std::io::{self, Read, Write};
+
+use glam::Vec3A;
+
+use super::morton_based_storage::morton_octant_id::MortonOctantId;
+use super::octant_meta::OctantPlacement;
+use super::octant_storage_trait::{ModifiableOctantStorage, OctantStorage, StorageError, StorageResult};
+use super::octree_base::{depth_first_search_from_storage, OctreeBase, SearchControlFlow};
+use super::spatial_octree_base::SpatialOctreeBase;
+use super::voxels::voxel_cube::VolumetricCube;
+use super::{Depth, SpatialSparseOctree};
+
+
+const VOX_MAGIC: &[u8; 4] = b"VOX ";
+const VOX_VERSION: i32 = 150;
+/// Maximum size of model along one axis.
+pub const VOX_MAX_SIZE: u16 = 256;
+
+pub type VoxPalette = [[u8; 4]; 256];
+
+#[derive(Debug)]
+pub enum VoxError {
+	Io(io::Error),
+	/// File doesn't start with `VOX ` or `MAIN` chunk.
+	InvalidHeader,
+	/// Chunk is truncated or its content doesn't match its id.
+	InvalidChunk([u8; 4]),
+	/// File has no `SIZE` and `XYZI` chunks.
+	MissingModel,
+	/// Voxel lies outside of model or model is larger than `VOX_MAX_SIZE`.
+	InvalidSize,
+	Storage(StorageError)
+}
+
+impl From<io::Error> for VoxError {
+	fn from(error: io::Error) -> Self {
+		VoxError::Io(error)
+	}
+}
+
+impl From<StorageError> for VoxError {
+	fn from(error: StorageError) -> Self {
+		VoxError::Storage(error)
+	}
+}
+
+/// Single model of `.vox` file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VoxModel {
+	pub size: [u16; 3],
+	/// Position and color index of every voxel, color index 0 is not used.
+	pub voxels: Vec<([u8; 3], u8)>,
+	/// Colors where `palette[index - 1]` belongs to color index, `None` when file uses default palette.
+	pub palette: Option<VoxPalette>
+}
+
+impl VoxModel {
+	/// Read first model of `.vox` file.
+	pub fn read<R: Read>(reader: &mut R) -> Result<Self, VoxError> {
+		let mut header = [0_u8; 8];
+		reader.read_exact(&mut header)?;
+		if &header[0..4] != VOX_MAGIC {
+			return Err(VoxError::InvalidHeader);
+		}
+
+		let (main_id, main_content, main_children) = read_chunk(reader)?;
+		if &main_id != b"MAIN" || !main_content.is_empty() {
+			return Err(VoxError::InvalidHeader);
+		}
+
+		let mut size: Option<[u16; 3]> = None;
+		let mut voxels: Option<Vec<([u8; 3], u8)>> = None;
+		let mut palette: Option<VoxPalette> = None;
+		let mut children_reader: &[u8] = &main_children;
+		while !children_reader.is_empty() {
+			let (chunk_id, content, _) = read_chunk(&mut children_reader)?;
+			match &chunk_id {
+				b"SIZE" if size.is_none() => {
+					let [x, y, z] = read_i32_array::<3>(&content).ok_or(VoxError::InvalidChunk(chunk_id))?;
+					let model_size = [x, y, z].map(|axis| u16::try_from(axis).ok().filter(|&axis| axis <= VOX_MAX_SIZE));
+					let [Some(x), Some(y), Some(z)] = model_size else {
+						return Err(VoxError::InvalidSize);
+					};
+					size = Some([x, y, z]);
+				},
+				b"XYZI" if voxels.is_none() => {
+					let [count] = read_i32_array::<1>(&content).ok_or(VoxError::InvalidChunk(chunk_id))?;
+					let voxel_bytes: &[u8] = usize::try_from(count).ok()
+						.and_then(|count| content.get(4..4 + count * 4))
+						.ok_or(VoxError::InvalidChunk(chunk_id))?;
+					voxels = Some(
+						voxel_bytes.chunks_exact(4)
+							.map(|voxel| ([voxel[0], voxel[1], voxel[2]], voxel[3]))
+							.collect()
+					);
+				},
+				b"RGBA" => {
+					if content.len() < 256 * 4 {
+						return Err(VoxError::InvalidChunk(chunk_id));
+					}
+					let mut colors: VoxPalette = [[0; 4]; 256];
+					for (color, bytes) in colors.iter_mut().zip(content.chunks_exact(4)) {
+						color.copy_from_slice(bytes);
+					}
+					palette = Some(colors);
+				},
+				_ => ()
+			}
+		}
+
+		let (Some(size), Some(voxels)) = (size, voxels) else {
+			return Err(VoxError::MissingModel);
+		};
+		if voxels.iter().any(|(position, _)| (0..3).any(|axis| position[axis] as u16 >= size[axis])) {
+			return Err(VoxError::InvalidSize);
+		}
+		Ok(
+			VoxModel{
+				size,
+				voxels,
+				palette
+			}
+		)
+	}
+
+	/// Write model as `.vox` file, palette is written only when model has one.
+	pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), VoxError> {
+		if self.size.iter().any(|&axis| axis > VOX_MAX_SIZE) {
+			return Err(VoxError::InvalidSize);
+		}
+
+		let mut children: Vec<u8> = Vec::new();
+		let size_content: Vec<u8> = self.size.iter().flat_map(|&axis| (axis as i32).to_le_bytes()).collect();
+		write_chunk(&mut children, b"SIZE", &size_content, &[])?;
+
+		let mut voxels_content: Vec<u8> = (self.voxels.len() as i32).to_le_bytes().to_vec();
+		for ([x, y, z], color_index) in self.voxels.iter() {
+			voxels_content.extend_from_slice(&[*x, *y, *z, *color_index]);
+		}
+		write_chunk(&mut children, b"XYZI", &voxels_content, &[])?;
+
+		if let Some(palette) = self.palette.as_ref() {
+			let palette_content: Vec<u8> = palette.iter().flatten().copied().collect();
+			write_chunk(&mut children, b"RGBA", &palette_content, &[])?;
+		}
+
+		writer.write_all(VOX_MAGIC)?;
+		writer.write_all(&VOX_VERSION.to_le_bytes())?;
+		write_chunk(writer, b"MAIN", &[], &children)?;
+		Ok(())
+	}
+
+	/// Smallest depth at which every voxel of model has its own octant.
+	pub fn depth(&self) -> Depth {
+		let largest_axis: u32 = self.size.iter().copied().max().unwrap_or(1).max(1) as u32;
+		largest_axis.next_power_of_two().trailing_zeros() as Depth
+	}
+
+	/// Root voxel where every voxel of model is unit cube with minimal corner at its `[x, y, z]`.
+	pub fn root_voxel(&self) -> VolumetricCube {
+		let half_extent: f32 = (1_u32 << self.depth()) as f32 * 0.5;
+		VolumetricCube::new(Vec3A::splat(half_extent), half_extent)
+	}
+
+	/// Color of color index, `None` when model uses default palette or for color index 0.
+	pub fn color(&self, color_index: u8) -> Option<[u8; 4]> {
+		let palette_index: usize = (color_index as usize).checked_sub(1)?;
+		self.palette.as_ref().map(|palette| palette[palette_index])
+	}
+
+	/// Convert model into spatial octree, see `root_voxel()` for placement of voxels.
+	///
+	/// ## Arguments
+	/// * `voxel_data` - Maps color index and its color to data of voxel octant.
+	/// * `empty_data` - Data of root and octants between root and voxels.
+	pub fn to_octree<Storage, F>(&self, empty_data: Storage::Data, mut voxel_data: F) -> StorageResult<SpatialOctreeBase<Storage>>
+	where
+		Storage: ModifiableOctantStorage<OctantId = MortonOctantId> + Default,
+		Storage::Data: Clone,
+		F: FnMut(u8, Option<[u8; 4]>) -> Storage::Data
+	{
+		let depth: Depth = self.depth();
+		let mut octree: SpatialOctreeBase<Storage> = SpatialOctreeBase::new_with_root(self.root_voxel(), empty_data.clone());
+		for ([x, y, z], color_index) in self.voxels.iter() {
+			let voxel_id: MortonOctantId = MortonOctantId::from_xyz(*x as u16, *y as u16, *z as u16, depth).map_err(|_| StorageError::OverMaxDepth(depth))?;
+			insert_with_ancestors(octree.octants_mut(), &voxel_id, voxel_data(*color_index, self.color(*color_index)), &empty_data)?;
+		}
+		Ok(octree)
+	}
+
+	/// Create model from octants at fixed depth inside of region, octants at other depths are ignored.
+	///
+	/// ## Arguments
+	/// * `depth` - Depth of exported octants, their `[x, y, z]` at this depth are grid positions.
+	/// * `region_min` - Grid position which becomes `[0, 0, 0]` of model.
+	/// * `region_size` - Size of model, at most `VOX_MAX_SIZE` along each axis.
+	/// * `color_index` - Maps octant data to color index, `None` or 0 leaves voxel empty.
+	pub fn from_octree<Storage, F>(octree: &OctreeBase<Storage>, depth: Depth, region_min: [u16; 3], region_size: [u16; 3], palette: Option<VoxPalette>, mut color_index: F) -> Result<Self, VoxError>
+	where
+		Storage: OctantStorage<OctantId = MortonOctantId>,
+		F: FnMut(&Storage::Data) -> Option<u8>
+	{
+		if region_size.iter().any(|&axis| axis > VOX_MAX_SIZE) {
+			return Err(VoxError::InvalidSize);
+		}
+
+		let mut voxels: Vec<([u8; 3], u8)> = Vec::new();
+		let root_id: MortonOctantId = octree.octants.get_root_id();
+		depth_first_search_from_storage(
+			&octree.octants,
+			&root_id,
+			|octant_depth, octant_id|{
+				if octant_depth < depth {
+					return SearchControlFlow::Continue;
+				}
+				let xyz: [u16; 3] = octant_id.xyz();
+				let is_in_region = (0..3).all(|axis| xyz[axis] >= region_min[axis] && ((xyz[axis] - region_min[axis]) as u32) < region_size[axis] as u32);
+				let voxel_color = octree.octants.get_octant(octant_id).and_then(&mut color_index).filter(|&color_index| color_index != 0);
+				if let (true, Some(voxel_color)) = (is_in_region, voxel_color) {
+					voxels.push(([0, 1, 2].map(|axis| (xyz[axis] - region_min[axis]) as u8), voxel_color));
+				}
+				SearchControlFlow::Skip
+			}
+		)?;
+
+		Ok(
+			VoxModel{
+				size: region_size,
+				voxels,
+				palette
+			}
+		)
+	}
+}
+
+impl SpatialSparseOctree<u8> {
+	/// Read first model of `.vox` file into tree, where data of voxel octants is their color index and other octants have 0.
+	pub fn read_vox<R: Read>(reader: &mut R) -> Result<Self, VoxError> {
+		let model: VoxModel = VoxModel::read(reader)?;
+		Ok(model.to_octree(0, |color_index, _| color_index)?)
+	}
+}
+
+/// Insert octant and all of its missing ancestors with empty data.
+fn insert_with_ancestors<Storage>(storage: &mut Storage, octant_id: &MortonOctantId, data: Storage::Data, empty_data: &Storage::Data) -> StorageResult<()>
+where
+	Storage: ModifiableOctantStorage<OctantId = MortonOctantId>,
+	Storage::Data: Clone
+{
+	let depth: Depth = octant_id.compute_depth();
+	if depth == 0 {
+		*storage.get_octant_mut(octant_id).ok_or(StorageError::InvalidOctantId)? = data;
+		return Ok(());
+	}
+	let mut parent_id: MortonOctantId = storage.get_root_id();
+	for level in (0..depth as u32).rev() {
+		let child_placement: OctantPlacement = OctantPlacement::OCTANTS_ORDERED[((octant_id.as_morton() >> (level * 3)) & 0b111) as usize];
+		if level == 0 {
+			storage.insert_octant(&parent_id, child_placement, data)?;
+			break;
+		}
+		parent_id = match storage.get_existing_child(&parent_id, child_placement) {
+			Ok(child_id) => child_id,
+			Err(StorageError::ChildNotFound(_)) => storage.insert_octant(&parent_id, child_placement, empty_data.clone())?.0,
+			Err(error) => return Err(error)
+		};
+	}
+	Ok(())
+}
+
+/// Id, content and children of chunk.
+type VoxChunk = ([u8; 4], Vec<u8>, Vec<u8>);
+
+/// Read chunk and return its id, content and children.
+fn read_chunk<R: Read>(reader: &mut R) -> Result<VoxChunk, VoxError> {
+	let mut chunk_header = [0_u8; 12];
+	reader.read_exact(&mut chunk_header)?;
+	let chunk_id: [u8; 4] = [chunk_header[0], chunk_header[1], chunk_header[2], chunk_header[3]];
+	let [content_size, children_size] = read_i32_array::<2>(&chunk_header[4..]).ok_or(VoxError::InvalidChunk(chunk_id))?;
+	// size comes from file, so buffer grows only with bytes which were really read
+	let read_part = |reader: &mut R, size: i32|{
+		let size: u64 = u64::try_from(size).map_err(|_| VoxError::InvalidChunk(chunk_id))?;
+		let mut part: Vec<u8> = Vec::new();
+		reader.by_ref().take(size).read_to_end(&mut part).map_err(|_| VoxError::InvalidChunk(chunk_id))?;
+		if part.len() as u64 != size {
+			return Err(VoxError::InvalidChunk(chunk_id));
+		}
+		Ok::<Vec<u8>, VoxError>(part)
+	};
+	let content: Vec<u8> = read_part(reader, content_size)?;
+	let children: Vec<u8> = read_part(reader, children_size)?;
+	Ok((chunk_id, content, children))
+}
+
+fn write_chunk<W: Write>(writer: &mut W, chunk_id: &[u8; 4], content: &[u8], children: &[u8]) -> io::Result<()> {
+	writer.write_all(chunk_id)?;
+	writer.write_all(&(content.len() as i32).to_le_bytes())?;
+	writer.write_all(&(children.len() as i32).to_le_bytes())?;
+	writer.write_all(content)?;
+	writer.write_all(children)
+}
+
+fn read_i32_array<const N: usize>(bytes: &[u8]) -> Option<[i32; N]> {
+	let mut values = [0_i32; N];
+	for (value, value_bytes) in values.iter_mut().zip(bytes.get(..N * 4)?.chunks_exact(4)) {
+		*value = i32::from_le_bytes(value_bytes.try_into().ok()?);
+	}
+	Some(values)
+}
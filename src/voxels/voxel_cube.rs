@@ -160,8 +160,21 @@ impl VolumetricCube  {
         self.set_half_extent(self.half_extent().max(distance_to_point));
     }
 
-	pub fn expand_in_direction(&mut self, _point: Vec3) {
-		unimplemented!()	
+	/// Double size of cube towards point, so cube before expansion becomes one of its octants.
+	///
+	/// ## Returns
+	/// Placement of cube before expansion inside of expanded cube.
+	pub fn expand_in_direction(&mut self, point: Vec3) -> OctantPlacement {
+		let half_extent: f32 = self.half_extent();
+		let towards_negative = Vec3A::from(point).cmplt(self.center());
+		// old cube takes half on opposite side of growth
+		let placement_index: usize = ((towards_negative.test(0) as usize) << 2)
+			| ((towards_negative.test(1) as usize) << 1)
+			| towards_negative.test(2) as usize;
+		let growth: Vec3A = Vec3A::select(towards_negative, Vec3A::splat(-half_extent), Vec3A::splat(half_extent));
+		self.set_center(self.center() + growth);
+		self.set_half_extent(half_extent * 2.0);
+		OctantPlacement::OCTANTS_ORDERED[placement_index]
 	}
 
 	pub fn get_octant_position(&self, octant_placement: OctantPlacement) -> Vec3A{
@@ -286,7 +299,41 @@ impl VolumetricCube  {
 			other_min.y <= self_min.y &&
 			other_max.y >= self_max.y &&
 			other_min.z <= self_min.z &&
-			other_max.z >= self_max.z 
+			other_max.z >= self_max.z
+	}
+
+	/// Separating axis test between triangle and cube, touching counts as intersection.
+	pub fn intersects_triangle(&self, triangle: [Vec3A; 3]) -> bool {
+		let half_extent = Vec3A::splat(self.half_extent());
+		let vertices = triangle.map(|vertex| vertex - self.center());
+		let edges = [vertices[1] - vertices[0], vertices[2] - vertices[1], vertices[0] - vertices[2]];
+
+		let is_separated_on = |axis: Vec3A|{
+			let projections = vertices.map(|vertex| vertex.dot(axis));
+			let cube_radius = half_extent.dot(axis.abs());
+			let min_projection = projections[0].min(projections[1]).min(projections[2]);
+			let max_projection = projections[0].max(projections[1]).max(projections[2]);
+			min_projection > cube_radius || max_projection < -cube_radius
+		};
+
+		// cross products of cube face normals and triangle edges
+		for edge in edges {
+			for cube_axis in [Vec3A::X, Vec3A::Y, Vec3A::Z] {
+				let axis = cube_axis.cross(edge);
+				if axis.length_squared() > f32::EPSILON && is_separated_on(axis) {
+					return false;
+				}
+			}
+		}
+
+		// cube face normals
+		if [Vec3A::X, Vec3A::Y, Vec3A::Z].into_iter().any(is_separated_on) {
+			return false;
+		}
+
+		// triangle normal
+		let normal = edges[0].cross(edges[1]);
+		normal.length_squared() <= f32::EPSILON || !is_separated_on(normal)
 	}
 
 
@@ -395,23 +442,21 @@ impl Voxel for VolumetricCube {
 impl VolumetricCube {
 	pub const CORNERS_PER_FACE: usize = 4;
 	
+	/// Direction of sub voxel center for every placement, indexed by placement, so it matches [x, y, z] bits of `OctantPlacement`.
 	const SPATIAL_POSITION: [Vec3A; OctantPlacement::OCTANTS_COUNT] = [
 		Vec3A::new(-1.0, -1.0, -1.0),// LOWER_BOTTOM_LEFT
 		Vec3A::new(-1.0, -1.0, 1.0), // LOWER_TOP_LEFT
+		Vec3A::new(-1.0, 1.0, -1.0), // UPPER_BOTTOM_LEFT
+		Vec3A::new(-1.0, 1.0, 1.0),  // UPPER_TOP_LEFT
+
 		Vec3A::new(1.0,  -1.0, -1.0),// LOWER_BOTTOM_RIGHT
 		Vec3A::new(1.0,  -1.0, 1.0), // LOWER_TOP_RIGHT
-		
-		
-
-		Vec3A::new(-1.0, 1.0, 1.0),  // UPPER_TOP_LEFT
-		Vec3A::new(1.0,  1.0, 1.0),  // UPPER_TOP_RIGHT
 		Vec3A::new(1.0,  1.0, -1.0), // UPPER_BOTTOM_RIGHT
-		Vec3A::new(-1.0, 1.0, -1.0), // UPPER_BOTTOM_LEFT
-
-
+		Vec3A::new(1.0,  1.0, 1.0),  // UPPER_TOP_RIGHT
 	];
 	
-	const SPATIAL_NEIGHBOR_DIRECTION: [Vec3A; OctantNeighborDirection::FACING_NEIGHBOR_DIRECTIONS_COUNT] = [
+	/// Unit offset towards neighbor, indexed by `OctantNeighborDirection`, diagonal offsets are sums of their facing ones.
+	const SPATIAL_NEIGHBOR_DIRECTION: [Vec3A; OctantNeighborDirection::NEIGHBOR_DIRECTIONS_COUNT] = [
 		// UP
 		Vec3A::new(0.0, 1.0, 0.0),
 		// DOWN
@@ -424,6 +469,36 @@ impl VolumetricCube {
 		Vec3A::new(1.0, 0.0, 0.0),
 		// LEFT
 		Vec3A::new(-1.0, 0.0, 0.0),
+
+		// NE, NW, SE, SW
+		Vec3A::new(1.0, 0.0, 1.0),
+		Vec3A::new(-1.0, 0.0, 1.0),
+		Vec3A::new(1.0, 0.0, -1.0),
+		Vec3A::new(-1.0, 0.0, -1.0),
+
+		// UN, US, UE, UW
+		Vec3A::new(0.0, 1.0, 1.0),
+		Vec3A::new(0.0, 1.0, -1.0),
+		Vec3A::new(1.0, 1.0, 0.0),
+		Vec3A::new(-1.0, 1.0, 0.0),
+
+		// DN, DS, DE, DW
+		Vec3A::new(0.0, -1.0, 1.0),
+		Vec3A::new(0.0, -1.0, -1.0),
+		Vec3A::new(1.0, -1.0, 0.0),
+		Vec3A::new(-1.0, -1.0, 0.0),
+
+		// UNE, UNW, USE, USW
+		Vec3A::new(1.0, 1.0, 1.0),
+		Vec3A::new(-1.0, 1.0, 1.0),
+		Vec3A::new(1.0, 1.0, -1.0),
+		Vec3A::new(-1.0, 1.0, -1.0),
+
+		// DNE, DNW, DSE, DSW
+		Vec3A::new(1.0, -1.0, 1.0),
+		Vec3A::new(-1.0, -1.0, 1.0),
+		Vec3A::new(1.0, -1.0, -1.0),
+		Vec3A::new(-1.0, -1.0, -1.0),
 	];
 
 	const SPATIAL_DIRECTIONAL_FACE_CORNERS: [[CornerPlacement; Self::CORNERS_PER_FACE]; OctantNeighborDirection::FACING_NEIGHBOR_DIRECTIONS_COUNT] = [
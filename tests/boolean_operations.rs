@@ -0,0 +1,136 @@
+#[cfg(test)]
+mod tests{
+	use modsvo::{
+		boolean_operations::{difference, intersection, union},
+		morton_based_storage::morton_octant_id::MortonOctantId,
+		octant_meta::OctantPlacement,
+		octant_storage_trait::{ModifiableOctantStorage, OctantStorage},
+		octree_base::SubdivisionControlFlow,
+		SparseOctree
+	};
+
+	const SAMPLE_DEPTH: u32 = 4;
+
+	fn build_tree(seed: u64, removed_every: u64) -> SparseOctree<u32> {
+		let mut octree: SparseOctree<u32> = SparseOctree::new_with_root(0);
+		octree.subdivide_if_from_root(
+			|depth, octant_id: &MortonOctantId, _|{
+				if depth < 3 && !(octant_id.as_morton() * 31 + seed).is_multiple_of(3) {
+					SubdivisionControlFlow::Continue(move |placement| depth as u32 * 8 + placement as u32 + 1)
+				}
+				else {
+					SubdivisionControlFlow::Skip
+				}
+			}
+		).unwrap();
+
+		// punch holes, so there is empty space in both trees
+		let removed: Vec<MortonOctantId> = octree.breadth_first_iterator()
+			.map(|(_, octant_id)| octant_id)
+			.filter(|octant_id| !octant_id.is_root() && (octant_id.as_morton() + seed).is_multiple_of(removed_every))
+			.collect();
+		for octant_id in removed {
+			octree.octants.remove_octant(&octant_id);
+		}
+		octree
+	}
+
+	/// Data filling volume of cell at sample depth, `None` for empty space.
+	fn sample(octree: &SparseOctree<u32>, cell_index: u32) -> Option<u32> {
+		let mut octant_id: MortonOctantId = octree.octants.get_root_id();
+		// tree without root is empty
+		let _ = octree.octants.get_octant(&octant_id)?;
+		for level in (0..SAMPLE_DEPTH).rev() {
+			let children = octree.octants.get_existing_children(&octant_id).unwrap();
+			if children.iter().all(Option::is_none) {
+				break;
+			}
+			let placement = OctantPlacement::OCTANTS_ORDERED[((cell_index >> (level * 3)) & 0b111) as usize];
+			octant_id = children[placement as usize]?;
+		}
+		octree.octants.get_octant(&octant_id).copied()
+	}
+
+	fn combine(left: &u32, right: &u32) -> u32 {
+		left * 100 + right
+	}
+
+	#[test]
+	fn test_boolean_operations_match_cell_sampling(){
+		// seeds are chosen so that both roots are subdivided
+		let left = build_tree(1, 7);
+		let right = build_tree(4, 5);
+
+		let union_tree = union(&left, &right, combine).unwrap();
+		let intersection_tree = intersection(&left, &right, combine).unwrap();
+		let difference_tree = difference(&left, &right).unwrap();
+
+		for cell_index in 0..(1 << (SAMPLE_DEPTH * 3)) {
+			let (left_cell, right_cell) = (sample(&left, cell_index), sample(&right, cell_index));
+			let expected_union = match (left_cell, right_cell) {
+				(Some(left_data), Some(right_data)) => Some(combine(&left_data, &right_data)),
+				(left_data, right_data) => left_data.or(right_data)
+			};
+			assert_eq!(sample(&union_tree, cell_index), expected_union);
+			assert_eq!(sample(&intersection_tree, cell_index), left_cell.zip(right_cell).map(|(left_data, right_data)| combine(&left_data, &right_data)));
+			assert_eq!(sample(&difference_tree, cell_index), if right_cell.is_some() { None } else { left_cell });
+		}
+	}
+
+	#[test]
+	fn test_in_place_operations_match_new_trees(){
+		let left = build_tree(3, 6);
+		let right = build_tree(4, 9);
+
+		let mut union_in_place = build_tree(3, 6);
+		union_in_place.union_with(&right, combine).unwrap();
+		let mut intersection_in_place = build_tree(3, 6);
+		intersection_in_place.intersect_with(&right, combine).unwrap();
+		let mut difference_in_place = build_tree(3, 6);
+		difference_in_place.subtract(&right).unwrap();
+
+		let union_tree = union(&left, &right, combine).unwrap();
+		let intersection_tree = intersection(&left, &right, combine).unwrap();
+		let difference_tree = difference(&left, &right).unwrap();
+		for cell_index in 0..(1 << (SAMPLE_DEPTH * 3)) {
+			assert_eq!(sample(&union_in_place, cell_index), sample(&union_tree, cell_index));
+			assert_eq!(sample(&intersection_in_place, cell_index), sample(&intersection_tree, cell_index));
+			assert_eq!(sample(&difference_in_place, cell_index), sample(&difference_tree, cell_index));
+		}
+
+		// emptied branches are removed instead of turning into filled leaves, root included
+		let mut emptied = build_tree(3, 6);
+		emptied.subtract(&left).unwrap();
+		assert!((0..(1 << (SAMPLE_DEPTH * 3))).all(|cell_index| sample(&emptied, cell_index).is_none()));
+		assert!(emptied.octants.get_octant(&emptied.octants.get_root_id()).is_none());
+	}
+
+	#[test]
+	fn test_empty_results_and_operands(){
+		let root_id: MortonOctantId = MortonOctantId::ROOT_OCTANT_ID;
+		let mut lower: SparseOctree<u32> = SparseOctree::new_with_root(0);
+		lower.octants.insert_octant(&root_id, OctantPlacement::LOWER_BOTTOM_LEFT, 1).unwrap();
+		let mut upper: SparseOctree<u32> = SparseOctree::new_with_root(0);
+		upper.octants.insert_octant(&root_id, OctantPlacement::UPPER_TOP_RIGHT, 2).unwrap();
+		let all_cells = 0..(1 << (SAMPLE_DEPTH * 3));
+
+		let disjoint = intersection(&lower, &upper, combine).unwrap();
+		assert!(all_cells.clone().all(|cell_index| sample(&disjoint, cell_index).is_none()));
+		let mut same = build_tree(1, 7);
+		same.subtract(&build_tree(1, 7)).unwrap();
+		assert!(all_cells.clone().all(|cell_index| sample(&same, cell_index).is_none()));
+
+		// empty tree works as operand
+		let empty = difference(&lower, &lower).unwrap();
+		assert!(all_cells.clone().all(|cell_index| sample(&empty, cell_index).is_none()));
+		let restored = union(&empty, &upper, combine).unwrap();
+		assert!(all_cells.clone().all(|cell_index| sample(&restored, cell_index) == sample(&upper, cell_index)));
+		let kept = difference(&upper, &empty).unwrap();
+		assert!(all_cells.clone().all(|cell_index| sample(&kept, cell_index) == sample(&upper, cell_index)));
+		let mut cleared = build_tree(1, 7);
+		cleared.intersect_with(&empty, combine).unwrap();
+		assert!(all_cells.clone().all(|cell_index| sample(&cleared, cell_index).is_none()));
+		let empty_intersection = intersection(&empty, &upper, combine).unwrap();
+		assert!(all_cells.clone().all(|cell_index| sample(&empty_intersection, cell_index).is_none()));
+	}
+}
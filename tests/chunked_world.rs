@@ -0,0 +1,164 @@
+#[cfg(test)]
+mod tests{
+	use std::collections::HashMap;
+
+	use glam::{IVec3, Vec3A};
+
+	use modsvo::{
+		chunked_world::{Chunk, ChunkLoader, ChunkOctantId, ChunkedWorld},
+		octant_meta::{OctantNeighborDirection, OctantPlacement},
+		octant_storage_trait::ModifiableOctantStorage,
+		voxels::voxel_cube::VolumetricCube
+	};
+
+	/// Keeps unloaded chunks, so they can be loaded again.
+	#[derive(Default)]
+	struct SavingLoader {
+		loaded: Vec<IVec3>,
+		saved: HashMap<IVec3, Chunk<u32>>
+	}
+
+	impl ChunkLoader<u32> for SavingLoader {
+		fn load_chunk(&mut self, chunk_coord: IVec3, chunk_voxel: VolumetricCube) -> Chunk<u32> {
+			self.loaded.push(chunk_coord);
+			self.saved.remove(&chunk_coord)
+				.unwrap_or_else(|| Chunk::<u32>::new_with_root(chunk_voxel, 0))
+		}
+
+		fn unload_chunk(&mut self, chunk_coord: IVec3, chunk: Chunk<u32>) {
+			self.saved.insert(chunk_coord, chunk);
+		}
+	}
+
+	/// Chunks subdivided once, children hold their placement.
+	fn subdivided_chunk(_: IVec3, chunk_voxel: VolumetricCube) -> Chunk<u32> {
+		let mut chunk = Chunk::<u32>::new_with_root(chunk_voxel, 0);
+		let root_id = chunk.get_root_id();
+		chunk.octants_mut().subdivide(&root_id, |placement| placement as u32).unwrap();
+		chunk
+	}
+
+	#[test]
+	fn test_lazy_loading_and_point_routing(){
+		let mut world = ChunkedWorld::new(1.0, SavingLoader::default());
+		let point = Vec3A::new(-0.5, 0.5, 3.5);
+		assert_eq!(world.octant_at_point(point), None);
+
+		let inserted = world.insert_at_point(point, 2, 7, |_| 1).unwrap();
+		assert_eq!(inserted.chunk_coord, IVec3::new(-1, 0, 1));
+		assert_eq!(world.loader().loaded, vec![IVec3::new(-1, 0, 1)]);
+		assert_eq!(world.octant_at_point(point), Some(inserted));
+		assert_eq!(world.get_octant(&inserted), Some(&7));
+
+		let voxel = world.get_voxel_by_id(&inserted).unwrap();
+		assert_eq!(voxel.half_extent(), 0.25);
+		assert!(voxel.contains_point(point));
+
+		world.insert_at_point(point + Vec3A::X * 0.1, 2, 8, |_| 1).unwrap();
+		assert_eq!(world.loaded_chunk_count(), 1);
+		assert_eq!(world.get_octant(&inserted), Some(&8));
+	}
+
+	#[test]
+	fn test_box_query_across_chunks(){
+		let mut world = ChunkedWorld::new(1.0, subdivided_chunk);
+		world.chunk_or_load(IVec3::ZERO);
+		world.chunk_or_load(IVec3::X);
+
+		let mut leaves: Vec<(IVec3, u32)> = world.leaves_in_box(Vec3A::new(1.5, 0.5, 0.5), Vec3A::new(2.5, 0.8, 0.8))
+			.iter()
+			.map(|leaf| (leaf.chunk_coord, *world.get_octant(leaf).unwrap()))
+			.collect();
+		leaves.sort_by_key(|(chunk_coord, _)| chunk_coord.to_array());
+		assert_eq!(
+			leaves,
+			vec![
+				(IVec3::ZERO, OctantPlacement::LOWER_BOTTOM_RIGHT as u32),
+				(IVec3::X, OctantPlacement::LOWER_BOTTOM_LEFT as u32)
+			]
+		);
+
+		// unloaded chunks are not loaded by queries
+		assert_eq!(world.leaves_in_box(Vec3A::new(4.5, 0.5, 0.5), Vec3A::new(5.0, 1.0, 1.0)).len(), 0);
+		assert_eq!(world.loaded_chunk_count(), 2);
+	}
+
+	#[test]
+	fn test_raycast_across_chunks(){
+		let mut world = ChunkedWorld::new(1.0, subdivided_chunk);
+		world.chunk_or_load(IVec3::ZERO);
+		world.chunk_or_load(IVec3::new(2, 0, 0));
+		let is_solid = |data: &u32| *data == OctantPlacement::LOWER_BOTTOM_RIGHT as u32;
+
+		let hit = world.raycast(Vec3A::new(0.5, 0.5, 0.5), Vec3A::new(2.0, 0.0, 0.0), 10.0, is_solid).unwrap();
+		assert_eq!(hit.octant.chunk_coord, IVec3::ZERO);
+		assert_eq!(hit.distance, 0.5);
+
+		let hit = world.raycast(Vec3A::new(1.5, 0.5, 0.5), Vec3A::X, 10.0, |&data| data == OctantPlacement::LOWER_BOTTOM_LEFT as u32).unwrap();
+		assert_eq!(hit.octant.chunk_coord, IVec3::new(2, 0, 0));
+		assert_eq!(hit.distance, 2.5);
+		assert_eq!(world.raycast(Vec3A::new(1.5, 0.5, 0.5), Vec3A::X, 2.0, |&data| data == OctantPlacement::LOWER_BOTTOM_LEFT as u32), None);
+
+		let hit = world.raycast(Vec3A::new(11.0, 0.5, 0.5), -Vec3A::X, 100.0, is_solid).unwrap();
+		assert_eq!(hit.octant.chunk_coord, IVec3::new(2, 0, 0));
+		assert_eq!(hit.distance, 5.0);
+
+		assert_eq!(world.raycast(Vec3A::new(0.5, 0.5, 0.5), Vec3A::ZERO, 10.0, is_solid), None);
+		assert_eq!(world.raycast(Vec3A::new(0.5, 3.5, 0.5), Vec3A::X, 10.0, is_solid), None);
+	}
+
+	#[test]
+	fn test_neighbor_across_chunks(){
+		let mut world = ChunkedWorld::new(1.0, subdivided_chunk);
+		world.chunk_or_load(IVec3::ZERO);
+		world.chunk_or_load(IVec3::X);
+		let child_of = |chunk_coord: IVec3, placement: OctantPlacement, world: &ChunkedWorld<u32, _>| ChunkOctantId{
+			chunk_coord,
+			octant_id: world.get_chunk(chunk_coord).unwrap().get_root_id().child_id_by_placement(placement)
+		};
+
+		let east_border = child_of(IVec3::ZERO, OctantPlacement::LOWER_BOTTOM_RIGHT, &world);
+		let across_border = child_of(IVec3::X, OctantPlacement::LOWER_BOTTOM_LEFT, &world);
+		assert_eq!(world.neighbor(&east_border, OctantNeighborDirection::E), Some(across_border));
+		assert_eq!(world.neighbor(&across_border, OctantNeighborDirection::W), Some(east_border));
+		assert_eq!(
+			world.neighbor(&east_border, OctantNeighborDirection::U),
+			Some(child_of(IVec3::ZERO, OctantPlacement::UPPER_BOTTOM_RIGHT, &world))
+		);
+
+		// deeper octant gets bigger neighbor, when same sized one doesn't exist
+		let chunk = world.get_chunk_mut(IVec3::ZERO).unwrap();
+		let grand_children = chunk.octants_mut().subdivide(&east_border.octant_id, |_| 100).unwrap();
+		let deep_octant = ChunkOctantId{chunk_coord: IVec3::ZERO, octant_id: grand_children[OctantPlacement::UPPER_TOP_RIGHT as usize]};
+		assert_eq!(world.neighbor(&deep_octant, OctantNeighborDirection::E), Some(across_border));
+
+		let corner = child_of(IVec3::ZERO, OctantPlacement::UPPER_TOP_RIGHT, &world);
+		assert_eq!(world.neighbor(&corner, OctantNeighborDirection::UNE), None);
+		assert_eq!(
+			world.neighbor(&corner, OctantNeighborDirection::DE),
+			Some(child_of(IVec3::X, OctantPlacement::LOWER_TOP_LEFT, &world))
+		);
+	}
+
+	#[test]
+	fn test_load_around_and_evict_far_chunks(){
+		let mut world = ChunkedWorld::new(1.0, SavingLoader::default());
+		// chunk of anchor, its 6 face and 12 edge neighbors
+		assert_eq!(world.load_around(Vec3A::ONE, 1.5), 19);
+		assert_eq!(world.load_around(Vec3A::ONE, 1.5), 0);
+
+		let inserted = world.insert_at_point(Vec3A::new(2.5, 0.5, 0.5), 1, 42, |_| 0).unwrap();
+		assert_eq!(world.evict_far_chunks(&[Vec3A::ONE, Vec3A::new(-0.5, 1.0, 1.0)], 0.0), 17);
+		assert!(world.is_loaded(IVec3::ZERO));
+		assert!(world.is_loaded(-IVec3::X));
+		assert_eq!(world.get_octant(&inserted), None);
+		assert_eq!(world.loader().saved.len(), 17);
+
+		world.chunk_or_load(IVec3::X);
+		assert_eq!(world.get_octant(&inserted), Some(&42));
+
+		world.unload_all();
+		assert_eq!(world.loaded_chunk_count(), 0);
+		assert_eq!(world.loader().saved.len(), 19);
+	}
+}
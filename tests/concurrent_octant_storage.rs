@@ -0,0 +1,228 @@
+#[cfg(test)]
+mod tests{
+	use std::sync::Arc;
+	use std::thread;
+
+	use modsvo::{
+		morton_based_storage::{concurrent_octant_storage::ConcurrentOctantStorage, morton_octant_id::MortonOctantId},
+		octant_meta::OctantPlacement,
+		octant_storage_trait::OctantStorage,
+		octree_base::{breadth_first_search_from_storage, SearchControlFlow},
+	};
+
+	use modsvo::conformance;
+
+	const THREAD_COUNT: usize = 8;
+
+	fn count_reachable_octants(storage: &ConcurrentOctantStorage<u32>) -> usize {
+		let read_guard = storage.read();
+		let mut reachable: usize = 0;
+		let _ = breadth_first_search_from_storage(
+			&read_guard,
+			&MortonOctantId::ROOT_OCTANT_ID,
+			|_, _|{
+				reachable += 1;
+				SearchControlFlow::Continue
+			}
+		);
+		reachable
+	}
+
+	/// Lock-free children lookup agrees with lookup through read guard for every given parent.
+	fn assert_lock_free_children_match_read(storage: &ConcurrentOctantStorage<u32>, parent_ids: &[MortonOctantId]) {
+		for parent_id in parent_ids {
+			let lock_free_children = storage.get_existing_children(parent_id).ok();
+			assert_eq!(lock_free_children, storage.read().get_existing_children(parent_id).ok(), "Children of {:?} differ.", parent_id.as_morton());
+		}
+	}
+
+	#[test]
+	fn test_concurrent_storage_interface_functions(){
+		let storage: Arc<ConcurrentOctantStorage<u32>> = Arc::new(ConcurrentOctantStorage::default());
+
+		// every check holds write guard for its whole run, so checks of different threads interleave between each other
+		let checkers: Vec<_> = (0..THREAD_COUNT)
+			.map(
+				|_|{
+					let storage = storage.clone();
+					thread::spawn(
+						move ||{
+							conformance::check_modifiable_octant_storage(&mut storage.write(), 3, |placement| placement as u32);
+							conformance::check_all(&mut storage.write(), |placement| placement as u32);
+						}
+					)
+				}
+			)
+			.collect();
+
+		checkers.into_iter().for_each(|checker| checker.join().unwrap());
+		assert_eq!(count_reachable_octants(&storage), storage.len());
+
+		let root_children = storage.subdivide(&MortonOctantId::ROOT_OCTANT_ID, |placement| placement as u32).unwrap();
+		for child_id in root_children {
+			storage.subdivide(&child_id, |placement| placement as u32).unwrap();
+		}
+		let mut parent_ids: Vec<MortonOctantId> = vec![MortonOctantId::ROOT_OCTANT_ID];
+		parent_ids.extend(root_children);
+		assert_lock_free_children_match_read(&storage, &parent_ids);
+	}
+
+	#[test]
+	fn test_lock_free_children_across_shard_boundary(){
+		let storage: ConcurrentOctantStorage<u32> = ConcurrentOctantStorage::default();
+		let root_children = storage.subdivide(&MortonOctantId::ROOT_OCTANT_ID, |placement| placement as u32).unwrap();
+		// children of octants right above shard depth are spread over different shards
+		let parent_id: MortonOctantId = root_children[OctantPlacement::UPPER_BOTTOM_RIGHT as usize];
+		assert_eq!(parent_id.compute_depth() + 1, storage.shard_depth());
+		let grand_children = storage.subdivide(&parent_id, |placement| placement as u32).unwrap();
+		assert_eq!(storage.get_existing_children(&parent_id).unwrap(), grand_children.map(Some));
+
+		storage.remove_octant(&grand_children[OctantPlacement::LOWER_TOP_LEFT as usize]).unwrap();
+		let existing_children = storage.get_existing_children(&parent_id).unwrap();
+		assert_eq!(existing_children.iter().flatten().count(), OctantPlacement::OCTANTS_COUNT - 1);
+		assert_eq!(existing_children[OctantPlacement::LOWER_TOP_LEFT as usize], None);
+		assert_lock_free_children_match_read(&storage, &[MortonOctantId::ROOT_OCTANT_ID, parent_id, grand_children[0]]);
+	}
+
+	#[test]
+	fn test_read_guard_is_read_only(){
+		let storage: ConcurrentOctantStorage<u32> = ConcurrentOctantStorage::default();
+		let mut read_guard = storage.read();
+		assert_eq!(read_guard.get_octant_mut(&MortonOctantId::ROOT_OCTANT_ID), None);
+		assert_eq!(read_guard.get_octant(&MortonOctantId::ROOT_OCTANT_ID), Some(&0));
+	}
+
+	#[test]
+	fn test_concurrent_storage_shard_access_from_many_threads(){
+		const ROUNDS: u32 = 50;
+		let storage: Arc<ConcurrentOctantStorage<u32>> = Arc::new(ConcurrentOctantStorage::default());
+
+		// every thread edits its own branch, children of branch lie in separate shards at shard depth,
+		// branches and root are shared by all in top shard
+		let workers: Vec<_> = (0..THREAD_COUNT)
+			.map(
+				|thread_index|{
+					let storage = storage.clone();
+					thread::spawn(
+						move ||{
+							let placement = OctantPlacement::OCTANTS_ORDERED[thread_index % OctantPlacement::OCTANTS_COUNT];
+							let (branch_id, _) = storage.insert_octant(&MortonOctantId::ROOT_OCTANT_ID, placement, 0).unwrap();
+							for round in 0..ROUNDS {
+								let children = storage.subdivide(&branch_id, |child_placement| round * 8 + child_placement as u32).unwrap();
+								assert_eq!(storage.get_existing_children(&branch_id).unwrap(), children.map(Some));
+								assert_lock_free_children_match_read(&storage, &[branch_id]);
+								for (child_index, child_id) in children.iter().enumerate() {
+									assert_eq!(storage.with_octant(child_id, |data| *data), Some(round * 8 + child_index as u32));
+								}
+
+								storage.with_octant_mut(&MortonOctantId::ROOT_OCTANT_ID, |data| *data += 1).unwrap();
+								storage.with_octant_mut(&branch_id, |data| *data += 1).unwrap();
+								let other_branch_id = MortonOctantId::ROOT_OCTANT_ID.children_ids()[(thread_index + 1) % OctantPlacement::OCTANTS_COUNT];
+								let _ = storage.get_existing_children(&other_branch_id);
+
+								storage.remove_octant(&children[round as usize % OctantPlacement::OCTANTS_COUNT]).unwrap();
+								assert!(!storage.contains_octant(&children[round as usize % OctantPlacement::OCTANTS_COUNT]));
+							}
+						}
+					)
+				}
+			)
+			.collect();
+
+		workers.into_iter().for_each(|worker| worker.join().unwrap());
+		// no increment of shared root was lost
+		assert_eq!(storage.get_octant_cloned(&MortonOctantId::ROOT_OCTANT_ID), Some(THREAD_COUNT as u32 * ROUNDS));
+		for branch_id in MortonOctantId::ROOT_OCTANT_ID.children_ids() {
+			assert_eq!(storage.get_octant_cloned(&branch_id), Some(ROUNDS));
+			let existing_children = storage.get_existing_children(&branch_id).unwrap();
+			// last round removed single child
+			assert_eq!(existing_children.iter().flatten().count(), OctantPlacement::OCTANTS_COUNT - 1);
+		}
+		assert_eq!(storage.len(), 1 + 8 + 8 * 7);
+		assert_eq!(count_reachable_octants(&storage), storage.len());
+	}
+
+	#[test]
+	fn test_parallel_insert_and_get(){
+		let storage: Arc<ConcurrentOctantStorage<u32>> = Arc::new(ConcurrentOctantStorage::default());
+		let root_children = storage.subdivide(&MortonOctantId::ROOT_OCTANT_ID, |placement| placement as u32).unwrap();
+
+		let workers: Vec<_> = root_children.into_iter()
+			.map(
+				|branch_id|{
+					let storage = storage.clone();
+					thread::spawn(
+						move ||{
+							let mut to_be_subdivided: Vec<MortonOctantId> = vec![branch_id];
+							while let Some(parent_id) = to_be_subdivided.pop() {
+								if parent_id.compute_depth() >= 4 {
+									continue;
+								}
+								let children = storage.subdivide(&parent_id, |_| parent_id.compute_depth() as u32 + 1).unwrap();
+								assert_lock_free_children_match_read(&storage, &[parent_id]);
+								for child_id in children {
+									assert_eq!(storage.get_octant_cloned(&child_id), Some(parent_id.compute_depth() as u32 + 1));
+								}
+								to_be_subdivided.extend(children);
+							}
+						}
+					)
+				}
+			)
+			.collect();
+
+		workers.into_iter().for_each(|worker| worker.join().unwrap());
+		// root + 8 + 64 + 512 + 4096
+		assert_eq!(storage.len(), 1 + 8 + 64 + 512 + 4096);
+		assert_eq!(count_reachable_octants(&storage), storage.len());
+	}
+
+	#[test]
+	fn test_concurrent_remove_cascades_leave_no_orphans(){
+		let storage: Arc<ConcurrentOctantStorage<u32>> = Arc::new(ConcurrentOctantStorage::default());
+		let contested_id: MortonOctantId = MortonOctantId::ROOT_OCTANT_ID.child_id_by_placement(OctantPlacement::UPPER_TOP_LEFT);
+		storage.insert_octant(&MortonOctantId::ROOT_OCTANT_ID, OctantPlacement::UPPER_TOP_LEFT, 1).unwrap();
+
+		let remover = {
+			let storage = storage.clone();
+			thread::spawn(
+				move ||{
+					for _ in 0..200 {
+						storage.remove_octant(&contested_id);
+						let _ = storage.insert_octant(&MortonOctantId::ROOT_OCTANT_ID, OctantPlacement::UPPER_TOP_LEFT, 1);
+					}
+				}
+			)
+		};
+
+		let builders: Vec<_> = (0..THREAD_COUNT)
+			.map(
+				|thread_index|{
+					let storage = storage.clone();
+					thread::spawn(
+						move ||{
+							let child_placement = OctantPlacement::OCTANTS_ORDERED[thread_index % OctantPlacement::OCTANTS_COUNT];
+							for _ in 0..200 {
+								let mut parent_id = contested_id;
+								for _ in 0..5 {
+									match storage.insert_octant(&parent_id, child_placement, thread_index as u32) {
+										Ok((child_id, _)) => parent_id = child_id,
+										Err(_) => break
+									}
+								}
+							}
+						}
+					)
+				}
+			)
+			.collect();
+
+		remover.join().unwrap();
+		builders.into_iter().for_each(|builder| builder.join().unwrap());
+		assert_eq!(count_reachable_octants(&storage), storage.len());
+
+		let removed = storage.remove_octant_and_collect(&MortonOctantId::ROOT_OCTANT_ID).unwrap();
+		assert!(!removed.is_empty());
+		assert!(storage.is_empty());
+	}
+}
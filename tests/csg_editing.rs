@@ -0,0 +1,131 @@
+#[cfg(test)]
+mod tests{
+	use glam::Vec3A;
+
+	use modsvo::{
+		csg::{CsgShape, Sphere},
+		morton_based_storage::morton_octant_id::MortonOctantId,
+		octant_meta::OctantPlacement,
+		octant_storage_trait::{ModifiableOctantStorage, OctantStorage, StorageError},
+		octree_base::SearchControlFlow,
+		voxel_trait::Voxel,
+		voxels::voxel_cube::{SpatialRelation, VolumetricCube},
+		Depth, SpatialSparseOctree
+	};
+
+	const TARGET_DEPTH: Depth = 4;
+	const GROUND: u32 = 0;
+	const STONE: u32 = 1;
+
+	fn collect_leaves(octree: &SpatialSparseOctree<u32>) -> Vec<(Depth, VolumetricCube, u32)> {
+		let mut leaves: Vec<(Depth, VolumetricCube, u32)> = Vec::new();
+		octree.depth_first_search_from_root(
+			&mut |depth, octant_id: &MortonOctantId, voxel: &VolumetricCube|{
+				let is_leaf = octree.octants().get_existing_children(octant_id).map(|children| children.iter().all(Option::is_none)).unwrap_or(true);
+				if is_leaf {
+					leaves.push((depth, *voxel, *octree.octants().get_octant(octant_id).unwrap()));
+				}
+				SearchControlFlow::Continue
+			}
+		).unwrap();
+		leaves
+	}
+
+	/// Data filling volume at point under leaf semantics, `None` for empty space.
+	fn sample(octree: &SpatialSparseOctree<u32>, point: Vec3A) -> Option<u32> {
+		let mut octant_id: MortonOctantId = octree.get_root_id();
+		let mut voxel: VolumetricCube = *octree.get_root_voxel();
+		let _ = octree.octants().get_octant(&octant_id)?;
+		while octree.octants().get_existing_children(&octant_id).unwrap().iter().any(Option::is_some) {
+			let placement = voxel.guess_octant(point);
+			octant_id = octree.octants().get_existing_child(&octant_id, placement).ok()?;
+			voxel = voxel.make_sub_voxel(placement);
+		}
+		octree.octants().get_octant(&octant_id).copied()
+	}
+
+	/// Centers of all cells at target depth of root with half extent 1.
+	fn cell_centers() -> impl Iterator<Item = Vec3A> {
+		let cells_per_axis: u32 = 1 << TARGET_DEPTH;
+		(0..cells_per_axis.pow(3)).map(
+			move |cell_index|{
+				let cell = Vec3A::new((cell_index % cells_per_axis) as f32, (cell_index / cells_per_axis % cells_per_axis) as f32, (cell_index / cells_per_axis / cells_per_axis) as f32);
+				(cell + 0.5) * 2.0 / cells_per_axis as f32 - 1.0
+			}
+		)
+	}
+
+	fn is_covered<S: CsgShape>(shape: &S, depth: Depth, voxel: &VolumetricCube) -> bool {
+		match shape.relation_to_voxel(voxel) {
+			SpatialRelation::Contained => true,
+			SpatialRelation::Intersecting => depth >= TARGET_DEPTH && shape.contains_point(voxel.center()),
+			SpatialRelation::Separate => false
+		}
+	}
+
+	#[test]
+	fn test_fill_and_carve_sphere(){
+		let mut octree: SpatialSparseOctree<u32> = SpatialSparseOctree::new_with_root(VolumetricCube::new(Vec3A::ZERO, 1.0), GROUND);
+		let sphere = Sphere::new(Vec3A::new(0.2, -0.1, 0.3), 0.45);
+
+		octree.fill_shape(&sphere, TARGET_DEPTH, STONE).unwrap();
+		let filled_leaves = collect_leaves(&octree);
+		for (depth, voxel, data) in filled_leaves.iter() {
+			assert!(*depth <= TARGET_DEPTH);
+			assert_eq!(*data == STONE, is_covered(&sphere, *depth, voxel));
+			// only boundary is subdivided down to target depth
+			if *depth < TARGET_DEPTH {
+				assert_ne!(sphere.relation_to_voxel(voxel), SpatialRelation::Intersecting);
+			}
+		}
+		// covered subtrees are single octants, so there is less leaves than full subdivision would create
+		assert!(filled_leaves.iter().any(|(depth, _, data)| *depth < TARGET_DEPTH && *data == STONE));
+
+		octree.carve_shape(&sphere, TARGET_DEPTH).unwrap();
+		let carved_leaves = collect_leaves(&octree);
+		assert!(carved_leaves.iter().all(|(_, _, data)| *data == GROUND));
+		assert!(carved_leaves.iter().all(|(depth, voxel, _)| !is_covered(&sphere, *depth, voxel)));
+	}
+
+	#[test]
+	fn test_fill_box_into_empty_space(){
+		let mut octree: SpatialSparseOctree<u32> = SpatialSparseOctree::new_with_root(VolumetricCube::new(Vec3A::ZERO, 1.0), GROUND);
+		let root_id: MortonOctantId = octree.get_root_id();
+		let children = octree.octants_mut().subdivide(&root_id, |_| GROUND).unwrap();
+		let empty_voxel = octree.get_voxel_by_id(&children[OctantPlacement::LOWER_BOTTOM_LEFT as usize]).unwrap();
+		octree.octants_mut().remove_octant(&children[OctantPlacement::LOWER_BOTTOM_LEFT as usize]).unwrap();
+
+		let block = VolumetricCube::new(empty_voxel.center() + Vec3A::splat(0.1), 0.3);
+		octree.fill_shape(&block, TARGET_DEPTH, STONE).unwrap();
+
+		let leaves = collect_leaves(&octree);
+		for (depth, voxel, data) in leaves.iter() {
+			if empty_voxel.contains_point(voxel.center()) {
+				// nothing but shape is created in empty space
+				assert_eq!(*data, STONE);
+				assert!(is_covered(&block, *depth, voxel));
+			}
+			else {
+				assert_eq!(*data, GROUND);
+			}
+		}
+		assert!(leaves.iter().any(|(_, _, data)| *data == STONE));
+
+		// carving whole tree leaves it empty, not as single filled root
+		octree.carve_shape(&VolumetricCube::new(Vec3A::ZERO, 2.0), TARGET_DEPTH).unwrap();
+		assert_eq!(octree.octants().get_octant(&root_id), None);
+		assert!(cell_centers().all(|point| sample(&octree, point).is_none()));
+		octree.carve_shape(&block, TARGET_DEPTH).unwrap();
+
+		// filling into empty tree creates only shape
+		octree.fill_shape(&block, TARGET_DEPTH, STONE).unwrap();
+		let cell_voxel = |point: Vec3A| VolumetricCube::new(point, 1.0 / (1 << TARGET_DEPTH) as f32);
+		for point in cell_centers() {
+			let expected: Option<u32> = is_covered(&block, TARGET_DEPTH, &cell_voxel(point)).then_some(STONE);
+			assert_eq!(sample(&octree, point), expected);
+		}
+		assert_eq!(sample(&octree, block.center()), Some(STONE));
+
+		assert!(matches!(octree.fill_shape(&block, 200, STONE), Err(StorageError::OverMaxDepth(_))));
+	}
+}
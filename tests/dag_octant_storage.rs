@@ -0,0 +1,111 @@
+#[cfg(test)]
+mod tests{
+	use modsvo::{
+		morton_based_storage::{dag_octant_storage::DagOctantStorage, hashed_octant_storage::HashedOctantStorage, morton_octant_id::MortonOctantId},
+		octant_meta::OctantPlacement,
+		octant_storage_trait::{ModifiableOctantStorage, OctantStorage},
+		octree_base::SearchControlFlow,
+		SparseOctree, SparseOctreeDag
+	};
+
+	fn collect_octants<Storage: OctantStorage<OctantId = MortonOctantId, Data = u32>>(storage: &Storage) -> Vec<(u64, u32)> {
+		let mut octants: Vec<(u64, u32)> = Vec::new();
+		modsvo::octree_base::depth_first_search_from_storage(
+			storage,
+			&storage.get_root_id(),
+			|_, octant_id: &MortonOctantId|{
+				octants.push((octant_id.as_morton(), *storage.get_octant(octant_id).unwrap()));
+				SearchControlFlow::Continue
+			}
+		).unwrap();
+		octants
+	}
+
+	/// Root with 8 equal children, every child has leaves holding their placement.
+	fn make_repetitive_octree() -> SparseOctree<u32> {
+		let mut octree: SparseOctree<u32> = SparseOctree::new_with_root(0);
+		let root_id: MortonOctantId = octree.octants.get_root_id();
+		for child_id in octree.octants.subdivide(&root_id, |_| 1).unwrap() {
+			octree.octants.subdivide(&child_id, |placement| 10 + placement as u32).unwrap();
+		}
+		octree
+	}
+
+	#[test]
+	fn test_identical_subtrees_are_shared(){
+		let octree: SparseOctree<u32> = make_repetitive_octree();
+		let dag: SparseOctreeDag<u32> = octree.to_dag().unwrap();
+
+		assert_eq!(dag.octants.octant_count(), 1 + 8 + 64);
+		// root, one shared child and 8 distinct leaves
+		assert_eq!(dag.octants.node_count(), 1 + 1 + 8);
+		assert_eq!(dag.octants.unique_data_count(), 2 + 8);
+		assert_eq!(dag.octants.compression_ratio(), 73.0 / 10.0);
+		assert!(dag.validate().is_empty());
+
+		assert_eq!(collect_octants(&dag.octants), collect_octants(&octree.octants));
+		let leaf_id: MortonOctantId = MortonOctantId::from_morton_code(0b1_101_011);
+		assert_eq!(dag.octants.get_octant(&leaf_id), Some(&(10 + 3)));
+		assert_eq!(dag.octants.get_parent(&leaf_id), Some(leaf_id.parent_id()));
+		assert_eq!(dag.octants.get_octant_depth(&leaf_id), Some(2));
+		assert_eq!(dag.octants.which_child_of(&leaf_id.parent_id(), &leaf_id).ok(), Some(OctantPlacement::UPPER_TOP_LEFT));
+		assert!(dag.octants.get_existing_child(&leaf_id, OctantPlacement::LOWER_BOTTOM_LEFT).is_err());
+		assert_eq!(dag.octants.get_octant(&leaf_id.child_id_by_placement(OctantPlacement::LOWER_BOTTOM_LEFT)), None);
+		assert!(dag.octants.estimated_heap_bytes().unwrap() > 0);
+	}
+
+	#[test]
+	fn test_convert_back_for_editing(){
+		let octree: SparseOctree<u32> = make_repetitive_octree();
+		let mut edited: SparseOctree<u32> = octree.to_dag().unwrap().to_hashed();
+		assert_eq!(sorted(collect_octants(&edited.octants)), sorted(collect_octants(&octree.octants)));
+
+		let leaf_id: MortonOctantId = MortonOctantId::from_morton_code(0b1_000_000);
+		*edited.octants.get_octant_mut(&leaf_id).unwrap() = 99;
+		let dag: SparseOctreeDag<u32> = edited.to_dag().unwrap();
+		// edited child can't be shared anymore
+		assert_eq!(dag.octants.node_count(), 1 + 2 + 9);
+		assert_eq!(dag.octants.get_octant(&leaf_id), Some(&99));
+		assert_eq!(dag.octants.get_octant(&MortonOctantId::from_morton_code(0b1_001_000)), Some(&10));
+	}
+
+	#[test]
+	fn test_custom_data_equality(){
+		let mut storage: HashedOctantStorage<f32> = HashedOctantStorage::new_with_root(0.0);
+		let root_id: MortonOctantId = storage.get_root_id();
+		storage.subdivide(&root_id, |placement| 1.0 + placement as u32 as f32 * 0.01).unwrap();
+
+		let exact: DagOctantStorage<f32> = DagOctantStorage::from_storage_with(&storage, |data| data.to_bits() as u64, |a, b| a == b).unwrap();
+		assert_eq!(exact.node_count(), 1 + 8);
+
+		let rounded: DagOctantStorage<f32> = DagOctantStorage::from_storage_with(&storage, |data| data.round() as u64, |a, b| a.round() == b.round()).unwrap();
+		assert_eq!(rounded.node_count(), 1 + 1);
+		assert_eq!(rounded.get_octant(&root_id.child_id_by_placement(OctantPlacement::UPPER_TOP_RIGHT)), Some(&1.0));
+	}
+
+	#[test]
+	fn test_storage_without_root(){
+		let mut storage: HashedOctantStorage<u32> = HashedOctantStorage::default();
+		let root_id: MortonOctantId = storage.get_root_id();
+		storage.remove_octant(&root_id).unwrap();
+
+		let dag: DagOctantStorage<u32> = DagOctantStorage::from_storage(&storage).unwrap();
+		assert_eq!(dag.node_count(), 0);
+		assert_eq!(dag.compression_ratio(), 1.0);
+		assert_eq!(dag.get_octant(&root_id), None);
+		assert_eq!(dag.to_hashed_storage().get_octant(&root_id), None);
+	}
+
+	#[test]
+	fn test_dag_is_read_only(){
+		let mut dag: DagOctantStorage<u32> = DagOctantStorage::from_storage(&HashedOctantStorage::<u32>::default()).unwrap();
+		let root_id: MortonOctantId = dag.get_root_id();
+		assert_eq!(dag.get_octant_mut(&root_id), None);
+		assert_eq!(dag.get_octant(&root_id), Some(&0));
+	}
+
+	fn sorted(mut octants: Vec<(u64, u32)>) -> Vec<(u64, u32)> {
+		octants.sort();
+		octants
+	}
+}
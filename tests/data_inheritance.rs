@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod tests{
+	use modsvo::{
+		morton_based_storage::morton_octant_id::MortonOctantId,
+		octant_meta::OctantPlacement,
+		octant_storage_trait::{ModifiableOctantStorage, OctantStorage, StorageError},
+		SparseOctree
+	};
+
+	#[test]
+	fn test_sample_at_falls_back_to_ancestor(){
+		let mut octree: SparseOctree<u32> = SparseOctree::new_with_root(1);
+		let root_id: MortonOctantId = octree.octants.get_root_id();
+		let (child_id, _) = octree.octants.insert_octant(&root_id, OctantPlacement::UPPER_TOP_RIGHT, 2).unwrap();
+
+		assert_eq!(octree.sample_at(&child_id), Some(&2));
+		let deep_id: MortonOctantId = MortonOctantId::from_morton_code(0b1_111_000_101);
+		assert_eq!(octree.inheritance_source(&deep_id), Some(child_id));
+		assert_eq!(octree.sample_at(&deep_id), Some(&2));
+		assert_eq!(octree.sample_at(&MortonOctantId::from_morton_code(0b1_011_000)), Some(&1));
+		assert_eq!(octree.sample_at(&MortonOctantId::INVALID_OCTANT_ID), None);
+
+		octree.octants.remove_octant(&root_id).unwrap();
+		assert_eq!(octree.sample_at(&deep_id), None);
+	}
+
+	#[test]
+	fn test_push_down_keeps_existing_children(){
+		let mut octree: SparseOctree<u32> = SparseOctree::new_with_root(10);
+		let root_id: MortonOctantId = octree.octants.get_root_id();
+		let (existing_id, _) = octree.octants.insert_octant(&root_id, OctantPlacement::LOWER_TOP_LEFT, 99).unwrap();
+
+		let children = octree.push_down(&root_id, |data, placement| data + placement as u32).unwrap();
+		assert_eq!(children[OctantPlacement::LOWER_TOP_LEFT as usize], existing_id);
+		assert_eq!(octree.octants.get_octant(&existing_id), Some(&99));
+		assert_eq!(octree.octants.get_octant(&children[7]), Some(&17));
+		assert_eq!(children, root_id.children_ids());
+
+		assert!(matches!(octree.push_down(&children[0].children_ids()[0], |data, _| *data), Err(StorageError::InvalidOctantId)));
+	}
+
+	#[test]
+	fn test_materialize_along_path(){
+		let mut octree: SparseOctree<u32> = SparseOctree::new_with_root(5);
+		let deep_id: MortonOctantId = MortonOctantId::from_morton_code(0b1_110_001_011);
+		let sampled_before: Option<u32> = octree.sample_at(&deep_id).copied();
+
+		assert_eq!(octree.materialize(&deep_id, |data, _| *data).unwrap(), deep_id);
+		assert_eq!(octree.octants.storage().len(), 1 + 8 * 3);
+		assert_eq!(octree.octants.get_octant(&deep_id).copied(), sampled_before);
+		// already existing octant is left as it is
+		assert_eq!(octree.materialize(&deep_id, |_, _| 0).unwrap(), deep_id);
+		assert_eq!(octree.octants.get_octant(&deep_id), Some(&5));
+
+		let too_deep_id: MortonOctantId = MortonOctantId::from_morton_code(1 << (3 * 17));
+		assert!(matches!(octree.materialize(&too_deep_id, |data, _| *data), Err(StorageError::OverMaxDepth(16))));
+	}
+}
@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod tests{
+	use modsvo::{
+		data_propagation::ChildrenData,
+		morton_based_storage::morton_octant_id::MortonOctantId,
+		octant_meta::OctantPlacement,
+		octant_storage_trait::{ModifiableOctantStorage, OctantStorage, StorageError},
+		SparseOctree
+	};
+
+	fn max_density(children: &ChildrenData<u32>) -> u32 {
+		children.iter().flatten().map(|&&density| density).max().unwrap_or(0)
+	}
+
+	/// Count of existing children, so every parent holds its own occupancy.
+	fn occupancy(children: &ChildrenData<u32>) -> u32 {
+		children.iter().flatten().count() as u32
+	}
+
+	/// Root with 8 children, first child has 8 leaves holding their placement.
+	fn make_octree() -> SparseOctree<u32> {
+		let mut octree: SparseOctree<u32> = SparseOctree::new_with_root(0);
+		let root_id: MortonOctantId = octree.octants.get_root_id();
+		let children = octree.octants.subdivide(&root_id, |_| 0).unwrap();
+		octree.octants.subdivide(&children[0], |placement| placement as u32).unwrap();
+		octree
+	}
+
+	#[test]
+	fn test_propagate_up_in_post_order(){
+		let mut octree: SparseOctree<u32> = make_octree();
+		let root_id: MortonOctantId = octree.octants.get_root_id();
+		let first_child: MortonOctantId = root_id.child_id_by_placement(OctantPlacement::LOWER_BOTTOM_LEFT);
+
+		assert_eq!(octree.propagate_up_from_root(max_density).unwrap(), 2);
+		assert_eq!(octree.octants.get_octant(&first_child), Some(&7));
+		// root already sees updated first child
+		assert_eq!(octree.octants.get_octant(&root_id), Some(&7));
+		assert_eq!(octree.octants.get_octant(&first_child.child_id_by_placement(OctantPlacement::LOWER_TOP_LEFT)), Some(&1));
+
+		octree.octants.remove_octant(&first_child.child_id_by_placement(OctantPlacement::UPPER_TOP_RIGHT)).unwrap();
+		// only branch is recomputed, root keeps its value
+		assert_eq!(octree.propagate_up(&first_child, occupancy).unwrap(), 1);
+		assert_eq!(octree.octants.get_octant(&first_child), Some(&7));
+		assert_eq!(octree.octants.get_octant(&root_id), Some(&7));
+
+		// leaf has nothing to propagate
+		let leaf_id: MortonOctantId = root_id.child_id_by_placement(OctantPlacement::UPPER_TOP_RIGHT);
+		assert_eq!(octree.propagate_up(&leaf_id, occupancy).unwrap(), 0);
+		assert!(matches!(octree.propagate_up(&leaf_id.child_id_by_placement(OctantPlacement::UPPER_TOP_RIGHT), occupancy), Err(StorageError::InvalidOctantId)));
+	}
+
+	#[test]
+	fn test_propagate_up_dirty_ancestors_only(){
+		let mut octree: SparseOctree<u32> = make_octree();
+		octree.propagate_up_from_root(max_density).unwrap();
+		let root_id: MortonOctantId = octree.octants.get_root_id();
+		let first_child: MortonOctantId = root_id.child_id_by_placement(OctantPlacement::LOWER_BOTTOM_LEFT);
+		let last_child: MortonOctantId = root_id.child_id_by_placement(OctantPlacement::UPPER_TOP_RIGHT);
+
+		let grand_children = octree.octants.subdivide(&last_child, |_| 3).unwrap();
+		*octree.octants.get_octant_mut(&grand_children[1]).unwrap() = 20;
+		let leaf_id: MortonOctantId = first_child.child_id_by_placement(OctantPlacement::UPPER_BOTTOM_LEFT);
+		*octree.octants.get_octant_mut(&leaf_id).unwrap() = 30;
+		// sibling which isn't dirty keeps its value and still counts for root
+		*octree.octants.get_octant_mut(&root_id.child_id_by_placement(OctantPlacement::UPPER_TOP_LEFT)).unwrap() = 100;
+
+		let recomputed: usize = octree.propagate_up_dirty([grand_children[1], grand_children[5], leaf_id], max_density).unwrap();
+		assert_eq!(recomputed, 3);
+		assert_eq!(octree.octants.get_octant(&last_child), Some(&20));
+		assert_eq!(octree.octants.get_octant(&first_child), Some(&30));
+		assert_eq!(octree.octants.get_octant(&root_id), Some(&100));
+
+		// parent of removed octant is recomputed itself
+		octree.octants.remove_octant(&leaf_id).unwrap();
+		assert_eq!(octree.propagate_up_dirty([first_child], max_density).unwrap(), 2);
+		assert_eq!(octree.octants.get_octant(&first_child), Some(&7));
+
+		assert!(matches!(octree.propagate_up_dirty([leaf_id], max_density), Err(StorageError::InvalidOctantId)));
+	}
+}
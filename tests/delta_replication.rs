@@ -0,0 +1,164 @@
+#[cfg(test)]
+mod tests{
+	use modsvo::{
+		delta_replication::{DeltaError, DeltaMessage, DeltaOperation, DeltaReceiver, ReplicatedOctantStorage},
+		morton_based_storage::{hashed_octant_storage::HashedOctantStorage, morton_octant_id::MortonOctantId},
+		octant_meta::OctantPlacement,
+		octant_storage_trait::{ModifiableOctantStorage, OctantStorage},
+		SparseOctree, SparseOctreeReplicated
+	};
+
+	use modsvo::conformance;
+
+	fn collect_octants(storage: &HashedOctantStorage<u32>) -> Vec<(u64, u32)> {
+		let mut octants: Vec<(u64, u32)> = storage.iter()
+			.map(|(octant_id, data)| (octant_id.as_morton(), *data))
+			.collect();
+		octants.sort();
+		octants
+	}
+
+	/// Send message through its binary form, like it would go over network.
+	fn transfer(message: &DeltaMessage<u32>) -> DeltaMessage<u32> {
+		DeltaMessage::from_bytes(&message.to_bytes()).unwrap()
+	}
+
+	#[test]
+	fn test_replicated_storage_interface_functions(){
+		let mut octant_storage: ReplicatedOctantStorage<HashedOctantStorage<u32>> = ReplicatedOctantStorage::default();
+
+		conformance::check_modifiable_octant_storage(&mut octant_storage, 3, |placement| placement as u32);
+		conformance::check_all(&mut octant_storage, |placement| placement as u32);
+	}
+
+	#[test]
+	fn test_client_follows_server(){
+		let mut server: SparseOctreeReplicated<u32> = SparseOctreeReplicated::default();
+		let mut client: SparseOctree<u32> = SparseOctree::default();
+		let mut receiver = DeltaReceiver::new();
+		let root_id: MortonOctantId = server.octants.get_root_id();
+
+		receiver.apply(&mut client.octants, &transfer(&server.octants.baseline())).unwrap();
+		assert_eq!(receiver.version(), Some(0));
+
+		let children = server.octants.subdivide(&root_id, |placement| placement as u32).unwrap();
+		server.octants.insert_octant(&children[2], OctantPlacement::UPPER_TOP_LEFT, 20).unwrap();
+		*server.octants.get_octant_mut(&children[5]).unwrap() = 50;
+		*server.octants.get_octant_mut(&children[5]).unwrap() += 1;
+		let first: DeltaMessage<u32> = transfer(server.octants.commit().unwrap());
+		assert_eq!(first.operations.len(), 3);
+		assert_eq!(first.operations[2], DeltaOperation::SetData(children[5], 51));
+
+		server.octants.remove_octant(&children[7]).unwrap();
+		// data set to removed octant isn't sent
+		let (inserted_id, _) = server.octants.insert_octant(&children[1], OctantPlacement::LOWER_BOTTOM_LEFT, 1).unwrap();
+		*server.octants.get_octant_mut(&inserted_id).unwrap() = 2;
+		server.octants.remove_octant(&children[1]).unwrap();
+		let second: DeltaMessage<u32> = transfer(server.octants.commit().unwrap());
+		assert_eq!((second.base_version, second.version), (1, 2));
+		assert!(!second.operations.iter().any(|operation| matches!(operation, DeltaOperation::SetData(..))));
+		assert!(server.octants.commit().is_none());
+
+		// messages can't be skipped, already applied ones are ignored
+		assert!(matches!(receiver.apply(&mut client.octants, &second), Err(DeltaError::VersionMismatch{expected: Some(0), found: 1})));
+		receiver.apply(&mut client.octants, &first).unwrap();
+		assert_eq!(receiver.apply(&mut client.octants, &first).unwrap(), 1);
+		receiver.apply(&mut client.octants, &second).unwrap();
+		assert_eq!(collect_octants(&client.octants), collect_octants(server.octants.storage()));
+	}
+
+	#[test]
+	fn test_late_joiner_and_acknowledgments(){
+		let storage: ReplicatedOctantStorage<HashedOctantStorage<u32>> = ReplicatedOctantStorage::default();
+		let mut server: SparseOctreeReplicated<u32> = SparseOctreeReplicated::new_with_storage(storage.with_history_limit(2));
+		let root_id: MortonOctantId = server.octants.get_root_id();
+		let mut early: (SparseOctree<u32>, DeltaReceiver) = (SparseOctree::default(), DeltaReceiver::new());
+
+		early.1.apply(&mut early.0.octants, &server.octants.baseline()).unwrap();
+		server.octants.acknowledge(1, 0);
+		let children = server.octants.subdivide(&root_id, |placement| placement as u32).unwrap();
+		server.octants.commit();
+		server.octants.subdivide(&children[4], |placement| 40 + placement as u32).unwrap();
+		server.octants.commit();
+		assert_eq!(server.octants.history_len(), 2);
+
+		for message in server.octants.updates_for(1).unwrap() {
+			early.1.apply(&mut early.0.octants, message).unwrap();
+		}
+		server.octants.acknowledge(1, early.1.version().unwrap());
+		assert_eq!(server.octants.history_len(), 0);
+		assert_eq!(server.octants.updates_for(1).map(|updates| updates.len()), Some(0));
+		assert!(server.octants.updates_for(2).is_none());
+
+		// late joiner starts from baseline, even with tree of its own
+		let mut late: (SparseOctree<u32>, DeltaReceiver) = (SparseOctree::new_with_root(7), DeltaReceiver::new());
+		late.0.octants.subdivide(&root_id, |_| 7).unwrap();
+		*server.octants.get_octant_mut(&root_id).unwrap() = 9;
+		let baseline: DeltaMessage<u32> = transfer(&server.octants.baseline());
+		assert!(baseline.is_baseline);
+		assert_eq!(baseline.version, 3);
+		late.1.apply(&mut late.0.octants, &baseline).unwrap();
+		server.octants.acknowledge(2, 3);
+		assert_eq!(collect_octants(&late.0.octants), collect_octants(server.octants.storage()));
+
+		// client lagging behind history limit needs baseline
+		for value in 0..3 {
+			*server.octants.get_octant_mut(&children[0]).unwrap() = value;
+			server.octants.commit();
+		}
+		assert_eq!(server.octants.history_len(), 2);
+		assert!(server.octants.updates_for(1).is_none());
+		server.octants.remove_client(1);
+		server.octants.remove_client(2);
+
+		early.1.apply(&mut early.0.octants, &server.octants.baseline()).unwrap();
+		assert_eq!(early.1.version(), Some(6));
+		assert_eq!(collect_octants(&early.0.octants), collect_octants(server.octants.storage()));
+	}
+
+	#[test]
+	fn test_history_without_clients(){
+		let mut server: SparseOctreeReplicated<u32> = SparseOctreeReplicated::default();
+		let root_id: MortonOctantId = server.octants.get_root_id();
+		let children = server.octants.subdivide(&root_id, |placement| placement as u32).unwrap();
+		for value in 0..5 {
+			*server.octants.get_octant_mut(&children[0]).unwrap() = value;
+			assert_eq!(server.octants.commit().map(|message| message.version), Some(value as u64 + 1));
+		}
+		assert_eq!(server.octants.history_len(), 1);
+
+		server.octants.acknowledge(1, 5);
+		for value in 0..3 {
+			*server.octants.get_octant_mut(&children[1]).unwrap() = value;
+			server.octants.commit();
+		}
+		assert_eq!(server.octants.history_len(), 3);
+		assert_eq!(server.octants.updates_for(1).map(|updates| updates.len()), Some(3));
+
+		// history of last client isn't kept after it leaves
+		server.octants.remove_client(1);
+		assert_eq!(server.octants.history_len(), 0);
+		assert!(server.octants.updates_for(1).is_none());
+	}
+
+	#[test]
+	fn test_invalid_messages(){
+		assert!(matches!(DeltaMessage::<u32>::from_bytes(b"VOX \x01\x00\x00\x00\x00"), Err(DeltaError::InvalidMessage)));
+		assert!(matches!(DeltaMessage::<u32>::from_bytes(b"MSVD\x01\x00\x00\x01\x01\x09"), Err(DeltaError::InvalidMessage)));
+		assert!(matches!(DeltaMessage::<u32>::from_bytes(b"MSVD\x01\x00\x00\x01\x01\x01\x01"), Err(DeltaError::Io(_))));
+
+		let message: DeltaMessage<u32> = DeltaMessage{
+			base_version: 300,
+			version: 301,
+			is_baseline: false,
+			operations: vec![DeltaOperation::Remove(MortonOctantId::from_morton_code(0b1_010))]
+		};
+		assert_eq!(transfer(&message), message);
+		// magic, format, flags, two byte base version, two byte version, count, tag and id
+		assert_eq!(message.to_bytes().len(), 4 + 1 + 1 + 2 + 2 + 1 + 1 + 1);
+
+		let mut storage: HashedOctantStorage<u32> = HashedOctantStorage::default();
+		let mut receiver = DeltaReceiver::new();
+		assert!(matches!(receiver.apply(&mut storage, &message), Err(DeltaError::VersionMismatch{expected: None, found: 300})));
+	}
+}
@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod tests{
+	use glam::Vec3A;
+	use modsvo::{
+		morton_based_storage::morton_octant_id::MortonOctantId,
+		octant_storage_trait::{ModifiableOctantStorage, OctantStorage},
+		voxels::voxel_cube::VolumetricCube,
+		SpatialSparseOctree
+	};
+
+	fn linear_field(point: Vec3A) -> f32 {
+		2.0 * point.x + point.y - point.z + 0.5
+	}
+
+	/// Full tree of given depth over cube with half extent 1, data of every octant is field at its center.
+	fn make_field_octree<F: Fn(Vec3A) -> f32>(depth: u8, field: F) -> SpatialSparseOctree<f32> {
+		let mut octree: SpatialSparseOctree<f32> = SpatialSparseOctree::new_with_root(VolumetricCube::new(Vec3A::ZERO, 1.0), 0.0);
+		let mut level: Vec<MortonOctantId> = vec![octree.get_root_id()];
+		for _ in 0..depth {
+			level = level.iter()
+				.flat_map(|octant_id| octree.octants_mut().subdivide(octant_id, |_| 0.0).unwrap())
+				.collect();
+		}
+		let octant_ids: Vec<MortonOctantId> = octree.octants().iter().map(|(octant_id, _)| *octant_id).collect();
+		for octant_id in octant_ids {
+			let center: Vec3A = octree.get_voxel_by_id(&octant_id).unwrap().center();
+			*octree.octants_mut().get_octant_mut(&octant_id).unwrap() = field(center);
+		}
+		octree
+	}
+
+	#[test]
+	fn test_nearest_sampling(){
+		let octree: SpatialSparseOctree<f32> = make_field_octree(2, linear_field);
+		let point: Vec3A = Vec3A::new(0.3, -0.6, 0.9);
+
+		let (_, voxel) = octree.octant_at_point(point).unwrap();
+		assert_eq!(voxel.half_extent(), 0.25);
+		assert_eq!(voxel.center(), Vec3A::new(0.25, -0.75, 0.75));
+		assert_eq!(octree.sample_nearest(point), Some(&linear_field(voxel.center())));
+		assert_eq!(octree.sample_nearest(Vec3A::new(1.5, 0.0, 0.0)), None);
+		assert_eq!(octree.sample_trilinear(Vec3A::new(0.0, -1.5, 0.0)), None);
+	}
+
+	#[test]
+	fn test_trilinear_sampling_reproduces_linear_field(){
+		let octree: SpatialSparseOctree<f32> = make_field_octree(3, linear_field);
+		for point in [Vec3A::new(0.1, -0.3, 0.42), Vec3A::new(-0.8, 0.7, 0.0), Vec3A::new(0.125, 0.125, 0.125)] {
+			let sampled: f32 = octree.sample_trilinear(point).unwrap();
+			assert!((sampled - linear_field(point)).abs() < 1e-5, "{} sampled as {}", point, sampled);
+		}
+		// outside of outermost samples field is clamped
+		assert!((octree.sample_trilinear(Vec3A::new(1.0, 0.0, 0.0)).unwrap() - linear_field(Vec3A::new(0.875, 0.0, 0.0))).abs() < 1e-5);
+	}
+
+	#[test]
+	fn test_missing_neighbors_fall_back_to_ancestors(){
+		let mut octree: SpatialSparseOctree<f32> = SpatialSparseOctree::new_with_root(VolumetricCube::new(Vec3A::ZERO, 1.0), 1.0);
+		let root_id: MortonOctantId = octree.get_root_id();
+		let children = octree.octants_mut().subdivide(&root_id, |_| 1.0).unwrap();
+		octree.octants_mut().subdivide(&children[0], |_| 0.0).unwrap();
+
+		// only sample below point on all axes lies in subdivided child
+		let sampled: f32 = octree.sample_trilinear(Vec3A::splat(-0.05)).unwrap();
+		assert!((sampled - (1.0 - 0.6 * 0.6 * 0.6)).abs() < 1e-5);
+		assert_eq!(octree.sample_trilinear(Vec3A::splat(0.5)), Some(1.0));
+	}
+
+	#[test]
+	fn test_gradient_and_normal(){
+		let octree: SpatialSparseOctree<f32> = make_field_octree(3, linear_field);
+		let gradient: Vec3A = octree.sample_gradient(Vec3A::new(0.1, 0.2, -0.3), 0.125, |value| *value).unwrap();
+		assert!((gradient - Vec3A::new(2.0, 1.0, -1.0)).length() < 1e-4);
+
+		// signed distance of plane, normal points away from ground
+		let ground: SpatialSparseOctree<f32> = make_field_octree(3, |point| point.y - 0.1);
+		let normal: Vec3A = ground.sample_normal(Vec3A::new(-0.4, 0.1, 0.6), 0.125, |distance| *distance).unwrap();
+		assert!((normal - Vec3A::Y).length() < 1e-5);
+		// samples beyond root are taken one-sided
+		let border_normal: Vec3A = ground.sample_normal(Vec3A::new(0.0, 0.1, 0.99), 0.125, |distance| *distance).unwrap();
+		assert!((border_normal - Vec3A::Y).length() < 1e-5);
+		assert_eq!(ground.sample_normal(Vec3A::splat(2.0), 0.125, |distance| *distance), None);
+	}
+
+	#[test]
+	#[should_panic]
+	fn test_gradient_step_has_to_be_positive(){
+		let octree: SpatialSparseOctree<f32> = make_field_octree(1, linear_field);
+		octree.sample_gradient(Vec3A::ZERO, 0.0, |value| *value);
+	}
+}
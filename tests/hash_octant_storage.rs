@@ -9,12 +9,21 @@ mod tests{
 
 	use modsvo::morton_based_storage::hashed_octant_storage::HashedOctantStorage;
 
-	use super::super::test_modifiable_octant_storage;
+	use modsvo::conformance;
 
 	#[test]
 	fn test_hash_storage_interface_functions(){
 		let mut octant_storage: HashedOctantStorage<u32> = HashedOctantStorage::<u32>::default();
 
-		test_modifiable_octant_storage(&mut octant_storage,3);
+		conformance::check_modifiable_octant_storage(&mut octant_storage, 3, |placement| placement as u32);
+		conformance::check_all(&mut octant_storage, |placement| placement as u32);
+	}
+
+	#[test]
+	fn test_hash_storage_interface_functions_with_owned_data(){
+		let mut octant_storage: HashedOctantStorage<String> = HashedOctantStorage::<String>::default();
+
+		conformance::check_modifiable_octant_storage(&mut octant_storage, 2, |placement| format!("{:?}", placement));
+		conformance::check_all(&mut octant_storage, |placement| format!("{:?}", placement));
 	}
 }
\ No newline at end of file
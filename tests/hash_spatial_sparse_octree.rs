@@ -12,6 +12,7 @@ mod tests{
 
 	use modsvo::morton_based_storage::morton_octant_id::MortonOctantId;
 	use modsvo::octree_base::OctantIdTypeInfo;
+	use modsvo::voxel_trait::Voxel;
 	#[test]
 	fn test_depth_first_search(){
 		type OctantId =  <SpatialSparseOctreeHashed<NoData> as OctantIdTypeInfo>::OctantId;
@@ -607,4 +608,38 @@ mod tests{
 		dbg!(MortonOctantId::from_xyz_array([0,0, u16::MAX], 16).unwrap().get_all_neighbors());
 
 	}
-}
\ No newline at end of file
+
+	#[test]
+	fn test_sub_voxels_and_neighbor_tables_follow_placement_bits(){
+		let voxel = VolumetricCube::new(Vec3A::new(1.0, -2.0, 3.0), 2.0);
+		// U, D, N, S, E, W in names of directions
+		let unit_offset = |name: char| match name {
+			'U' => Vec3A::Y,
+			'D' => Vec3A::NEG_Y,
+			'N' => Vec3A::Z,
+			'S' => Vec3A::NEG_Z,
+			'E' => Vec3A::X,
+			'W' => Vec3A::NEG_X,
+			_ => unreachable!()
+		};
+
+		for placement in OctantPlacement::OCTANTS_ORDERED {
+			let bits = placement as usize;
+			let expected_direction = Vec3A::new(
+				if bits & 0b100 != 0 { 1.0 } else { -1.0 },
+				if bits & 0b010 != 0 { 1.0 } else { -1.0 },
+				if bits & 0b001 != 0 { 1.0 } else { -1.0 }
+			);
+			let sub_voxel = voxel.make_sub_voxel(placement);
+			assert_eq!(sub_voxel.center(), voxel.center() + expected_direction, "{:?}", placement);
+			assert_eq!(voxel.get_octant_position(placement), sub_voxel.center(), "{:?}", placement);
+			assert_eq!(voxel.guess_octant(sub_voxel.center()), placement);
+
+			for (direction, neighbor_placement) in OctantPlacement::all_neighbors_for(placement) {
+				let offset: Vec3A = format!("{:?}", direction).chars().map(unit_offset).sum();
+				let neighbor_voxel = voxel.make_sub_voxel(neighbor_placement);
+				assert_eq!(neighbor_voxel.center() - sub_voxel.center(), offset * 2.0, "{:?} of {:?}", direction, placement);
+			}
+		}
+	}
+}
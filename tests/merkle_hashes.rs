@@ -0,0 +1,147 @@
+#[cfg(test)]
+mod tests{
+	use std::hash::Hasher;
+
+	use modsvo::{
+		merkle_hashes::{diff_merkle_hashes, stable_hash, MerkleChange, MerkleDifference, MerkleHashes, StableHasher},
+		morton_based_storage::morton_octant_id::MortonOctantId,
+		octant_meta::OctantPlacement,
+		octant_storage_trait::{ModifiableOctantStorage, OctantStorage},
+		SparseOctree
+	};
+
+	/// Root with 8 children, every child has 8 leaves holding their placement.
+	fn make_octree() -> SparseOctree<u32> {
+		let mut octree: SparseOctree<u32> = SparseOctree::new_with_root(0);
+		let root_id: MortonOctantId = octree.octants.get_root_id();
+		for child_id in octree.octants.subdivide(&root_id, |placement| placement as u32).unwrap() {
+			octree.octants.subdivide(&child_id, |placement| 10 + placement as u32).unwrap();
+		}
+		octree
+	}
+
+	#[test]
+	fn test_equal_trees_have_equal_hashes(){
+		let octree: SparseOctree<u32> = make_octree();
+		let hashes: MerkleHashes<MortonOctantId> = octree.merkle_hashes(stable_hash);
+		let root_id: MortonOctantId = octree.octants.get_root_id();
+
+		assert_eq!(hashes.len(), 1 + 8 + 64);
+		assert_eq!(hashes.subtree_hash(&root_id), make_octree().merkle_hashes(stable_hash).subtree_hash(&root_id));
+		// same leaves under every child, but children data differ
+		let first_child: MortonOctantId = root_id.child_id_by_placement(OctantPlacement::LOWER_BOTTOM_LEFT);
+		let last_child: MortonOctantId = root_id.child_id_by_placement(OctantPlacement::UPPER_TOP_RIGHT);
+		assert_ne!(hashes.subtree_hash(&first_child), hashes.subtree_hash(&last_child));
+		assert_eq!(
+			hashes.subtree_hash(&first_child.child_id_by_placement(OctantPlacement::UPPER_TOP_LEFT)),
+			hashes.subtree_hash(&last_child.child_id_by_placement(OctantPlacement::UPPER_TOP_LEFT))
+		);
+		assert!(diff_merkle_hashes(&octree.octants, &hashes, &make_octree().octants, &hashes).is_empty());
+	}
+
+	#[test]
+	fn test_incremental_update_matches_full_rehash(){
+		let mut octree: SparseOctree<u32> = make_octree();
+		let mut hashes: MerkleHashes<MortonOctantId> = octree.merkle_hashes(stable_hash);
+		let leaf_id: MortonOctantId = MortonOctantId::from_morton_code(0b1_011_101);
+
+		*octree.octants.get_octant_mut(&leaf_id).unwrap() = 99;
+		hashes.update_path(&octree.octants, &leaf_id, stable_hash).unwrap();
+		let recomputed: MerkleHashes<MortonOctantId> = octree.merkle_hashes(stable_hash);
+		assert_eq!(hashes.get(&octree.octants.get_root_id()), recomputed.get(&octree.octants.get_root_id()));
+
+		// inserted branch is hashed on demand from its top
+		let (inserted_id, _) = octree.octants.insert_octant(&leaf_id, OctantPlacement::LOWER_TOP_LEFT, 5).unwrap();
+		octree.octants.insert_octant(&inserted_id, OctantPlacement::LOWER_TOP_LEFT, 6).unwrap();
+		hashes.update_path(&octree.octants, &inserted_id, stable_hash).unwrap();
+		assert_eq!(hashes.len(), 1 + 8 + 64 + 2);
+		assert_eq!(hashes.get(&octree.octants.get_root_id()), octree.merkle_hashes(stable_hash).get(&octree.octants.get_root_id()));
+
+		octree.octants.remove_octant(&inserted_id).unwrap();
+		hashes.forget(&inserted_id);
+		hashes.update_path(&octree.octants, &leaf_id, stable_hash).unwrap();
+		hashes.retain_existing(&octree.octants);
+		assert_eq!(hashes.len(), recomputed.len());
+		assert_eq!(hashes.get(&octree.octants.get_root_id()), recomputed.get(&octree.octants.get_root_id()));
+
+		assert!(hashes.update_path(&octree.octants, &inserted_id, stable_hash).is_err());
+	}
+
+	#[test]
+	fn test_forget_drops_whole_branch(){
+		let mut octree: SparseOctree<u32> = make_octree();
+		let mut hashes: MerkleHashes<MortonOctantId> = octree.merkle_hashes(stable_hash);
+		let root_id: MortonOctantId = octree.octants.get_root_id();
+		let branch_id: MortonOctantId = root_id.child_id_by_placement(OctantPlacement::UPPER_BOTTOM_LEFT);
+
+		octree.octants.remove_octant(&branch_id).unwrap();
+		assert_eq!(hashes.forget(&branch_id), make_octree().merkle_hashes(stable_hash).get(&branch_id));
+		hashes.update_path(&octree.octants, &root_id, stable_hash).unwrap();
+		assert_eq!(hashes.len(), 1 + 7 + 56);
+		assert_eq!(hashes.forget(&branch_id), None);
+
+		// different branch at place of removed one isn't hashed from hashes of removed children
+		octree.octants.insert_octant(&root_id, OctantPlacement::UPPER_BOTTOM_LEFT, 4).unwrap();
+		let (child_id, _) = octree.octants.insert_octant(&branch_id, OctantPlacement::LOWER_BOTTOM_LEFT, 77).unwrap();
+		octree.octants.insert_octant(&child_id, OctantPlacement::UPPER_TOP_RIGHT, 78).unwrap();
+		hashes.update_path(&octree.octants, &branch_id, stable_hash).unwrap();
+		let recomputed: MerkleHashes<MortonOctantId> = octree.merkle_hashes(stable_hash);
+		assert_eq!(hashes.len(), recomputed.len());
+		assert_eq!(hashes.get(&root_id), recomputed.get(&root_id));
+		assert!(diff_merkle_hashes(&octree.octants, &hashes, &octree.octants, &recomputed).is_empty());
+	}
+
+	#[test]
+	fn test_diff_reports_minimal_differences(){
+		let left: SparseOctree<u32> = make_octree();
+		let mut right: SparseOctree<u32> = make_octree();
+		let root_id: MortonOctantId = right.octants.get_root_id();
+		let changed_id: MortonOctantId = MortonOctantId::from_morton_code(0b1_001_010);
+		let removed_id: MortonOctantId = root_id.child_id_by_placement(OctantPlacement::UPPER_TOP_RIGHT);
+		*right.octants.get_octant_mut(&changed_id).unwrap() = 42;
+		right.octants.remove_octant(&removed_id).unwrap();
+		let (added_id, _) = right.octants.insert_octant(&changed_id, OctantPlacement::LOWER_BOTTOM_LEFT, 1).unwrap();
+		right.octants.insert_octant(&added_id, OctantPlacement::LOWER_BOTTOM_LEFT, 2).unwrap();
+
+		let left_hashes: MerkleHashes<MortonOctantId> = left.merkle_hashes(stable_hash);
+		let right_hashes: MerkleHashes<MortonOctantId> = right.merkle_hashes(stable_hash);
+		assert_ne!(left_hashes.subtree_hash(&root_id), right_hashes.subtree_hash(&root_id));
+
+		let differences: Vec<MerkleDifference<MortonOctantId>> = diff_merkle_hashes(&left.octants, &left_hashes, &right.octants, &right_hashes);
+		assert_eq!(
+			differences,
+			vec![
+				MerkleDifference{
+					octant_id: changed_id,
+					path: vec![OctantPlacement::LOWER_TOP_LEFT, OctantPlacement::UPPER_BOTTOM_LEFT],
+					change: MerkleChange::Changed
+				},
+				MerkleDifference{
+					octant_id: added_id,
+					path: vec![OctantPlacement::LOWER_TOP_LEFT, OctantPlacement::UPPER_BOTTOM_LEFT, OctantPlacement::LOWER_BOTTOM_LEFT],
+					change: MerkleChange::Added
+				},
+				MerkleDifference{
+					octant_id: removed_id,
+					path: vec![OctantPlacement::UPPER_TOP_RIGHT],
+					change: MerkleChange::Removed
+				}
+			]
+		);
+	}
+
+	#[test]
+	fn test_stable_hash_is_deterministic(){
+		assert_eq!(stable_hash(&0u32), stable_hash(&0u32));
+		assert_ne!(stable_hash(&1u32), stable_hash(&2u32));
+		// known FNV-1a value of "a"
+		let mut hasher = StableHasher::default();
+		hasher.write(b"a");
+		assert_eq!(hasher.finish(), 0xaf63dc4c8601ec8c);
+		assert_eq!(stable_hash(&0x0102u16), {
+			let mut hasher = StableHasher::default();
+			hasher.write(&[2, 1]);
+			hasher.finish()
+		});
+	}
+}
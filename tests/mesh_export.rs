@@ -0,0 +1,111 @@
+#[cfg(test)]
+mod tests{
+	use glam::Vec3A;
+
+	use modsvo::{
+		mesh_export::{depth_color, CubeStyle, MeshFormat},
+		morton_based_storage::morton_octant_id::MortonOctantId,
+		octant_meta::OctantPlacement,
+		octant_storage_trait::ModifiableOctantStorage,
+		voxels::voxel_cube::VolumetricCube,
+		SpatialSparseOctree
+	};
+
+	/// Root subdivided into 8 children and first child subdivided again, which gives 15 leaves.
+	fn make_octree() -> SpatialSparseOctree<u8> {
+		let mut octree = SpatialSparseOctree::<u8>::new_with_root(VolumetricCube::new(Vec3A::ZERO, 1.0), 0);
+		let root_id = octree.get_root_id();
+		let children = octree.octants_mut().subdivide(&root_id, |placement| placement as u8).unwrap();
+		octree.octants_mut().subdivide(&children[0], |placement| placement as u8 + 10).unwrap();
+		octree
+	}
+
+	fn export_to_string(octree: &SpatialSparseOctree<u8>, format: MeshFormat, style: CubeStyle) -> String {
+		let mut output: Vec<u8> = Vec::new();
+		octree.export_leaves(&mut output, format, style, |depth, _| depth_color(depth)).unwrap();
+		String::from_utf8(output).unwrap()
+	}
+
+	fn header_line_count(text: &str) -> usize {
+		text.lines().position(|line| line == "end_header").unwrap() + 1
+	}
+
+	#[test]
+	fn test_export_solid_ascii_ply(){
+		let text = export_to_string(&make_octree(), MeshFormat::PlyAscii, CubeStyle::Solid);
+		assert!(text.contains("element vertex 120\n"));
+		assert!(text.contains("element face 90\n"));
+		assert!(!text.contains("element edge"));
+
+		let body: Vec<&str> = text.lines().skip(header_line_count(&text)).collect();
+		assert_eq!(body.len(), 120 + 90);
+		assert!(body[120..].iter().all(|face| face.starts_with("4 ")));
+		// faces reference only vertices of their own cube
+		let first_cube_face: Vec<u32> = body[120].split(' ').skip(1).map(|index| index.parse().unwrap()).collect();
+		assert!(first_cube_face.iter().all(|&index| index < 8));
+		// first leaf is at depth 2
+		assert!(body[0].ends_with(&format!("{} {} {}", depth_color(2)[0], depth_color(2)[1], depth_color(2)[2])));
+	}
+
+	#[test]
+	fn test_export_solid_faces_point_outwards(){
+		let octree = SpatialSparseOctree::<u8>::new_with_root(VolumetricCube::new(Vec3A::ZERO, 1.0), 0);
+		let text = export_to_string(&octree, MeshFormat::PlyAscii, CubeStyle::Solid);
+		let body: Vec<&str> = text.lines().skip(header_line_count(&text)).collect();
+		let vertices: Vec<Vec3A> = body[..8].iter()
+			.map(|line| {
+				let axes: Vec<f32> = line.split(' ').take(3).map(|axis| axis.parse().unwrap()).collect();
+				Vec3A::new(axes[0], axes[1], axes[2])
+			})
+			.collect();
+		for face in body[8..].iter() {
+			let indices: Vec<usize> = face.split(' ').skip(1).map(|index| index.parse().unwrap()).collect();
+			let [first, second, third] = [vertices[indices[0]], vertices[indices[1]], vertices[indices[2]]];
+			let normal = (second - first).cross(third - first);
+			let face_center = indices.iter().map(|&index| vertices[index]).sum::<Vec3A>() / 4.0;
+			assert!(normal.dot(face_center) > 0.0);
+		}
+	}
+
+	#[test]
+	fn test_export_wireframe_obj(){
+		let text = export_to_string(&make_octree(), MeshFormat::Obj, CubeStyle::Wireframe);
+		assert_eq!(text.lines().filter(|line| line.starts_with("v ")).count(), 120);
+		assert_eq!(text.lines().filter(|line| line.starts_with("l ")).count(), 15 * 12);
+		assert_eq!(text.lines().filter(|line| line.starts_with("f ")).count(), 0);
+		// indices are 1 based
+		assert_eq!(text.lines().find(|line| line.starts_with("l ")), Some("l 1 2"));
+	}
+
+	#[test]
+	fn test_export_binary_ply_size(){
+		let mut output: Vec<u8> = Vec::new();
+		make_octree().export_leaves(&mut output, MeshFormat::PlyBinary, CubeStyle::Wireframe, |_, &data| [data, data, data]).unwrap();
+		let header_end = output.windows(11).position(|window| window == b"end_header\n").unwrap() + 11;
+		assert!(String::from_utf8_lossy(&output[..header_end]).contains("format binary_little_endian 1.0"));
+		// vertex: 3 floats and 3 colors, edge: 2 ints
+		assert_eq!(output.len() - header_end, 120 * 15 + 180 * 8);
+	}
+
+	#[test]
+	fn test_export_leaf_centers_with_morton_ids(){
+		let octree = make_octree();
+		let mut output: Vec<u8> = Vec::new();
+		octree.export_leaf_centers_ply(&mut output, false, |_, &data| [data, 0, 0]).unwrap();
+		let text = String::from_utf8(output).unwrap();
+		assert!(text.contains("element vertex 15\n"));
+		assert!(text.contains("property double morton_id\n"));
+
+		let mut exported_ids: Vec<u64> = Vec::new();
+		for line in text.lines().skip(header_line_count(&text)) {
+			let values: Vec<&str> = line.split(' ').collect();
+			let center = Vec3A::new(values[0].parse().unwrap(), values[1].parse().unwrap(), values[2].parse().unwrap());
+			let octant_id = MortonOctantId::from_morton_code(values[6].parse().unwrap());
+			assert_eq!(octree.get_voxel_by_id(&octant_id).unwrap().center(), center);
+			exported_ids.push(octant_id.as_morton());
+		}
+		let split_child = MortonOctantId::from_morton_code(1).child_id_by_placement(OctantPlacement::OCTANTS_ORDERED[0]);
+		assert!(!exported_ids.contains(&split_child.as_morton()));
+		assert_eq!(exported_ids.len(), 15);
+	}
+}
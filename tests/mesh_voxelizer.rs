@@ -0,0 +1,113 @@
+#[cfg(test)]
+mod tests{
+	use glam::Vec3A;
+
+	use modsvo::{
+		mesh_voxelizer::{FillRule, MeshOctant, TriangleMesh},
+		morton_based_storage::morton_octant_id::MortonOctantId,
+		octant_storage_trait::{OctantStorage, StorageError},
+		octree_base::SearchControlFlow,
+		voxels::voxel_cube::VolumetricCube,
+		Depth, SpatialSparseOctree
+	};
+
+	const MAX_DEPTH: Depth = 4;
+	const INTERIOR: usize = usize::MAX;
+	const BOX_HALF_SIZE: f32 = 0.45;
+
+	fn box_mesh(half_size: f32) -> (Vec<Vec3A>, Vec<u32>) {
+		let positions: Vec<Vec3A> = (0..8)
+			.map(|corner: u32| Vec3A::new(
+				if corner & 4 != 0 { half_size } else { -half_size },
+				if corner & 2 != 0 { half_size } else { -half_size },
+				if corner & 1 != 0 { half_size } else { -half_size }
+			))
+			.collect();
+		// two triangles per face, wound counter clockwise when seen from outside
+		let indices: Vec<u32> = vec![
+			4, 6, 7, 4, 7, 5, // +x
+			0, 1, 3, 0, 3, 2, // -x
+			2, 3, 7, 2, 7, 6, // +y
+			0, 4, 5, 0, 5, 1, // -y
+			1, 5, 7, 1, 7, 3, // +z
+			0, 2, 6, 0, 6, 4  // -z
+		];
+		(positions, indices)
+	}
+
+	fn collect_octants(octree: &SpatialSparseOctree<usize>) -> Vec<(Depth, VolumetricCube, usize, bool)> {
+		let mut octants = Vec::new();
+		octree.depth_first_search_from_root(
+			&mut |depth, octant_id: &MortonOctantId, voxel: &VolumetricCube|{
+				let is_leaf = octree.octants().get_existing_children(octant_id).map(|children| children.iter().all(Option::is_none)).unwrap_or(true);
+				octants.push((depth, *voxel, *octree.octants().get_octant(octant_id).unwrap(), is_leaf));
+				SearchControlFlow::Continue
+			}
+		).unwrap();
+		octants
+	}
+
+	fn voxelize_box(fill_rule: Option<FillRule>) -> SpatialSparseOctree<usize> {
+		let (positions, indices) = box_mesh(BOX_HALF_SIZE);
+		SpatialSparseOctree::<usize>::voxelize_mesh(
+			VolumetricCube::new(Vec3A::new(0.01, 0.02, 0.03), 1.0),
+			TriangleMesh::new(&positions, &indices),
+			MAX_DEPTH,
+			fill_rule,
+			|mesh_octant, _| match mesh_octant {
+				MeshOctant::Surface(triangles) => triangles[0],
+				MeshOctant::Interior => INTERIOR
+			}
+		).unwrap()
+	}
+
+	#[test]
+	fn test_triangle_cube_intersection(){
+		let cube = VolumetricCube::new(Vec3A::ZERO, 1.0);
+		assert!(cube.intersects_triangle([Vec3A::new(-2.0, -2.0, 0.0), Vec3A::new(2.0, -2.0, 0.0), Vec3A::new(0.0, 3.0, 0.0)]));
+		assert!(cube.intersects_triangle([Vec3A::new(-0.1, 0.0, 0.0), Vec3A::new(0.1, 0.0, 0.0), Vec3A::new(0.0, 0.1, 0.0)]));
+		assert!(!cube.intersects_triangle([Vec3A::new(2.0, 0.0, 0.0), Vec3A::new(3.0, 0.0, 0.0), Vec3A::new(2.0, 1.0, 0.0)]));
+		// only triangle normal separates this triangle from corner of cube
+		assert!(!cube.intersects_triangle([Vec3A::new(3.1, 0.0, 0.0), Vec3A::new(0.0, 3.1, 0.0), Vec3A::new(0.0, 0.0, 3.1)]));
+		assert!(cube.intersects_triangle([Vec3A::new(3.1, 0.0, 0.0), Vec3A::new(0.0, 3.1, 0.0), Vec3A::new(0.0, 0.0, 3.1)].map(|vertex| vertex * 0.9)));
+	}
+
+	#[test]
+	fn test_voxelize_surface(){
+		let (positions, indices) = box_mesh(BOX_HALF_SIZE);
+		let mesh = TriangleMesh::new(&positions, &indices);
+		let octants = collect_octants(&voxelize_box(None));
+
+		for (depth, voxel, first_triangle, is_leaf) in octants.iter() {
+			assert!(voxel.intersects_triangle(mesh.triangle(*first_triangle)));
+			assert_eq!(*is_leaf, *depth == MAX_DEPTH);
+		}
+		// every surface cell at max depth is present, there are no interior cells
+		let leaves: Vec<&VolumetricCube> = octants.iter().filter(|octant| octant.3).map(|octant| &octant.1).collect();
+		assert!(leaves.iter().all(|voxel| voxel.center().abs().max_element() > BOX_HALF_SIZE - voxel.half_extent() * 2.0));
+		assert!(leaves.len() > 6 * 4 * 4);
+	}
+
+	#[test]
+	fn test_voxelize_solid_fill(){
+		for fill_rule in [FillRule::Parity, FillRule::Winding] {
+			let octants = collect_octants(&voxelize_box(Some(fill_rule)));
+			let interior: Vec<&(Depth, VolumetricCube, usize, bool)> = octants.iter().filter(|octant| octant.2 == INTERIOR).collect();
+			assert!(!interior.is_empty());
+			for (_, voxel, _, is_leaf) in interior {
+				assert!(is_leaf);
+				assert!(voxel.max().max_element() <= BOX_HALF_SIZE && voxel.min().min_element() >= -BOX_HALF_SIZE);
+			}
+			// large interior octants are kept whole
+			assert!(octants.iter().any(|octant| octant.2 == INTERIOR && octant.0 < MAX_DEPTH));
+		}
+
+		let (positions, indices) = box_mesh(BOX_HALF_SIZE);
+		let mesh = TriangleMesh::new(&positions, &indices);
+		assert!(mesh.contains_point(Vec3A::ZERO, FillRule::Parity) && mesh.contains_point(Vec3A::ZERO, FillRule::Winding));
+		assert!(!mesh.contains_point(Vec3A::splat(0.6), FillRule::Parity) && !mesh.contains_point(Vec3A::new(-0.6, 0.0, 0.0), FillRule::Winding));
+
+		let too_deep = SpatialSparseOctree::<usize>::voxelize_mesh(VolumetricCube::new(Vec3A::ZERO, 1.0), mesh, 200, None, |_, _| 0);
+		assert!(matches!(too_deep, Err(StorageError::OverMaxDepth(_))));
+	}
+}
@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod tests{
+	use glam::Vec3A;
+
+	use modsvo::{
+		morton_based_storage::morton_octant_id::MortonOctantId,
+		octant_meta::OctantPlacement,
+		octant_storage_trait::{ModifiableOctantStorage, OctantStorage},
+		voxels::voxel_cube::VolumetricCube,
+		SparseOctree, SparseOctreePersistent, SparseOctreeTransactional, SpatialSparseOctree
+	};
+
+	fn fill_octree<Storage: ModifiableOctantStorage<Data = u64>>(storage: &mut Storage) {
+		let root_id = storage.get_root_id();
+		let children = storage.subdivide(&root_id, |placement| placement as u64).unwrap();
+		storage.insert_octant(&children[0], OctantPlacement::LOWER_BOTTOM_LEFT, 10).unwrap();
+		storage.insert_octant(&children[0], OctantPlacement::UPPER_TOP_RIGHT, 11).unwrap();
+	}
+
+	#[test]
+	fn test_stats_shape(){
+		let mut octree = SparseOctree::<u64>::new_with_root(0);
+		fill_octree(&mut octree.octants);
+		let stats = octree.stats().unwrap();
+		assert_eq!(stats.octants_per_depth, [1, 8, 2]);
+		assert_eq!(stats.octant_count, 11);
+		assert_eq!(stats.leaf_count, 9);
+		assert_eq!(stats.average_branching_factor, 5.0);
+		assert_eq!(stats.child_mask_histogram.len(), 2);
+		assert_eq!(stats.child_mask_histogram.get(&0xFF), Some(&1));
+		assert_eq!(stats.child_mask_histogram.get(&0b1000_0001), Some(&1));
+	}
+
+	#[test]
+	fn test_stats_estimated_heap_bytes(){
+		let mut hashed = SparseOctree::<u64>::new_with_root(0);
+		fill_octree(&mut hashed.octants);
+		let hashed_bytes = hashed.stats().unwrap().estimated_heap_bytes;
+		let slot_bytes = std::mem::size_of::<MortonOctantId>() + std::mem::size_of::<u64>() + 1;
+		assert_eq!(hashed_bytes, hashed.octants.storage().capacity() * slot_bytes);
+		assert!(hashed_bytes >= 11 * slot_bytes);
+
+		let mut persistent = SparseOctreePersistent::<u64>::new_with_root(0);
+		fill_octree(&mut persistent.octants);
+		let persistent_bytes = persistent.stats().unwrap().estimated_heap_bytes;
+		assert_eq!(persistent_bytes % 11, 0);
+		assert!(persistent_bytes / 11 > std::mem::size_of::<u64>() + 8 * std::mem::size_of::<usize>());
+
+		// history of edits is counted on top of wrapped storage
+		let mut transactional = SparseOctreeTransactional::<u64>::new_with_root(0);
+		fill_octree(&mut transactional.octants);
+		let transactional_stats = transactional.stats().unwrap();
+		assert_eq!(transactional_stats.octant_count, 11);
+		assert!(transactional_stats.estimated_heap_bytes > transactional.octants.memory_usage());
+	}
+
+	#[test]
+	fn test_spatial_stats_volume_per_depth(){
+		let mut octree = SpatialSparseOctree::<u64>::new_with_root(VolumetricCube::new(Vec3A::ZERO, 2.0), 0);
+		fill_octree(octree.octants_mut());
+		let stats = octree.stats().unwrap();
+		assert_eq!(stats.octree.octant_count, 11);
+		assert_eq!(stats.volume_per_depth, [64.0, 64.0, 2.0]);
+	}
+
+	#[test]
+	fn test_stats_of_empty_storage(){
+		let mut octree = SparseOctree::<u64>::new_with_root(0);
+		let root_id = octree.octants.get_root_id();
+		octree.octants.remove_octant(&root_id);
+		let stats = octree.stats().unwrap();
+		assert_eq!(stats.octant_count, 0);
+		assert!(stats.octants_per_depth.is_empty());
+	}
+}
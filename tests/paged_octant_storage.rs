@@ -0,0 +1,185 @@
+#[cfg(test)]
+mod tests{
+	use std::io::ErrorKind;
+	use std::path::PathBuf;
+
+	use modsvo::{
+		morton_based_storage::{morton_octant_id::MortonOctantId, paged_octant_storage::PagedOctantStorage},
+		octant_meta::OctantPlacement,
+		octant_storage_trait::{ModifiableOctantStorage, OctantStorage},
+		octree_base::SearchControlFlow,
+		SparseOctreePaged
+	};
+
+	use modsvo::conformance;
+
+	/// Empty directory for single test, removed again by `remove_test_directory`.
+	fn test_directory(name: &str) -> PathBuf {
+		let directory: PathBuf = std::env::temp_dir().join(format!("modsvo_paged_{}_{}", name, std::process::id()));
+		let _ = std::fs::remove_dir_all(&directory);
+		directory
+	}
+
+	fn remove_test_directory(directory: &PathBuf) {
+		std::fs::remove_dir_all(directory).unwrap();
+	}
+
+	/// Ids and data of all octants in depth first order.
+	fn collect_octants(octree: &SparseOctreePaged<u32>) -> Vec<(u64, u32)> {
+		let mut octants: Vec<(u64, u32)> = Vec::new();
+		octree.depth_first_search_from_root(
+			|_, octant_id: &MortonOctantId|{
+				octants.push((octant_id.as_morton(), *octree.octants.get_octant(octant_id).unwrap()));
+				SearchControlFlow::Continue
+			}
+		).unwrap();
+		octants
+	}
+
+	/// Full tree of depth 3, data of every octant is its Morton code.
+	fn build_octree(octree: &mut SparseOctreePaged<u32>) {
+		let root_id: MortonOctantId = octree.octants.get_root_id();
+		for child_id in octree.octants.subdivide(&root_id, |_| 0).unwrap() {
+			for grand_child_id in octree.octants.subdivide(&child_id, |_| 0).unwrap() {
+				octree.octants.subdivide(&grand_child_id, |_| 0).unwrap();
+			}
+		}
+		octree.depth_first_search_from_root_mut(
+			|_, octant_id: &MortonOctantId, storage|{
+				*storage.get_octant_mut(octant_id).unwrap() = octant_id.as_morton() as u32;
+				SearchControlFlow::Continue
+			}
+		).unwrap();
+	}
+
+	#[test]
+	fn test_paged_storage_interface_functions(){
+		let directory: PathBuf = test_directory("interface");
+		{
+			let mut octant_storage: PagedOctantStorage<u32> = PagedOctantStorage::create(&directory, 2, 3, 0).unwrap();
+
+			conformance::check_modifiable_octant_storage(&mut octant_storage, 3, |placement| placement as u32);
+			conformance::check_all(&mut octant_storage, |placement| placement as u32);
+		}
+		remove_test_directory(&directory);
+	}
+
+	#[test]
+	fn test_flush_and_reopen(){
+		let directory: PathBuf = test_directory("reopen");
+		let built_octants: Vec<(u64, u32)> = {
+			let storage: PagedOctantStorage<u32> = PagedOctantStorage::create(&directory, 2, 4, 0).unwrap();
+			let mut octree: SparseOctreePaged<u32> = SparseOctreePaged::new_with_storage(storage);
+			build_octree(&mut octree);
+			assert!(octree.octants.cached_page_count() <= 4);
+			octree.octants.flush().unwrap();
+			// root page and pages of all octants at depth 2
+			assert_eq!(octree.octants.page_count(), 1 + 64);
+			collect_octants(&octree)
+		};
+		assert_eq!(built_octants.len(), 1 + 8 + 64 + 512);
+
+		let storage: PagedOctantStorage<u32> = PagedOctantStorage::open(&directory, 2).unwrap();
+		assert_eq!(storage.page_depth(), 2);
+		assert_eq!(storage.cached_page_count(), 0);
+		let mut octree: SparseOctreePaged<u32> = SparseOctreePaged::new_with_storage(storage);
+		assert_eq!(collect_octants(&octree), built_octants);
+
+		// modifications are written back on drop
+		let modified_id: MortonOctantId = MortonOctantId::from_morton_code(built_octants[3].0);
+		let removed_id: MortonOctantId = MortonOctantId::from_morton_code(built_octants.last().unwrap().0).parent_id().parent_id();
+		*octree.octants.get_octant_mut(&modified_id).unwrap() = 7;
+		octree.octants.remove_octant(&removed_id).unwrap();
+		assert!(octree.octants.cached_page_count() <= 2);
+		drop(octree);
+
+		let storage: PagedOctantStorage<u32> = PagedOctantStorage::open(&directory, 2).unwrap();
+		assert_eq!(storage.page_count(), 1 + 56);
+		let page_files: usize = std::fs::read_dir(&directory).unwrap()
+			.filter(|entry| entry.as_ref().unwrap().file_name().to_string_lossy().starts_with("page_"))
+			.count();
+		assert_eq!(page_files, 1 + 56);
+		assert_eq!(storage.get_octant(&modified_id), Some(&7));
+		assert_eq!(storage.get_octant(&removed_id), None);
+		let octree: SparseOctreePaged<u32> = SparseOctreePaged::new_with_storage(storage);
+		assert_eq!(collect_octants(&octree).len(), 1 + 7 + 56 + 448);
+		drop(octree);
+
+		assert_eq!(PagedOctantStorage::<u32>::create(&directory, 2, 2, 0).err().map(|error| error.kind()), Some(std::io::ErrorKind::AlreadyExists));
+		remove_test_directory(&directory);
+	}
+
+	#[test]
+	fn test_pages_evicted_during_immutable_access(){
+		let directory: PathBuf = test_directory("eviction");
+		{
+			let storage: PagedOctantStorage<u32> = PagedOctantStorage::create(&directory, 2, 2, 0).unwrap();
+			let mut octree: SparseOctreePaged<u32> = SparseOctreePaged::new_with_storage(storage);
+			build_octree(&mut octree);
+
+			// references stay valid while pages are evicted under them
+			let first_leaf: MortonOctantId = MortonOctantId::from_morton_code(0b1_000_000_000);
+			let last_leaf: MortonOctantId = MortonOctantId::from_morton_code(0b1_111_111_111);
+			let first_data: &u32 = octree.octants.get_octant(&first_leaf).unwrap();
+			let visited: usize = collect_octants(&octree).len();
+			let last_data: &u32 = octree.octants.get_octant(&last_leaf).unwrap();
+			assert_eq!((*first_data, *last_data), (first_leaf.as_morton() as u32, last_leaf.as_morton() as u32));
+			assert_eq!(visited, 1 + 8 + 64 + 512);
+			assert!(octree.octants.cached_page_count() <= 2);
+			assert!(octree.octants.estimated_heap_bytes().unwrap() > 0);
+
+			// retired pages are reused, so repeated traversals don't hold more than every page once
+			let resident_pages: usize = octree.octants.resident_page_count();
+			assert!(resident_pages > octree.octants.cached_page_count() && resident_pages <= octree.octants.page_count());
+			for _ in 0..3 {
+				assert_eq!(collect_octants(&octree).len(), visited);
+			}
+			assert_eq!(octree.octants.resident_page_count(), resident_pages);
+			assert_eq!(*first_data, first_leaf.as_morton() as u32);
+
+			// retired pages are dropped on mutable access
+			octree.octants.flush().unwrap();
+			assert!(octree.octants.resident_page_count() <= 2);
+		}
+		remove_test_directory(&directory);
+	}
+
+	#[test]
+	fn test_io_errors_are_returned_by_flush(){
+		let directory: PathBuf = test_directory("io_errors");
+		let root_id: MortonOctantId = MortonOctantId::ROOT_OCTANT_ID;
+		// every octant has its own page, cache holds two of them
+		let mut storage: PagedOctantStorage<u32> = PagedOctantStorage::create(&directory, 1, 2, 0).unwrap();
+		let (child_id, _) = storage.insert_octant(&root_id, OctantPlacement::UPPER_TOP_RIGHT, 1).unwrap();
+		storage.flush().unwrap();
+
+		// evicted page which can't be written back is kept in memory instead of losing its modifications
+		std::fs::remove_dir_all(&directory).unwrap();
+		*storage.get_octant_mut(&root_id).unwrap() = 5;
+		*storage.get_octant_mut(&child_id).unwrap() = 7;
+		let (other_child_id, _) = storage.insert_octant(&root_id, OctantPlacement::LOWER_BOTTOM_LEFT, 3).unwrap();
+		assert_eq!(storage.resident_page_count(), 3);
+		assert_eq!((storage.get_octant(&root_id), storage.get_octant(&child_id)), (Some(&5), Some(&7)));
+		assert_eq!(storage.flush().err().map(|error| error.kind()), Some(ErrorKind::NotFound));
+		std::fs::create_dir_all(&directory).unwrap();
+		storage.flush().unwrap();
+		drop(storage);
+		let storage: PagedOctantStorage<u32> = PagedOctantStorage::open(&directory, 1).unwrap();
+		assert_eq!(storage.get_octant(&root_id), Some(&5));
+		assert_eq!(storage.get_octant(&child_id), Some(&7));
+		assert_eq!(storage.get_octant(&other_child_id), Some(&3));
+		drop(storage);
+
+		// octants of damaged page are missing, header isn't trusted with size of page
+		let child_page: PathBuf = directory.join(format!("page_{:016x}.bin", child_id.as_morton()));
+		std::fs::write(&child_page, u64::MAX.to_le_bytes()).unwrap();
+		let mut storage: PagedOctantStorage<u32> = PagedOctantStorage::open(&directory, 1).unwrap();
+		assert_eq!(storage.get_octant(&child_id), None);
+		assert!(storage.insert_octant(&child_id, OctantPlacement::LOWER_BOTTOM_LEFT, 2).is_err());
+		assert_eq!(storage.get_octant(&root_id), Some(&5));
+		assert_eq!(storage.flush().err().map(|error| error.kind()), Some(ErrorKind::InvalidData));
+		storage.flush().unwrap();
+		drop(storage);
+		remove_test_directory(&directory);
+	}
+}
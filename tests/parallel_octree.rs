@@ -0,0 +1,151 @@
+#[cfg(all(test, feature = "rayon"))]
+mod tests{
+	use std::sync::Mutex;
+
+	use glam::Vec3A;
+
+	use modsvo::{
+		morton_based_storage::morton_octant_id::MortonOctantId,
+		octant_storage_trait::OctantStorage,
+		octree_base::{SearchControlFlow, SearchControlFlowResult, SubdivisionControlFlow},
+		voxels::voxel_cube::VolumetricCube,
+		Depth, SparseOctreeHashed, SpatialSparseOctreeHashed
+	};
+
+	const SPLIT_DEPTH: Depth = 2;
+
+	fn flatten_result(result: SearchControlFlowResult<MortonOctantId>) -> (u8, MortonOctantId) {
+		match result {
+			SearchControlFlowResult::Continue(octant_id) => (0, octant_id),
+			SearchControlFlowResult::Break(octant_id) => (1, octant_id),
+			SearchControlFlowResult::Skip(octant_id) => (2, octant_id)
+		}
+	}
+
+	fn build_uneven_octree() -> SparseOctreeHashed<u32> {
+		let mut octree: SparseOctreeHashed<u32> = SparseOctreeHashed::default();
+		octree.subdivide_if_from_root(
+			|depth, octant_id: &MortonOctantId, _|{
+				if depth < 5 && !octant_id.as_morton().is_multiple_of(3) {
+					SubdivisionControlFlow::Continue(move |_| depth as u32 + 1)
+				}
+				else {
+					SubdivisionControlFlow::Skip
+				}
+			}
+		).unwrap();
+		octree
+	}
+
+	#[test]
+	fn test_par_depth_first_search_matches_sequential(){
+		let octree = build_uneven_octree();
+
+		let mut sequential_order: Vec<MortonOctantId> = Vec::new();
+		octree.depth_first_search_from_root(
+			|_, octant_id|{
+				sequential_order.push(*octant_id);
+				SearchControlFlow::Continue
+			}
+		).unwrap();
+
+		let parallel_leaves: Vec<MortonOctantId> = octree.par_leaves(SPLIT_DEPTH).into_iter().map(|(_, octant_id)| octant_id).collect();
+		let sequential_leaves: Vec<MortonOctantId> = sequential_order.iter()
+			.copied()
+			.filter(|octant_id| octree.octants.get_existing_children(octant_id).unwrap().iter().all(Option::is_none))
+			.collect();
+		assert_eq!(parallel_leaves, sequential_leaves);
+
+		for break_at in [0, 1, sequential_order.len() / 3, sequential_order.len() - 1] {
+			let break_id: MortonOctantId = sequential_order[break_at];
+			let search_func = |_, octant_id: &MortonOctantId|{
+				if *octant_id == break_id { SearchControlFlow::Break }
+				else if octant_id.as_morton().is_multiple_of(7) { SearchControlFlow::Skip }
+				else { SearchControlFlow::Continue }
+			};
+			let sequential_result = octree.depth_first_search_from_root(search_func).unwrap();
+			let parallel_result = octree.par_depth_first_search_from_root(SPLIT_DEPTH, search_func).unwrap();
+			assert_eq!(flatten_result(parallel_result), flatten_result(sequential_result));
+		}
+
+		let sequential_result = octree.depth_first_search_from_root(|_, _| SearchControlFlow::Continue).unwrap();
+		let parallel_result = octree.par_depth_first_search_from_root(SPLIT_DEPTH, |_, _| SearchControlFlow::Continue).unwrap();
+		assert_eq!(flatten_result(sequential_result), (0, *sequential_order.last().unwrap()));
+		assert_eq!(flatten_result(parallel_result), (0, *sequential_order.last().unwrap()));
+	}
+
+	#[test]
+	fn test_par_subdivide_if_matches_sequential(){
+		let sphere_center = Vec3A::new(0.3, -0.2, 0.1);
+		let touches_sphere = |voxel: &VolumetricCube| (voxel.center() - sphere_center).length() < 0.4 + voxel.half_extent() * 1.8;
+
+		let root_voxel = VolumetricCube::new(Vec3A::ZERO, 1.0);
+		let mut sequential: SpatialSparseOctreeHashed<u32> = SpatialSparseOctreeHashed::new_with_root(root_voxel, 0);
+		let root_id: MortonOctantId = sequential.get_root_id();
+		sequential.subdivide_if(
+			&root_id,
+			&root_voxel,
+			|depth, _, voxel, _|{
+				if depth < 6 && touches_sphere(voxel) { SubdivisionControlFlow::Continue(move |placement| placement as u32 + depth as u32) }
+				else { SubdivisionControlFlow::Skip }
+			}
+		).unwrap();
+
+		let mut parallel: SpatialSparseOctreeHashed<u32> = SpatialSparseOctreeHashed::new_with_root(root_voxel, 0);
+		parallel.par_subdivide_if_from_root(
+			SPLIT_DEPTH,
+			|depth, _, voxel, _|{
+				if depth < 6 && touches_sphere(voxel) { SubdivisionControlFlow::Continue(move |placement| placement as u32 + depth as u32) }
+				else { SubdivisionControlFlow::Skip }
+			}
+		).unwrap();
+
+		let sequential_leaves = sequential.par_leaves(SPLIT_DEPTH);
+		let parallel_leaves = parallel.par_leaves(SPLIT_DEPTH);
+		assert_eq!(sequential_leaves.len(), parallel_leaves.len());
+		for ((sequential_depth, sequential_id, sequential_voxel), (parallel_depth, parallel_id, parallel_voxel)) in sequential_leaves.iter().zip(parallel_leaves.iter()) {
+			assert_eq!(sequential_depth, parallel_depth);
+			assert_eq!(sequential_id, parallel_id);
+			assert_eq!(sequential_voxel.center(), parallel_voxel.center());
+			assert_eq!(sequential.octants().get_octant(sequential_id), parallel.octants().get_octant(parallel_id));
+		}
+	}
+
+	#[test]
+	fn test_par_subdivide_if_stops_at_break(){
+		let branch_id: MortonOctantId = MortonOctantId::ROOT_OCTANT_ID.children_ids()[3].children_ids()[5];
+		let break_id: MortonOctantId = branch_id.children_ids()[2];
+		let predicate = |depth: u8, octant_id: &MortonOctantId|{
+			if *octant_id == break_id { SubdivisionControlFlow::Break }
+			else if depth < 5 { SubdivisionControlFlow::Continue(move |_| depth as u32 + 1) }
+			else { SubdivisionControlFlow::Skip }
+		};
+
+		let mut sequential: SparseOctreeHashed<u32> = SparseOctreeHashed::default();
+		let sequential_result = sequential.subdivide_if_from_root(|depth, octant_id, _| predicate(depth, octant_id)).unwrap();
+
+		let evaluated: Mutex<Vec<MortonOctantId>> = Mutex::new(Vec::new());
+		let mut parallel: SparseOctreeHashed<u32> = SparseOctreeHashed::default();
+		let parallel_result = parallel.par_subdivide_if_from_root(
+			SPLIT_DEPTH,
+			|depth, octant_id, _|{
+				evaluated.lock().unwrap().push(*octant_id);
+				predicate(depth, octant_id)
+			}
+		).unwrap();
+
+		assert_eq!(flatten_result(parallel_result), (1, break_id));
+		assert_eq!(flatten_result(sequential_result), (1, break_id));
+		let mut sequential_octants: Vec<(u64, u32)> = sequential.octants.iter().map(|(octant_id, data)| (octant_id.as_morton(), *data)).collect();
+		let mut parallel_octants: Vec<(u64, u32)> = parallel.octants.iter().map(|(octant_id, data)| (octant_id.as_morton(), *data)).collect();
+		sequential_octants.sort();
+		parallel_octants.sort();
+		assert_eq!(parallel_octants, sequential_octants);
+
+		// branch which broke didn't evaluate anything after its break
+		let evaluated: Vec<MortonOctantId> = evaluated.into_inner().unwrap();
+		assert!(evaluated.contains(&break_id));
+		assert!(!branch_id.children_ids()[3..].iter().any(|sibling_id| evaluated.contains(sibling_id)));
+		assert!(!evaluated.iter().any(|octant_id| octant_id.compute_depth() > break_id.compute_depth() && octant_id.as_morton() >> (3 * (octant_id.compute_depth() - SPLIT_DEPTH)) == branch_id.as_morton()));
+	}
+}
@@ -0,0 +1,122 @@
+#[cfg(test)]
+mod tests{
+	use glam::Vec3A;
+	use modsvo::{
+		morton_based_storage::morton_octant_id::MortonOctantId,
+		octant_meta::OctantPlacement,
+		octant_storage_trait::{ModifiableOctantStorage, OctantStorage},
+		pathfinding::{NeighborConnectivity, OctreePath, PathfindingOptions},
+		voxels::voxel_cube::VolumetricCube,
+		SpatialSparseOctree
+	};
+
+	const FREE: u8 = 0;
+	const BLOCKED: u8 = 1;
+
+	fn is_free(data: &u8, _: &VolumetricCube) -> bool {
+		*data == FREE
+	}
+
+	/// Grid of 4x4x4 unit cells around origin, cells for which `blocked` returns true hold `BLOCKED`.
+	fn make_grid<F: Fn(Vec3A) -> bool>(blocked: F) -> SpatialSparseOctree<u8> {
+		let mut octree: SpatialSparseOctree<u8> = SpatialSparseOctree::new_with_root(VolumetricCube::new(Vec3A::ZERO, 2.0), FREE);
+		let root_id: MortonOctantId = octree.get_root_id();
+		for child_id in octree.octants_mut().subdivide(&root_id, |_| FREE).unwrap() {
+			octree.octants_mut().subdivide(&child_id, |_| FREE).unwrap();
+		}
+		let octant_ids: Vec<MortonOctantId> = octree.octants().iter().map(|(octant_id, _)| *octant_id).collect();
+		for octant_id in octant_ids {
+			let voxel: VolumetricCube = octree.get_voxel_by_id(&octant_id).unwrap();
+			if voxel.half_extent() == 0.5 && blocked(voxel.center()) {
+				*octree.octants_mut().get_octant_mut(&octant_id).unwrap() = BLOCKED;
+			}
+		}
+		octree
+	}
+
+	#[test]
+	fn test_neighbors_across_depths(){
+		let mut octree: SpatialSparseOctree<u8> = SpatialSparseOctree::new_with_root(VolumetricCube::new(Vec3A::ZERO, 1.0), FREE);
+		let root_id: MortonOctantId = octree.get_root_id();
+		let children = octree.octants_mut().subdivide(&root_id, |_| FREE).unwrap();
+		let grand_children = octree.octants_mut().subdivide(&children[0], |_| FREE).unwrap();
+
+		let coarse_voxel: VolumetricCube = octree.get_voxel_by_id(&children[OctantPlacement::LOWER_BOTTOM_RIGHT as usize]).unwrap();
+		let neighbors = octree.leaf_neighbors(&coarse_voxel, NeighborConnectivity::Faces);
+		// four small leaves of subdivided child on west side, one leaf to north and one up
+		assert_eq!(neighbors.len(), 4 + 1 + 1);
+		assert_eq!(neighbors.iter().filter(|(_, voxel)| voxel.half_extent() == 0.25).count(), 4);
+
+		let fine_voxel: VolumetricCube = octree.get_voxel_by_id(&grand_children[OctantPlacement::LOWER_BOTTOM_RIGHT as usize]).unwrap();
+		let neighbors = octree.leaf_neighbors(&fine_voxel, NeighborConnectivity::Faces);
+		assert_eq!(neighbors.len(), 4);
+		assert!(neighbors.iter().any(|(octant_id, _)| *octant_id == children[OctantPlacement::LOWER_BOTTOM_RIGHT as usize]));
+
+		let edges: usize = octree.leaf_neighbors(&fine_voxel, NeighborConnectivity::Edges).len();
+		let corners: usize = octree.leaf_neighbors(&fine_voxel, NeighborConnectivity::Corners).len();
+		assert!(4 < edges && edges < corners);
+		// all siblings touch fine leaf, from larger octants only the one on east side does
+		assert_eq!(corners, 7 + 1);
+	}
+
+	#[test]
+	fn test_path_through_hole_in_wall(){
+		// wall at x between 0 and 1 with single hole
+		let hole: Vec3A = Vec3A::new(0.5, 1.5, 1.5);
+		let octree: SpatialSparseOctree<u8> = make_grid(|center| center.x == 0.5 && center != hole);
+		let start: Vec3A = Vec3A::new(-1.5, -1.5, -1.5);
+		let goal: Vec3A = Vec3A::new(1.5, -1.5, -1.5);
+
+		for connectivity in [NeighborConnectivity::Faces, NeighborConnectivity::Edges, NeighborConnectivity::Corners] {
+			let options = PathfindingOptions{connectivity, smooth: false};
+			let path: OctreePath<MortonOctantId> = octree.find_path(start, goal, options, is_free).unwrap();
+			assert_eq!(path.waypoints.first(), Some(&start));
+			assert_eq!(path.waypoints.last(), Some(&goal));
+			assert_eq!(path.waypoints.len(), path.octants.len());
+			assert!(path.waypoints.contains(&hole));
+			for octant_id in &path.octants {
+				assert_eq!(octree.octants().get_octant(octant_id), Some(&FREE));
+			}
+		}
+		let face_path = octree.find_path(start, goal, PathfindingOptions::default(), is_free).unwrap();
+		let corner_path = octree.find_path(start, goal, PathfindingOptions{connectivity: NeighborConnectivity::Corners, smooth: false}, is_free).unwrap();
+		// 8 steps to hole and 7 steps from it
+		assert_eq!(face_path.octants.len(), 1 + 8 + 7);
+		assert!(corner_path.length < face_path.length);
+	}
+
+	#[test]
+	fn test_unreachable_and_blocked_endpoints(){
+		let octree: SpatialSparseOctree<u8> = make_grid(|center| center.x == 0.5);
+		let options = PathfindingOptions{connectivity: NeighborConnectivity::Corners, smooth: true};
+		assert!(octree.find_path(Vec3A::splat(-1.5), Vec3A::splat(1.5), options, is_free).is_none());
+		assert!(octree.find_path(Vec3A::splat(-1.5), Vec3A::new(0.5, 0.0, 0.0), options, is_free).is_none());
+		assert!(octree.find_path(Vec3A::splat(-1.5), Vec3A::splat(3.0), options, is_free).is_none());
+
+		let path: OctreePath<MortonOctantId> = octree.find_path(Vec3A::splat(-1.2), Vec3A::splat(-1.4), options, is_free).unwrap();
+		assert_eq!(path.octants.len(), 1);
+		assert_eq!(path.waypoints, vec![Vec3A::splat(-1.2), Vec3A::splat(-1.4)]);
+	}
+
+	#[test]
+	fn test_smoothing_pulls_string(){
+		let octree: SpatialSparseOctree<u8> = make_grid(|_| false);
+		let start: Vec3A = Vec3A::new(-1.5, -1.5, -1.5);
+		let goal: Vec3A = Vec3A::new(1.5, 1.5, -1.5);
+
+		let staircase = octree.find_path(start, goal, PathfindingOptions::default(), is_free).unwrap();
+		assert_eq!(staircase.waypoints.len(), 7);
+		assert!((staircase.length - 6.0).abs() < 1e-5);
+
+		let smooth = octree.find_path(start, goal, PathfindingOptions{smooth: true, ..Default::default()}, is_free).unwrap();
+		assert_eq!(smooth.octants, staircase.octants);
+		assert_eq!(smooth.waypoints, vec![start, goal]);
+		assert!((smooth.length - start.distance(goal)).abs() < 1e-5);
+
+		// line of sight is blocked by obstacle in the middle
+		let octree: SpatialSparseOctree<u8> = make_grid(|center| center.x.abs() == 0.5 && center.y.abs() == 0.5 && center.z == -1.5);
+		let around = octree.find_path(start, goal, PathfindingOptions{smooth: true, ..Default::default()}, is_free).unwrap();
+		assert!(around.waypoints.len() > 2);
+		assert!(around.length > start.distance(goal));
+	}
+}
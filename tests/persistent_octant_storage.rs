@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod tests{
+	use glam::Vec3A;
+
+	use modsvo::{
+		morton_based_storage::{morton_octant_id::MortonOctantId, persistent_octant_storage::PersistentOctantStorage},
+		octant_meta::OctantPlacement,
+		octant_storage_trait::{ModifiableOctantStorage, OctantStorage},
+		octree_base::SearchControlFlow,
+		voxels::voxel_cube::VolumetricCube,
+		SparseOctreePersistent, SpatialSparseOctreePersistent
+	};
+
+	use modsvo::conformance;
+
+	fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+	#[test]
+	fn test_persistent_storage_interface_functions(){
+		let mut octant_storage: PersistentOctantStorage<u32> = PersistentOctantStorage::<u32>::default();
+
+		conformance::check_modifiable_octant_storage(&mut octant_storage, 3, |placement| placement as u32);
+		conformance::check_all(&mut octant_storage, |placement| placement as u32);
+	}
+
+	#[test]
+	fn test_snapshot_isolation_and_sharing(){
+		let mut octree: SparseOctreePersistent<u32> = SparseOctreePersistent::default();
+		let root_id: MortonOctantId = octree.octants.get_root_id();
+		let children = octree.octants.subdivide(&root_id, |placement| placement as u32).unwrap();
+		octree.octants.subdivide(&children[1], |_| 10).unwrap();
+		octree.octants.subdivide(&children[6], |_| 20).unwrap();
+
+		let snapshot = octree.snapshot();
+		assert_send_sync(&snapshot);
+
+		*octree.octants.get_octant_mut(&children[1].children_ids()[3]).unwrap() = 99;
+		octree.octants.remove_octant(&children[0]).unwrap();
+
+		// snapshot keeps old state
+		assert_eq!(snapshot.octants.get_octant(&children[1].children_ids()[3]), Some(&10));
+		assert_eq!(snapshot.octants.get_octant(&children[0]), Some(&0));
+		assert_eq!(octree.octants.get_octant(&children[1].children_ids()[3]), Some(&99));
+		assert_eq!(octree.octants.get_octant(&children[0]), None);
+
+		// only path to modified octant was copied
+		assert!(!octree.octants.shares_octant_with(&snapshot.octants, &root_id));
+		assert!(!octree.octants.shares_octant_with(&snapshot.octants, &children[1]));
+		assert!(octree.octants.shares_octant_with(&snapshot.octants, &children[6]));
+		assert!(octree.octants.shares_octant_with(&snapshot.octants, &children[1].children_ids()[0]));
+
+		let removed = octree.octants.remove_octant_and_collect(&children[6]).unwrap();
+		assert_eq!(removed.len(), OctantPlacement::OCTANTS_COUNT + 1);
+		assert_eq!(snapshot.octants.get_octant(&children[6].children_ids()[7]), Some(&20));
+	}
+
+	#[test]
+	fn test_snapshot_traversal_from_other_thread(){
+		let mut octree: SpatialSparseOctreePersistent<u32> = SpatialSparseOctreePersistent::new_with_root(VolumetricCube::new(Vec3A::ZERO, 1.0), 0);
+		let root_id: MortonOctantId = octree.get_root_id();
+		let children = octree.octants_mut().subdivide(&root_id, |_| 1).unwrap();
+		octree.octants_mut().subdivide(&children[2], |_| 2).unwrap();
+
+		let snapshot = octree.snapshot();
+		let reader = std::thread::spawn(
+			move ||{
+				let mut visited: usize = 0;
+				snapshot.depth_first_search_from_root(
+					&mut |_, _, _|{
+						visited += 1;
+						SearchControlFlow::Continue
+					}
+				).unwrap();
+				visited
+			}
+		);
+
+		octree.octants_mut().remove_octant(&children[2]).unwrap();
+		assert_eq!(reader.join().unwrap(), 1 + 2 * OctantPlacement::OCTANTS_COUNT);
+	}
+}
@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests{
+	use glam::Vec3A;
+
+	use modsvo::{
+		morton_based_storage::morton_octant_id::MortonOctantId,
+		octant_storage_trait::{OctantStorage, StorageError},
+		octree_base::SearchControlFlow,
+		point_cloud::{ColoredPoint, PointReducer, PointStatistics, PointStatisticsReducer},
+		voxels::voxel_cube::VolumetricCube,
+		Depth, SpatialSparseOctree
+	};
+
+	const MAX_DEPTH: Depth = 6;
+	const MIN_POINTS: usize = 8;
+
+	/// Deterministic points, most of them in dense cluster in one corner.
+	fn generate_points(count: usize) -> Vec<ColoredPoint> {
+		let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+		let mut next_unit = ||{
+			state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+			(state >> 40) as f32 / (1_u64 << 24) as f32
+		};
+		(0..count)
+			.map(
+				|index|{
+					let unit_position = Vec3A::new(next_unit(), next_unit(), next_unit());
+					let position = if index % 4 == 0 { unit_position * 2.0 - 1.0 } else { unit_position * 0.2 + Vec3A::new(0.5, -0.7, 0.3) };
+					ColoredPoint{ position, color: unit_position }
+				}
+			)
+			.collect()
+	}
+
+	#[test]
+	fn test_point_cloud_statistics_and_adaptivity(){
+		let points = generate_points(4000);
+		let root_voxel = VolumetricCube::new(Vec3A::ZERO, 1.0);
+		let octree = SpatialSparseOctree::<PointStatistics>::from_point_cloud(root_voxel, &points, MAX_DEPTH, MIN_POINTS, &mut PointStatisticsReducer).unwrap();
+
+		let root_statistics = octree.octants().get_octant(&octree.get_root_id()).unwrap();
+		let expected_root = PointStatisticsReducer.reduce_points(&points.iter().collect::<Vec<_>>());
+		assert_eq!(root_statistics.count, points.len());
+		assert!((root_statistics.centroid - expected_root.centroid).length() < 1e-4);
+		assert!((root_statistics.color_average - expected_root.color_average).length() < 1e-4);
+		assert_eq!((root_statistics.bounds_min, root_statistics.bounds_max), (expected_root.bounds_min, expected_root.bounds_max));
+
+		let mut deepest_dense: Depth = 0;
+		let mut deepest_sparse: Depth = 0;
+		octree.depth_first_search_from_root(
+			&mut |depth, octant_id: &MortonOctantId, voxel: &VolumetricCube|{
+				let statistics = octree.octants().get_octant(octant_id).unwrap();
+				// all points of octant are inside of its voxel
+				assert!(statistics.count > 0);
+				assert!(voxel.contains_point(statistics.bounds_min) && voxel.contains_point(statistics.bounds_max));
+				assert!(voxel.contains_point(statistics.centroid));
+
+				let children: Vec<MortonOctantId> = octree.octants().get_existing_children(octant_id).map(|children| children.into_iter().flatten().collect()).unwrap_or_default();
+				if children.is_empty() {
+					assert!(depth == MAX_DEPTH || statistics.count < MIN_POINTS);
+					let expected_count = points.iter().filter(|point| voxel.contains_point(point.position)).count();
+					assert_eq!(statistics.count, expected_count);
+				}
+				else {
+					assert!(statistics.count >= MIN_POINTS);
+					let children_count: usize = children.iter().map(|child_id| octree.octants().get_octant(child_id).unwrap().count).sum();
+					assert_eq!(children_count, statistics.count);
+				}
+
+				if voxel.contains_point(Vec3A::new(0.6, -0.6, 0.4)) {
+					deepest_dense = deepest_dense.max(depth);
+				}
+				if voxel.contains_point(Vec3A::new(-0.6, 0.6, -0.6)) {
+					deepest_sparse = deepest_sparse.max(depth);
+				}
+				SearchControlFlow::Continue
+			}
+		).unwrap();
+		assert!(deepest_dense > deepest_sparse);
+	}
+
+	#[test]
+	fn test_point_cloud_edge_cases(){
+		let root_voxel = VolumetricCube::new(Vec3A::ZERO, 1.0);
+		let outside_points = [Vec3A::splat(2.0), Vec3A::new(0.1, 0.2, 0.3)];
+		let octree = SpatialSparseOctree::<PointStatistics>::from_point_cloud(root_voxel, &outside_points, MAX_DEPTH, 1, &mut PointStatisticsReducer).unwrap();
+		let root_statistics = octree.octants().get_octant(&octree.get_root_id()).unwrap();
+		assert_eq!(root_statistics.count, 1);
+		assert_eq!(root_statistics.centroid, Vec3A::new(0.1, 0.2, 0.3));
+
+		let empty: [Vec3A; 0] = [];
+		let empty_octree = SpatialSparseOctree::<PointStatistics>::from_point_cloud(root_voxel, &empty, MAX_DEPTH, 1, &mut PointStatisticsReducer).unwrap();
+		assert_eq!(*empty_octree.octants().get_octant(&empty_octree.get_root_id()).unwrap(), PointStatistics::default());
+
+		let too_deep = SpatialSparseOctree::<PointStatistics>::from_point_cloud(root_voxel, &empty, 40, 1, &mut PointStatisticsReducer);
+		assert!(matches!(too_deep, Err(StorageError::OverMaxDepth(_))));
+	}
+}
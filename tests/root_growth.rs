@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod tests{
+	use glam::{Vec3, Vec3A};
+
+	use modsvo::{
+		morton_based_storage::morton_octant_id::MortonOctantId,
+		octant_meta::OctantPlacement,
+		octant_storage_trait::{ModifiableOctantStorage, OctantStorage, StorageError},
+		octree_base::SearchControlFlow,
+		storage_validation::validate,
+		voxel_trait::Voxel,
+		voxels::voxel_cube::VolumetricCube,
+		SpatialSparseOctree, SpatialSparseOctreePersistent
+	};
+
+	/// Center, half extent and data of every octant, sorted so trees with different ids can be compared.
+	fn collect_voxels(octree: &SpatialSparseOctree<u32>) -> Vec<([i32; 3], i32, u32)> {
+		let mut voxels: Vec<([i32; 3], i32, u32)> = Vec::new();
+		octree.depth_first_search_from_root(
+			&mut |_, octant_id: &MortonOctantId, voxel: &VolumetricCube|{
+				let center = (voxel.center() * 1000.0).round().to_array().map(|axis| axis as i32);
+				voxels.push((center, (voxel.half_extent() * 1000.0).round() as i32, *octree.octants().get_octant(octant_id).unwrap()));
+				SearchControlFlow::Continue
+			}
+		).unwrap();
+		voxels.sort();
+		voxels
+	}
+
+	fn make_octree() -> SpatialSparseOctree<u32> {
+		let mut octree = SpatialSparseOctree::<u32>::new_with_root(VolumetricCube::new(Vec3A::ZERO, 1.0), 0);
+		let root_id = octree.get_root_id();
+		let children = octree.octants_mut().subdivide(&root_id, |placement| 10 + placement as u32).unwrap();
+		octree.octants_mut().subdivide(&children[5], |placement| 20 + placement as u32).unwrap();
+		octree
+	}
+
+	#[test]
+	fn test_expand_in_direction(){
+		let mut cube = VolumetricCube::new(Vec3A::ZERO, 1.0);
+		let placement = cube.expand_in_direction(Vec3::new(-3.0, 2.0, -0.5));
+		assert_eq!(placement, OctantPlacement::LOWER_TOP_RIGHT);
+		assert_eq!(cube.center(), Vec3A::new(-1.0, 1.0, -1.0));
+		assert_eq!(cube.half_extent(), 2.0);
+		let old_cube = cube.make_sub_voxel(placement);
+		assert_eq!((old_cube.center(), old_cube.half_extent()), (Vec3A::ZERO, 1.0));
+	}
+
+	#[test]
+	fn test_grow_root_keeps_octants_in_place(){
+		let mut octree = make_octree();
+		let voxels_before = collect_voxels(&octree);
+		let placement = octree.grow_root(Vec3A::new(5.0, -5.0, 5.0), 99).unwrap();
+		assert_eq!(placement, OctantPlacement::UPPER_BOTTOM_LEFT);
+		assert_eq!(octree.get_root_voxel().half_extent(), 2.0);
+
+		// old root moved under new root and its ids were rewritten
+		let old_root_id = MortonOctantId::ROOT_OCTANT_ID.child_id_by_placement(placement);
+		assert_eq!(octree.octants().get_octant(&old_root_id), Some(&0));
+		let old_root_voxel = octree.get_voxel_by_id(&old_root_id).unwrap();
+		assert_eq!((old_root_voxel.center(), old_root_voxel.half_extent()), (Vec3A::ZERO, 1.0));
+		let moved_id = old_root_id.child_id_by_placement(OctantPlacement::LOWER_TOP_RIGHT).child_id_by_placement(OctantPlacement::UPPER_TOP_LEFT);
+		assert_eq!(octree.octants().get_octant(&moved_id), Some(&23));
+
+		let mut voxels_after = collect_voxels(&octree);
+		voxels_after.retain(|(_, _, data)| *data != 99);
+		assert_eq!(voxels_after, voxels_before);
+		assert_eq!(validate(octree.octants()), []);
+	}
+
+	#[test]
+	fn test_insert_at_point_outside_root(){
+		let mut octree = make_octree();
+		let point = Vec3A::new(-6.5, 0.5, 3.5);
+		let octant_id = octree.insert_at_point(point, 1, 7, |_| 1).unwrap();
+		assert_eq!(octree.get_root_voxel().half_extent(), 4.0);
+		assert_eq!(octant_id.compute_depth(), 3);
+		let voxel = octree.get_voxel_by_id(&octant_id).unwrap();
+		assert!(voxel.contains_point(point));
+		assert_eq!(voxel.half_extent(), 0.5);
+		assert_eq!(octree.octants().get_octant(&octant_id), Some(&7));
+
+		// inserting inside doesn't grow
+		let inside_id = octree.insert_at_point(Vec3A::new(0.5, 0.5, 0.5), 3, 8, |_| 1).unwrap();
+		assert_eq!(octree.get_root_voxel().half_extent(), 4.0);
+		assert_eq!(octree.get_voxel_by_id(&inside_id).unwrap().half_extent(), 0.5);
+		assert_eq!(validate(octree.octants()), []);
+	}
+
+	#[test]
+	fn test_shrink_to_fit(){
+		let mut octree = make_octree();
+		let voxels_before = collect_voxels(&octree);
+		octree.grow_to_contain(Vec3A::new(10.0, 10.0, 10.0), |_| 99).unwrap();
+		assert_eq!(octree.get_root_voxel().half_extent(), 8.0);
+
+		assert_eq!(octree.shrink_to_fit().unwrap(), 3);
+		assert_eq!((octree.get_root_voxel().center(), octree.get_root_voxel().half_extent()), (Vec3A::ZERO, 1.0));
+		assert_eq!(collect_voxels(&octree), voxels_before);
+		// root with many children stays
+		assert_eq!(octree.shrink_to_fit().unwrap(), 0);
+	}
+
+	#[test]
+	fn test_grow_over_max_depth(){
+		let mut octree = SpatialSparseOctreePersistent::<u32>::new_with_root(VolumetricCube::new(Vec3A::ZERO, 1.0), 0);
+		let mut octant_id = octree.get_root_id();
+		for _ in 0..octree.octants().get_max_depth() {
+			octant_id = octree.octants_mut().insert_octant(&octant_id, OctantPlacement::UPPER_TOP_RIGHT, 1).unwrap().0;
+		}
+		assert!(matches!(octree.grow_root(Vec3A::splat(2.0), 0), Err(StorageError::OverMaxDepth(_))));
+		assert_eq!(octree.get_root_voxel().half_extent(), 1.0);
+		assert_eq!(octree.octants().get_octant(&octant_id), Some(&1));
+	}
+
+	#[test]
+	fn test_insert_over_max_depth_doesnt_grow(){
+		let mut octree = make_octree();
+		let voxels_before = collect_voxels(&octree);
+		let max_depth = octree.octants().get_max_depth();
+		let result = octree.insert_at_point(Vec3A::new(3.0, 0.5, 0.5), max_depth, 7, |_| 1);
+		assert!(matches!(result, Err(StorageError::OverMaxDepth(_))));
+		assert_eq!(octree.get_root_voxel().half_extent(), 1.0);
+		assert_eq!(collect_voxels(&octree), voxels_before);
+
+		// same depth inside of root fits
+		assert!(octree.insert_at_point(Vec3A::new(0.5, 0.5, 0.5), max_depth, 7, |_| 1).is_ok());
+	}
+}
@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod tests{
+	use glam::Vec3A;
+
+	use modsvo::{
+		octant_storage_trait::{OctantStorage, StorageError},
+		octree_base::SearchControlFlow,
+		sdf::{self, SdfRegion},
+		voxels::voxel_cube::VolumetricCube,
+		SpatialSparseOctree
+	};
+
+	const MAX_DEPTH: u8 = 5;
+
+	#[test]
+	fn test_primitives_and_combinators(){
+		let unit_sphere = sdf::sphere(Vec3A::ZERO, 1.0);
+		assert_eq!(unit_sphere(Vec3A::new(2.0, 0.0, 0.0)), 1.0);
+		assert_eq!(unit_sphere(Vec3A::ZERO), -1.0);
+
+		let unit_box = sdf::cuboid(Vec3A::ZERO, Vec3A::ONE);
+		assert_eq!(unit_box(Vec3A::new(0.0, 3.0, 0.0)), 2.0);
+		assert_eq!(unit_box(Vec3A::new(0.5, 0.0, 0.0)), -0.5);
+		assert!((unit_box(Vec3A::new(2.0, 2.0, 1.0)) - 2.0_f32.sqrt()).abs() < 1e-6);
+
+		let floor = sdf::plane(Vec3A::new(0.0, -1.0, 0.0), Vec3A::new(0.0, 2.0, 0.0));
+		assert_eq!(floor(Vec3A::new(5.0, 1.0, -3.0)), 2.0);
+
+		let far_sphere = sdf::sphere(Vec3A::new(3.0, 0.0, 0.0), 1.0);
+		let union = sdf::union(unit_sphere.clone(), far_sphere.clone());
+		assert!(union(Vec3A::ZERO) < 0.0 && union(Vec3A::new(3.0, 0.0, 0.0)) < 0.0);
+		assert!(union(Vec3A::new(1.5, 0.0, 0.0)) > 0.0);
+
+		let intersection = sdf::intersect(unit_sphere.clone(), unit_box.clone());
+		assert_eq!(intersection(Vec3A::new(0.0, 1.5, 0.0)), 0.5);
+
+		let hollow = sdf::subtract(unit_box.clone(), unit_sphere.clone());
+		assert!(hollow(Vec3A::ZERO) > 0.0);
+		assert!(hollow(Vec3A::new(0.95, 0.95, 0.95)) < 0.0);
+
+		let blended = sdf::smooth_union(unit_sphere.clone(), far_sphere.clone(), 1.0);
+		let seam = Vec3A::new(1.5, 0.0, 0.0);
+		assert!(blended(seam) < union(seam));
+		assert_eq!(sdf::smooth_union(unit_sphere, far_sphere, 0.0)(seam), union(seam));
+	}
+
+	#[test]
+	fn test_generate_from_sdf_classifies_regions(){
+		let shape = sdf::smooth_union(
+			sdf::sphere(Vec3A::new(-0.3, 0.0, 0.0), 0.4),
+			sdf::cuboid(Vec3A::new(0.3, 0.1, 0.0), Vec3A::new(0.2, 0.3, 0.25)),
+			0.1
+		);
+		let root_voxel = VolumetricCube::new(Vec3A::ZERO, 1.0);
+		let octree = SpatialSparseOctree::<Option<SdfRegion>>::generate_from_sdf(root_voxel, shape.clone(), MAX_DEPTH, 0.0, |region, _| Some(region)).unwrap();
+
+		let mut counts = [0_usize; 3];
+		octree.depth_first_search_from_root(
+			&mut |depth, octant_id, voxel: &VolumetricCube|{
+				let region = octree.octants().get_octant(octant_id).unwrap().unwrap();
+				let has_children = octree.octants().get_existing_children(octant_id).map(|children| children.iter().any(Option::is_some)).unwrap_or(false);
+				assert_eq!(region, SdfRegion::classify(voxel, shape(voxel.center())));
+				// only surface octants above max depth are subdivided
+				assert_eq!(has_children, region == SdfRegion::Surface && depth < MAX_DEPTH);
+				counts[region as usize] += 1;
+				SearchControlFlow::Continue
+			}
+		).unwrap();
+		assert!(counts.iter().all(|&count| count > 0));
+	}
+
+	#[test]
+	fn test_generate_from_sdf_tolerance_and_depth_limits(){
+		let root_voxel = VolumetricCube::new(Vec3A::ZERO, 1.0);
+		let shape = sdf::plane(Vec3A::new(0.0, 0.1, 0.0), Vec3A::Y);
+
+		let count_octants = |tolerance: f32|{
+			let octree = SpatialSparseOctree::<u8>::generate_from_sdf(root_voxel, shape.clone(), MAX_DEPTH, tolerance, |region, _| region as u8).unwrap();
+			let mut count: usize = 0;
+			octree.depth_first_search_from_root(&mut |_, _, _|{ count += 1; SearchControlFlow::Continue }).unwrap();
+			count
+		};
+		// bounding radius of octants at depth 2 is below 0.5
+		assert!(count_octants(0.5) < count_octants(0.0));
+		assert_eq!(count_octants(10.0), 1);
+
+		let too_deep = SpatialSparseOctree::<u8>::generate_from_sdf(root_voxel, shape, 200, 0.0, |region, _| region as u8);
+		assert!(matches!(too_deep, Err(StorageError::OverMaxDepth(_))));
+	}
+}
@@ -0,0 +1,154 @@
+#[cfg(test)]
+mod tests{
+	use modsvo::{
+		morton_based_storage::{hashed_octant_storage::HashedOctantStorage, morton_octant_id::{MortonOctantId, MortonParentIdIterator}},
+		octant_meta::OctantPlacement,
+		octant_storage_trait::{ModifiableOctantStorage, OctantStorage, StorageResult},
+		storage_validation::{validate, validate_with_octant_ids, StorageViolation},
+		Depth, SparseOctree, SparseOctreePersistent, SparseOctreeTransactional
+	};
+
+	#[derive(Clone, Copy, PartialEq)]
+	enum Fault {
+		None,
+		ReversedChildren,
+		WrongDepth,
+		WrongWhichChild
+	}
+
+	/// Hashed storage which breaks one rule of `OctantStorage`.
+	struct FaultyStorage {
+		storage: HashedOctantStorage<u32>,
+		fault: Fault
+	}
+
+	impl OctantStorage for FaultyStorage {
+		type OctantId = MortonOctantId;
+		type ParentIdIterator = MortonParentIdIterator;
+		type Data = u32;
+
+		fn get_root_id(&self) -> Self::OctantId {
+			self.storage.get_root_id()
+		}
+
+		fn get_max_depth(&self) -> Depth {
+			self.storage.get_max_depth()
+		}
+
+		fn get_octant_depth(&self, octant_id: &Self::OctantId) -> Option<Depth> {
+			let depth = self.storage.get_octant_depth(octant_id)?;
+			Some(if self.fault == Fault::WrongDepth && depth == 2 { 3 } else { depth })
+		}
+
+		fn get_octant(&self, octant_id: &Self::OctantId) -> Option<&Self::Data> {
+			self.storage.get_octant(octant_id)
+		}
+
+		fn get_octant_mut(&mut self, octant_id: &Self::OctantId) -> Option<&mut Self::Data> {
+			self.storage.get_octant_mut(octant_id)
+		}
+
+		fn get_existing_child(&self, parent_id: &Self::OctantId, child_placement: OctantPlacement) -> StorageResult<Self::OctantId> {
+			self.storage.get_existing_child(parent_id, child_placement)
+		}
+
+		fn get_parent(&self, octant_id: &Self::OctantId) -> Option<Self::OctantId> {
+			self.storage.get_parent(octant_id)
+		}
+
+		fn get_ancestors_for(&self, octant_id: &Self::OctantId) -> Option<Self::ParentIdIterator> {
+			self.storage.get_ancestors_for(octant_id)
+		}
+
+		fn get_existing_children(&self, parent_id: &Self::OctantId) -> StorageResult<[Option<Self::OctantId>; OctantPlacement::OCTANTS_COUNT]> {
+			let mut children = self.storage.get_existing_children(parent_id)?;
+			if self.fault == Fault::ReversedChildren {
+				children.reverse();
+			}
+			Ok(children)
+		}
+
+		fn which_child_of(&self, parent_id: &Self::OctantId, child_id: &Self::OctantId) -> StorageResult<OctantPlacement> {
+			let placement = self.storage.which_child_of(parent_id, child_id)?;
+			Ok(if self.fault == Fault::WrongWhichChild { OctantPlacement::OCTANTS_ORDERED[7 - placement as usize] } else { placement })
+		}
+	}
+
+	fn make_storage(fault: Fault) -> FaultyStorage {
+		let mut storage = HashedOctantStorage::<u32>::new_with_root(0);
+		let children = storage.subdivide(&storage.get_root_id(), |placement| placement as u32).unwrap();
+		storage.insert_octant(&children[3], OctantPlacement::LOWER_TOP_RIGHT, 9).unwrap();
+		FaultyStorage{
+			storage,
+			fault
+		}
+	}
+
+	#[test]
+	fn test_valid_storages(){
+		assert_eq!(validate(&make_storage(Fault::None)), []);
+
+		let mut octree = SparseOctree::<u32>::new_with_root(0);
+		let root_id = octree.octants.get_root_id();
+		octree.octants.subdivide(&root_id, |placement| placement as u32).unwrap();
+		assert_eq!(octree.validate(), []);
+
+		let mut persistent = SparseOctreePersistent::<u32>::new_with_root(0);
+		let root_id = persistent.octants.get_root_id();
+		persistent.octants.subdivide(&root_id, |placement| placement as u32).unwrap();
+		assert_eq!(persistent.validate(), []);
+
+		let mut transactional = SparseOctreeTransactional::<u32>::new_with_root(0);
+		let root_id = transactional.octants.get_root_id();
+		transactional.octants.subdivide(&root_id, |placement| placement as u32).unwrap();
+		assert_eq!(transactional.validate(), []);
+	}
+
+	#[test]
+	fn test_reversed_children_order(){
+		let root_id = MortonOctantId::ROOT_OCTANT_ID;
+		let child_id = root_id.child_id_by_placement(OctantPlacement::UPPER_TOP_LEFT);
+		// every child of root is listed on opposite placement and single grand child moves from LOWER_TOP_RIGHT to UPPER_BOTTOM_LEFT
+		let expected: Vec<StorageViolation<MortonOctantId>> = OctantPlacement::OCTANTS_ORDERED.iter()
+			.map(|&placement| StorageViolation::ChildrenMismatch{parent_id: root_id, placement})
+			.chain(
+				[
+					StorageViolation::ChildrenMismatch{parent_id: child_id, placement: OctantPlacement::UPPER_BOTTOM_LEFT},
+					StorageViolation::ChildrenMismatch{parent_id: child_id, placement: OctantPlacement::LOWER_TOP_RIGHT}
+				]
+			)
+			.collect();
+		assert_eq!(validate(&make_storage(Fault::ReversedChildren)), expected);
+	}
+
+	#[test]
+	fn test_wrong_depth_and_placement(){
+		let grand_child_id = MortonOctantId::ROOT_OCTANT_ID
+			.child_id_by_placement(OctantPlacement::UPPER_TOP_LEFT)
+			.child_id_by_placement(OctantPlacement::LOWER_TOP_RIGHT);
+		assert_eq!(
+			validate(&make_storage(Fault::WrongDepth)),
+			[StorageViolation::DepthMismatch{octant_id: grand_child_id, expected: 2, found: Some(3)}]
+		);
+
+		let violations = validate(&make_storage(Fault::WrongWhichChild));
+		assert_eq!(violations.len(), 9);
+		assert!(violations.contains(&StorageViolation::PlacementMismatch{octant_id: grand_child_id, expected: OctantPlacement::LOWER_TOP_RIGHT, found: Some(OctantPlacement::UPPER_BOTTOM_LEFT)}));
+	}
+
+	#[test]
+	fn test_orphans_and_missing_root(){
+		let storage = make_storage(Fault::None);
+		let orphan_id = MortonOctantId::ROOT_OCTANT_ID
+			.child_id_by_placement(OctantPlacement::UPPER_TOP_LEFT)
+			.child_id_by_placement(OctantPlacement::UPPER_TOP_LEFT)
+			.child_id_by_placement(OctantPlacement::LOWER_BOTTOM_LEFT);
+		let octant_ids = storage.storage.iter().map(|(octant_id, _)| *octant_id).chain([orphan_id]);
+		assert_eq!(validate_with_octant_ids(&storage, octant_ids), [StorageViolation::Orphan{octant_id: orphan_id}]);
+
+		let mut storage = make_storage(Fault::None);
+		let root_id = storage.get_root_id();
+		storage.storage.remove_octant(&root_id);
+		assert_eq!(validate(&storage), [StorageViolation::MissingRoot]);
+	}
+}
@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod tests{
+	use modsvo::{
+		morton_based_storage::{hashed_octant_storage::HashedOctantStorage, morton_octant_id::MortonOctantId},
+		octant_meta::OctantPlacement,
+		octant_storage_trait::{ModifiableOctantStorage, OctantStorage},
+		transactional_octant_storage::TransactionalOctantStorage,
+		SparseOctreeTransactional
+	};
+
+	use modsvo::conformance;
+
+	fn collect_octants(storage: &HashedOctantStorage<u32>) -> Vec<(u64, u32)> {
+		let mut octants: Vec<(u64, u32)> = storage.iter()
+			.map(|(octant_id, data)| (octant_id.as_morton(), *data))
+			.collect();
+		octants.sort();
+		octants
+	}
+
+	#[test]
+	fn test_transactional_storage_interface_functions(){
+		let mut octant_storage: TransactionalOctantStorage<HashedOctantStorage<u32>> = TransactionalOctantStorage::default();
+
+		conformance::check_modifiable_octant_storage(&mut octant_storage, 3, |placement| placement as u32);
+		conformance::check_all(&mut octant_storage, |placement| placement as u32);
+	}
+
+	#[test]
+	fn test_undo_redo_transactions(){
+		let mut octree: SparseOctreeTransactional<u32> = SparseOctreeTransactional::default();
+		let root_id: MortonOctantId = octree.octants.get_root_id();
+		let initial_state = collect_octants(octree.octants.storage());
+
+		octree.octants.begin_transaction("build");
+		let children = octree.octants.subdivide(&root_id, |placement| placement as u32).unwrap();
+		let grand_children = octree.octants.subdivide(&children[3], |placement| 10 + placement as u32).unwrap();
+		*octree.octants.get_octant_mut(&grand_children[5]).unwrap() = 100;
+		octree.octants.commit_transaction();
+		let built_state = collect_octants(octree.octants.storage());
+
+		octree.octants.remove_octant(&children[3]).unwrap();
+		assert_eq!(octree.octants.undo_name(), Some("remove_octant"));
+		let removed_state = collect_octants(octree.octants.storage());
+		assert_eq!(removed_state.len(), 8);
+
+		assert!(octree.octants.undo().unwrap());
+		assert_eq!(collect_octants(octree.octants.storage()), built_state);
+
+		assert_eq!(octree.octants.undo_name(), Some("build"));
+		assert!(octree.octants.undo().unwrap());
+		assert_eq!(collect_octants(octree.octants.storage()), initial_state);
+		assert!(!octree.octants.undo().unwrap());
+
+		assert!(octree.octants.redo().unwrap());
+		assert_eq!(collect_octants(octree.octants.storage()), built_state);
+		assert!(octree.octants.redo().unwrap());
+		assert_eq!(collect_octants(octree.octants.storage()), removed_state);
+		assert!(!octree.octants.redo().unwrap());
+
+		// new edit invalidates redo history
+		octree.octants.undo().unwrap();
+		octree.octants.insert_octant(&children[0], OctantPlacement::UPPER_TOP_RIGHT, 7).unwrap();
+		assert!(!octree.octants.can_redo());
+	}
+
+	#[test]
+	fn test_undo_root_removal_and_abort(){
+		let mut storage: TransactionalOctantStorage<HashedOctantStorage<u32>> = TransactionalOctantStorage::default();
+		let root_id: MortonOctantId = storage.get_root_id();
+		storage.subdivide(&root_id, |placement| placement as u32).unwrap();
+		let subdivided_state = collect_octants(storage.storage());
+
+		let removed = storage.remove_octant_and_collect(&root_id).unwrap();
+		assert_eq!(removed.len(), OctantPlacement::OCTANTS_COUNT + 1);
+		assert_eq!(storage.get_octant(&root_id), None);
+
+		storage.undo().unwrap();
+		assert_eq!(collect_octants(storage.storage()), subdivided_state);
+
+		storage.begin_transaction("discarded");
+		storage.insert_root(42);
+		storage.remove_octant(&root_id.child_id_by_placement(OctantPlacement::LOWER_TOP_LEFT)).unwrap();
+		storage.abort_transaction().unwrap();
+		assert_eq!(collect_octants(storage.storage()), subdivided_state);
+		assert!(!storage.is_transaction_open());
+	}
+
+	#[test]
+	fn test_memory_budget(){
+		let mut storage: TransactionalOctantStorage<HashedOctantStorage<u32>> = TransactionalOctantStorage::default();
+		let root_id: MortonOctantId = storage.get_root_id();
+		storage.subdivide(&root_id, |placement| placement as u32).unwrap();
+		let single_transaction_bytes = storage.memory_usage();
+
+		storage.set_memory_budget(single_transaction_bytes * 2);
+		for value in 0..10 {
+			storage.insert_octant(&root_id, OctantPlacement::LOWER_BOTTOM_LEFT, value).unwrap();
+		}
+		assert!(storage.memory_usage() <= storage.memory_budget());
+
+		let mut undo_count = 0;
+		while storage.undo().unwrap() {
+			undo_count += 1;
+		}
+		assert!(undo_count < 11);
+	}
+}
@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests{
+	use modsvo::{
+		assert_octrees_eq,
+		morton_based_storage::morton_octant_id::MortonOctantId,
+		octant_meta::OctantPlacement,
+		octant_storage_trait::{ModifiableOctantStorage, OctantStorage},
+		tree_dump::{format_path, OctantChange},
+		SparseOctree, SparseOctreePersistent
+	};
+
+	fn make_octree() -> SparseOctree<u8> {
+		let mut octree = SparseOctree::<u8>::new_with_root(0);
+		let root_id = octree.octants.get_root_id();
+		octree.octants.insert_octant(&root_id, OctantPlacement::LOWER_BOTTOM_LEFT, 1).unwrap();
+		let (child_id, _) = octree.octants.insert_octant(&root_id, OctantPlacement::UPPER_TOP_RIGHT, 2).unwrap();
+		octree.octants.insert_octant(&child_id, OctantPlacement::LOWER_TOP_LEFT, 3).unwrap();
+		octree
+	}
+
+	#[test]
+	fn test_dump_tree(){
+		let octree = make_octree();
+		let expected = "\
+ROOT: 0
+├─ LOWER_BOTTOM_LEFT: 1
+└─ UPPER_TOP_RIGHT: 2
+   └─ LOWER_TOP_LEFT: 3
+";
+		assert_eq!(octree.dump_tree(), expected);
+
+		let without_data = octree.dump_tree_with(|&data| (data % 2 == 1).then(|| format!("odd {}", data)));
+		assert_eq!(without_data.lines().collect::<Vec<_>>(), ["ROOT", "├─ LOWER_BOTTOM_LEFT: odd 1", "└─ UPPER_TOP_RIGHT", "   └─ LOWER_TOP_LEFT: odd 3"]);
+	}
+
+	#[test]
+	fn test_diff_reports_added_removed_and_changed(){
+		let left = make_octree();
+		let mut right = make_octree();
+		let root_id = right.octants.get_root_id();
+		let removed_id = root_id.child_id_by_placement(OctantPlacement::UPPER_TOP_RIGHT);
+		right.octants.remove_octant(&removed_id);
+		*right.octants.get_octant_mut(&root_id.child_id_by_placement(OctantPlacement::LOWER_BOTTOM_LEFT)).unwrap() = 5;
+		right.octants.insert_octant(&root_id, OctantPlacement::UPPER_BOTTOM_LEFT, 6).unwrap();
+
+		let tree_diff = left.diff(&right);
+		let changes: Vec<(MortonOctantId, String, OctantChange<u8>)> = tree_diff.octants.iter()
+			.map(|octant_diff| (octant_diff.octant_id, format_path(&octant_diff.path), octant_diff.change))
+			.collect();
+		assert_eq!(
+			changes,
+			[
+				(root_id.child_id_by_placement(OctantPlacement::LOWER_BOTTOM_LEFT), "/LOWER_BOTTOM_LEFT".to_string(), OctantChange::Changed{old: &1, new: &5}),
+				(root_id.child_id_by_placement(OctantPlacement::UPPER_BOTTOM_LEFT), "/UPPER_BOTTOM_LEFT".to_string(), OctantChange::Added(&6)),
+				(removed_id, "/UPPER_TOP_RIGHT".to_string(), OctantChange::Removed(&2)),
+				(removed_id.child_id_by_placement(OctantPlacement::LOWER_TOP_LEFT), "/UPPER_TOP_RIGHT/LOWER_TOP_LEFT".to_string(), OctantChange::Removed(&3))
+			]
+		);
+		let formatted = tree_diff.to_string();
+		assert_eq!(formatted.lines().count(), 4);
+		assert!(formatted.lines().next().unwrap().starts_with("~ /LOWER_BOTTOM_LEFT"));
+		assert!(formatted.lines().next().unwrap().ends_with(": 1 -> 5"));
+	}
+
+	#[test]
+	fn test_diff_between_storages(){
+		let left = make_octree();
+		let mut right = SparseOctreePersistent::<u8>::new_with_root(0);
+		assert!(!left.diff(&right).is_empty());
+
+		let root_id = right.octants.get_root_id();
+		right.octants.insert_octant(&root_id, OctantPlacement::LOWER_BOTTOM_LEFT, 1).unwrap();
+		let (child_id, _) = right.octants.insert_octant(&root_id, OctantPlacement::UPPER_TOP_RIGHT, 2).unwrap();
+		right.octants.insert_octant(&child_id, OctantPlacement::LOWER_TOP_LEFT, 3).unwrap();
+		assert!(left.diff(&right).is_empty());
+		assert_octrees_eq!(left, right);
+	}
+
+	#[test]
+	#[should_panic(expected = "Octrees differ")]
+	fn test_assert_octrees_eq_panics(){
+		let left = make_octree();
+		let right = SparseOctree::<u8>::new_with_root(0);
+		assert_octrees_eq!(left, right);
+	}
+}
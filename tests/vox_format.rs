@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod tests{
+	use std::fs::File;
+
+	use glam::Vec3A;
+
+	use modsvo::{
+		morton_based_storage::morton_octant_id::MortonOctantId,
+		octant_storage_trait::OctantStorage,
+		vox_format::{VoxError, VoxModel},
+		SpatialSparseOctree
+	};
+
+	const STAIRS_FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/stairs_palette.vox");
+	const SINGLE_FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/single_default_palette.vox");
+
+	fn sorted_voxels(model: &VoxModel) -> Vec<([u8; 3], u8)> {
+		let mut voxels = model.voxels.clone();
+		voxels.sort();
+		voxels
+	}
+
+	#[test]
+	fn test_read_vox_fixture_into_octree(){
+		let model = VoxModel::read(&mut File::open(STAIRS_FIXTURE).unwrap()).unwrap();
+		assert_eq!(model.size, [5, 3, 4]);
+		assert_eq!(model.voxels.len(), 7);
+		assert_eq!(model.depth(), 3);
+		assert_eq!(model.color(3), Some([14, 26, 58, 255]));
+		assert_eq!(model.color(0), None);
+
+		let octree = SpatialSparseOctree::<u8>::read_vox(&mut File::open(STAIRS_FIXTURE).unwrap()).unwrap();
+		for ([x, y, z], color_index) in model.voxels.iter() {
+			let voxel_id = MortonOctantId::from_xyz(*x as u16, *y as u16, *z as u16, model.depth()).unwrap();
+			assert_eq!(octree.octants().get_octant(&voxel_id), Some(color_index));
+			// voxel is unit cube at its grid position
+			let voxel = octree.get_voxel_by_id(&voxel_id).unwrap();
+			assert_eq!(voxel.center(), Vec3A::new(*x as f32, *y as f32, *z as f32) + 0.5);
+			assert_eq!(voxel.half_extent(), 0.5);
+		}
+	}
+
+	#[test]
+	fn test_vox_round_trip(){
+		let model = VoxModel::read(&mut File::open(STAIRS_FIXTURE).unwrap()).unwrap();
+		let octree = SpatialSparseOctree::<u8>::read_vox(&mut File::open(STAIRS_FIXTURE).unwrap()).unwrap();
+
+		let exported = VoxModel::from_octree(&octree.base, model.depth(), [0, 0, 0], model.size, model.palette, |color_index| Some(*color_index)).unwrap();
+		let mut written: Vec<u8> = Vec::new();
+		exported.write(&mut written).unwrap();
+		let reread = VoxModel::read(&mut written.as_slice()).unwrap();
+		assert_eq!(reread.size, model.size);
+		assert_eq!(reread.palette, model.palette);
+		assert_eq!(sorted_voxels(&reread), sorted_voxels(&model));
+
+		// region is shifted into origin of model
+		let region = VoxModel::from_octree(&octree.base, model.depth(), [1, 0, 0], [4, 2, 1], None, |color_index| Some(*color_index)).unwrap();
+		assert_eq!(sorted_voxels(&region), vec![([0, 0, 0], 2), ([1, 1, 0], 3), ([3, 0, 0], 2)]);
+
+		let single_bytes = std::fs::read(SINGLE_FIXTURE).unwrap();
+		let single = VoxModel::read(&mut single_bytes.as_slice()).unwrap();
+		assert_eq!(single.palette, None);
+		let single_octree: SpatialSparseOctree<u8> = single.to_octree(0, |color_index, _| color_index).unwrap();
+		assert_eq!(single_octree.octants().get_octant(&single_octree.get_root_id()), Some(&42));
+		let mut single_written: Vec<u8> = Vec::new();
+		single.write(&mut single_written).unwrap();
+		assert_eq!(single_written, single_bytes);
+	}
+
+	#[test]
+	fn test_invalid_vox_files(){
+		assert!(matches!(VoxModel::read(&mut b"NOPE\x96\x00\x00\x00".as_slice()), Err(VoxError::InvalidHeader)));
+
+		let mut truncated = std::fs::read(STAIRS_FIXTURE).unwrap();
+		truncated.truncate(40);
+		assert!(VoxModel::read(&mut truncated.as_slice()).is_err());
+
+		// chunk claiming more bytes than file has is rejected without allocating its size
+		let mut oversized: Vec<u8> = b"VOX \x96\x00\x00\x00MAIN".to_vec();
+		oversized.extend_from_slice(&0_i32.to_le_bytes());
+		oversized.extend_from_slice(&i32::MAX.to_le_bytes());
+		oversized.extend_from_slice(b"SIZE");
+		assert!(matches!(VoxModel::read(&mut oversized.as_slice()), Err(VoxError::InvalidChunk(chunk_id)) if &chunk_id == b"MAIN"));
+
+		let outside = VoxModel{ size: [2, 2, 2], voxels: vec![([3, 0, 0], 1)], palette: None };
+		let mut written: Vec<u8> = Vec::new();
+		outside.write(&mut written).unwrap();
+		assert!(matches!(VoxModel::read(&mut written.as_slice()), Err(VoxError::InvalidSize)));
+	}
+}